@@ -506,7 +506,7 @@ def get_library_version() -> str:
     """Get the version of the underlying Rust library.
 
     Returns:
-        Version string (e.g., "0.1.0").
+        Version string (e.g., "0.2.0").
     """
     library = _Library()
     version = library.lib.mdacademic_version()
@@ -1,3 +1,3 @@
 """Version information for markdown-academic."""
 
-__version__ = "0.1.0"
+__version__ = "0.2.0"
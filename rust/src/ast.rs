@@ -1,6 +1,10 @@
 //! Abstract Syntax Tree definitions for the extended Markdown language.
 
-use std::collections::HashMap;
+use crate::collections::{HashMap, HashSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::Deserialize;
 
 /// A complete parsed document.
 #[derive(Debug, Clone, PartialEq)]
@@ -9,6 +13,84 @@ pub struct Document {
     pub metadata: Metadata,
     /// Document content as a sequence of blocks
     pub blocks: Vec<Block>,
+    /// 1-based source line on which each top-level block starts, parallel
+    /// to `blocks` as parsed. Passes that add or remove blocks leave it
+    /// alone, so it only describes a freshly parsed document.
+    pub block_lines: Vec<usize>,
+}
+
+impl Document {
+    /// Collect every citation key used anywhere in the document, including
+    /// nested blocks, captions, table cells, and footnotes.
+    pub fn cited_keys(&self) -> HashSet<String> {
+        let mut keys = HashSet::new();
+        collect_block_keys(&self.blocks, &mut keys);
+        for inlines in self.metadata.footnote_definitions.values() {
+            collect_inline_keys(inlines, &mut keys);
+        }
+        keys
+    }
+}
+
+fn collect_block_keys(blocks: &[Block], keys: &mut HashSet<String>) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            }
+            | Block::Heading {
+                content: inlines, ..
+            } => collect_inline_keys(inlines, keys),
+            Block::Environment {
+                content, caption, ..
+            } => {
+                collect_block_keys(content, keys);
+                if let Some(caption) = caption {
+                    collect_inline_keys(caption, keys);
+                }
+            }
+            Block::BlockQuote(blocks) | Block::Abstract(blocks) => collect_block_keys(blocks, keys),
+            Block::List { items, .. } => {
+                for item in items {
+                    collect_block_keys(&item.content, keys);
+                }
+            }
+            Block::Table {
+                headers,
+                rows,
+                caption,
+                ..
+            } => {
+                for cell in headers.iter().chain(rows.iter().flatten()) {
+                    collect_inline_keys(cell, keys);
+                }
+                if let Some(caption) = caption {
+                    collect_inline_keys(caption, keys);
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    collect_inline_keys(&item.term, keys);
+                    collect_block_keys(&item.description, keys);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_inline_keys(inlines: &[Inline], keys: &mut HashSet<String>) {
+    for inline in inlines {
+        match inline {
+            Inline::Citation(cite) => keys.extend(cite.keys.iter().cloned()),
+            _ => {
+                if let Some(inner) = inline.children() {
+                    collect_inline_keys(inner, keys);
+                }
+            }
+        }
+    }
 }
 
 /// Document metadata from TOML front matter.
@@ -16,14 +98,14 @@ pub struct Document {
 pub struct Metadata {
     /// User-defined LaTeX macros
     pub macros: HashMap<String, Macro>,
-    /// Path to bibliography file
+    /// Path to a bibliography file, a directory of `.bib` files, or a glob
     pub bibliography_path: Option<String>,
     /// Document title
     pub title: Option<String>,
     /// Document subtitle
     pub subtitle: Option<String>,
     /// Document author(s)
-    pub authors: Vec<String>,
+    pub authors: Vec<Author>,
     /// Document date
     pub date: Option<String>,
     /// Document abstract
@@ -38,6 +120,96 @@ pub struct Metadata {
     pub advisor: Option<String>,
     /// Document language
     pub lang: Option<String>,
+    /// Abbreviation definitions (`*[HTML]: HyperText Markup Language`)
+    pub abbreviations: HashMap<String, String>,
+    /// Link definitions (`[id]: https://example.com "Title"`), keyed by
+    /// normalized id (see [`crate::parser::normalize_link_id`])
+    pub link_definitions: HashMap<String, (String, Option<String>)>,
+    /// Footnote definitions (`[^id]: text`), keyed by id
+    pub footnote_definitions: HashMap<String, Vec<Inline>>,
+    /// 1-based source line of each footnote definition, keyed by id
+    pub footnote_lines: HashMap<String, usize>,
+    /// Default in-text citation format (`citation_style`)
+    pub citation_style: Option<CitationFormat>,
+    /// Deepest heading level listed in the table of contents (`toc_depth`)
+    pub toc_depth: Option<u8>,
+    /// Default numbering of equations and numbered environments
+    /// (`number_equations`)
+    pub numbering_scheme: Option<NumberingScheme>,
+    /// Bibliography keys listed without being cited (`nocite`); `"*"`
+    /// stands for every entry
+    pub nocite: Vec<String>,
+}
+
+/// How in-text citations are presented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CitationFormat {
+    /// Author and year, e.g. `[Knuth, 1984]`.
+    #[default]
+    AuthorYear,
+    /// Bibliography position, e.g. `[3]`.
+    Numeric,
+}
+
+impl CitationFormat {
+    /// Look up a format by its front-matter name (`"author-year"`, `"numeric"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "author-year" | "authoryear" => Some(CitationFormat::AuthorYear),
+            "numeric" => Some(CitationFormat::Numeric),
+            _ => None,
+        }
+    }
+}
+
+/// How equations, figures, tables and other numbered environments
+/// (theorems, lemmas, definitions, ...) are numbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NumberingScheme {
+    /// One sequence through the whole document ("(3)", "Theorem 4").
+    #[default]
+    Flat,
+    /// Restart at each heading of level `depth` or above, prefixed with that
+    /// heading's number ("(2.3)", "Figure 3.1" with `depth: 1`). Elements
+    /// before the first such heading are numbered without a prefix.
+    BySection { depth: u8 },
+}
+
+impl NumberingScheme {
+    /// Look up a scheme by its front-matter name (`"flat"`, `"per-section"`).
+    /// Per-section numbering restarts at top-level headings.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "flat" => Some(NumberingScheme::Flat),
+            "per-section" | "by-section" => Some(NumberingScheme::BySection { depth: 1 }),
+            _ => None,
+        }
+    }
+}
+
+/// A document author from front matter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Author {
+    /// Display name
+    pub name: String,
+    /// Contact email address
+    pub email: Option<String>,
+    /// Affiliated institution
+    pub institution: Option<String>,
+    /// Link for the institution
+    pub institution_url: Option<String>,
+}
+
+impl Author {
+    /// Create an author with only a name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
 }
 
 /// A user-defined macro.
@@ -55,6 +227,13 @@ pub enum Block {
     /// A paragraph of inline content
     Paragraph(Vec<Inline>),
 
+    /// A paragraph ending in an attribute block (`{#id .class}`)
+    AttributedParagraph {
+        content: Vec<Inline>,
+        id: Option<String>,
+        classes: Vec<String>,
+    },
+
     /// A heading with level (1-6), content, and optional label
     Heading {
         level: u8,
@@ -85,12 +264,18 @@ pub enum Block {
     DisplayMath {
         content: String,
         label: Option<String>,
+        /// Whether the equation takes a number (`{.unnumbered}` on a math fence turns this off)
+        numbered: bool,
     },
 
     /// A custom environment (theorem, proof, figure, etc.)
     Environment {
         kind: EnvironmentKind,
         label: Option<String>,
+        /// Optional name shown after the number ("Theorem 1 (Pythagoras)")
+        title: Option<String>,
+        /// Label of the result a proof proves (`::: proof {for=thm:main}`)
+        proves: Option<String>,
         content: Vec<Block>,
         caption: Option<Vec<Inline>>,
     },
@@ -105,6 +290,8 @@ pub enum Block {
     Table {
         headers: Vec<Vec<Inline>>,
         alignments: Vec<Alignment>,
+        /// Relative column widths (fractions summing to 1), if specified
+        widths: Option<Vec<f64>>,
         rows: Vec<Vec<Vec<Inline>>>,
         label: Option<String>,
         caption: Option<Vec<Inline>>,
@@ -116,6 +303,10 @@ pub enum Block {
     /// A page break / section break
     PageBreak,
 
+    /// A float barrier (`\clearpage`): pending figures and tables are placed
+    /// before anything that follows, which starts a new page
+    ClearPage,
+
     /// An abstract section
     Abstract(Vec<Block>),
 
@@ -153,6 +344,8 @@ pub enum EnvironmentKind {
     Figure,
     Table,
     Algorithm,
+    /// Code listing
+    Listing,
     /// Abstract environment
     Abstract,
     /// Note environment
@@ -191,6 +384,7 @@ impl EnvironmentKind {
             "figure" | "fig" => Self::Figure,
             "table" | "tab" => Self::Table,
             "algorithm" | "algo" => Self::Algorithm,
+            "listing" | "lst" => Self::Listing,
             "abstract" | "abs" => Self::Abstract,
             "note" => Self::Note,
             "warning" | "caution" => Self::Warning,
@@ -218,6 +412,7 @@ impl EnvironmentKind {
             Self::Figure => "Figure",
             Self::Table => "Table",
             Self::Algorithm => "Algorithm",
+            Self::Listing => "Listing",
             Self::Abstract => "Abstract",
             Self::Note => "Note",
             Self::Warning => "Warning",
@@ -273,9 +468,28 @@ pub enum Inline {
     /// Small caps text
     SmallCaps(Vec<Inline>),
 
+    /// Text in another language or writing direction (`[text]{lang=fr}`,
+    /// `[text]{dir=rtl}` or both)
+    Lang {
+        lang: Option<String>,
+        /// `ltr`, `rtl` or `auto`
+        dir: Option<String>,
+        content: Vec<Inline>,
+    },
+
     /// Inline code
     Code(String),
 
+    /// Keyboard input (e.g., [[Ctrl+C]])
+    Kbd(String),
+
+    /// Verbatim text (`` `*literal*`{.raw} ``): shown as plain text, never
+    /// rewritten by smart quotes, abbreviations or other passes
+    Verbatim(String),
+
+    /// An abbreviation with its expansion
+    Abbreviation { abbr: String, title: String },
+
     /// A link
     Link {
         url: String,
@@ -283,11 +497,25 @@ pub enum Inline {
         content: Vec<Inline>,
     },
 
+    /// A reference-style link (`[text][id]`, `[text][]` or `[text]`).
+    /// Resolution turns it into a [`Inline::Link`] using the document's link
+    /// definitions, or back into its source text if `id` is undefined.
+    LinkReference {
+        content: Vec<Inline>,
+        /// The link definition id, as written
+        id: String,
+        /// Source after the content (`]`, `][]` or `][id]`)
+        closing: String,
+    },
+
     /// An image
     Image {
         url: String,
+        /// Alt text flattened to plain text
         alt: String,
         title: Option<String>,
+        /// Key-value pairs from a trailing `{srcset="..." sizes="..."}`
+        attributes: Vec<(String, String)>,
     },
 
     /// Inline math
@@ -299,6 +527,8 @@ pub enum Inline {
     /// A cross-reference
     Reference {
         label: String,
+        /// Which form of the target's display text to use (`@label{bare}`)
+        form: ReferenceForm,
         /// Resolved text (filled in during resolution)
         resolved: Option<String>,
     },
@@ -312,10 +542,55 @@ pub enum Inline {
     /// A hard line break
     HardBreak,
 
+    /// A LaTeX-style tie (`Fig.~1`)
+    NonBreakingSpace,
+
     /// Raw HTML inline
     RawHtml(String),
 }
 
+impl Inline {
+    /// The inlines nested inside this one, or `None` for leaves.
+    ///
+    /// Covers every container variant, including the content of inline
+    /// footnotes, so recursive passes that descend through this method pick
+    /// up new variants automatically. Passes that treat footnotes
+    /// differently must match [`Inline::Footnote`] before calling it.
+    pub fn children(&self) -> Option<&[Inline]> {
+        match self {
+            Inline::Emphasis(inner)
+            | Inline::Strong(inner)
+            | Inline::Strikethrough(inner)
+            | Inline::Subscript(inner)
+            | Inline::Superscript(inner)
+            | Inline::SmallCaps(inner)
+            | Inline::Lang { content: inner, .. }
+            | Inline::Link { content: inner, .. }
+            | Inline::LinkReference { content: inner, .. }
+            | Inline::Footnote(FootnoteKind::Inline(inner)) => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Mutable access to the inlines nested inside this one; see
+    /// [`Inline::children`].
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Inline>> {
+        match self {
+            Inline::Emphasis(inner)
+            | Inline::Strong(inner)
+            | Inline::Strikethrough(inner)
+            | Inline::Subscript(inner)
+            | Inline::Superscript(inner)
+            | Inline::SmallCaps(inner)
+            | Inline::Lang { content: inner, .. }
+            | Inline::Link { content: inner, .. }
+            | Inline::LinkReference { content: inner, .. }
+            | Inline::Footnote(FootnoteKind::Inline(inner)) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
 /// Citation style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CitationStyle {
@@ -330,7 +605,7 @@ pub enum CitationStyle {
     YearOnly,
 }
 
-/// Citation with optional locator.
+/// Citation with optional per-key locators.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Citation {
     /// Citation keys
@@ -339,8 +614,39 @@ pub struct Citation {
     pub style: CitationStyle,
     /// Optional prefix (e.g., "see")
     pub prefix: Option<String>,
-    /// Optional suffix/locator (e.g., "p. 42")
-    pub locator: Option<String>,
+    /// Locator for each key, in key order (e.g., "p. 42"); may be shorter
+    /// than `keys` when trailing keys have none
+    pub locators: Vec<Option<String>>,
+}
+
+impl Citation {
+    /// The locator attached to the key at `index`, if any.
+    pub fn locator(&self, index: usize) -> Option<&str> {
+        self.locators.get(index)?.as_deref()
+    }
+}
+
+/// Display hint on a cross-reference, written as a `{hint}` suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferenceForm {
+    /// The label's usual display (`Section 2`, `(1)`, `Theorem 3`).
+    #[default]
+    Default,
+    /// Just the number (`@sec:intro{bare}` → `2`).
+    Bare,
+    /// The kind name and number (`@eq:x{full}` → `Equation (1)`).
+    Full,
+}
+
+impl ReferenceForm {
+    /// Look up a form by its hint name (`"bare"`, `"full"`).
+    pub fn from_hint(hint: &str) -> Option<Self> {
+        match hint {
+            "bare" => Some(ReferenceForm::Bare),
+            "full" => Some(ReferenceForm::Full),
+            _ => None,
+        }
+    }
 }
 
 /// Footnote variants.
@@ -360,12 +666,87 @@ pub struct ResolvedDocument {
     pub labels: HashMap<String, LabelInfo>,
     /// Resolved citations
     pub citations: HashMap<String, BibEntry>,
-    /// Footnote contents (id -> content)
+    /// Footnote contents: `[^id]` definitions by id, inline `^[...]` notes
+    /// by `^[N]` in order of appearance
     pub footnotes: HashMap<String, Vec<Inline>>,
     /// Section numbering
     pub section_numbers: HashMap<String, String>,
     /// Environment numbering (label -> number)
-    pub env_numbers: HashMap<String, u32>,
+    pub env_numbers: HashMap<String, String>,
+    /// Problems that did not stop resolution, such as an optional
+    /// bibliography that could not be read
+    pub warnings: Vec<String>,
+}
+
+impl ResolvedDocument {
+    /// Build a nested outline of the document's top-level headings, mirroring
+    /// the `[[toc]]` structure but returning data instead of HTML.
+    #[cfg(feature = "std")]
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        let mut roots: Vec<OutlineEntry> = Vec::new();
+        let mut stack: Vec<OutlineEntry> = Vec::new();
+
+        for block in &self.document.blocks {
+            let Block::Heading {
+                level,
+                content,
+                label,
+            } = block
+            else {
+                continue;
+            };
+
+            let entry = OutlineEntry {
+                level: *level,
+                number: label
+                    .as_ref()
+                    .and_then(|l| self.section_numbers.get(l))
+                    .cloned(),
+                text: crate::resolve::references::inlines_to_text(content),
+                html_id: label
+                    .as_deref()
+                    .map(crate::resolve::references::label_to_id),
+                children: Vec::new(),
+            };
+
+            while stack.last().is_some_and(|top| top.level >= entry.level) {
+                close_outline_entry(&mut stack, &mut roots);
+            }
+            stack.push(entry);
+        }
+
+        while !stack.is_empty() {
+            close_outline_entry(&mut stack, &mut roots);
+        }
+
+        roots
+    }
+}
+
+/// Pop the innermost open entry and attach it to its parent.
+#[cfg(feature = "std")]
+fn close_outline_entry(stack: &mut Vec<OutlineEntry>, roots: &mut Vec<OutlineEntry>) {
+    if let Some(entry) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(entry),
+            None => roots.push(entry),
+        }
+    }
+}
+
+/// A heading in the document outline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    /// Heading level (1-6)
+    pub level: u8,
+    /// Section number (e.g., "2.1"), if the heading is labeled
+    pub number: Option<String>,
+    /// Plain text of the heading
+    pub text: String,
+    /// HTML id of the heading, if it has one
+    pub html_id: Option<String>,
+    /// Subheadings
+    pub children: Vec<OutlineEntry>,
 }
 
 /// Information about a label target.
@@ -373,8 +754,27 @@ pub struct ResolvedDocument {
 pub struct LabelInfo {
     /// The display text for references (e.g., "Theorem 1", "Figure 2")
     pub display: String,
+    /// The number alone (e.g., "1", "2.3"), if the target is numbered
+    pub number: Option<String>,
+    /// The kind name with the number (e.g., "Equation (1)", "Section 2")
+    pub full: String,
     /// The HTML id for linking
     pub html_id: String,
+    /// A short preview of the target: an equation's LaTeX, or the first
+    /// sentence of an environment's text
+    pub preview: Option<String>,
+}
+
+impl LabelInfo {
+    /// The display text in the given form, falling back to the usual
+    /// display when the target has no number.
+    pub fn text(&self, form: ReferenceForm) -> &str {
+        match form {
+            ReferenceForm::Default => &self.display,
+            ReferenceForm::Bare => self.number.as_deref().unwrap_or(&self.display),
+            ReferenceForm::Full => &self.full,
+        }
+    }
 }
 
 /// A bibliography entry.
@@ -384,15 +784,72 @@ pub struct BibEntry {
     pub entry_type: String,
     pub title: Option<String>,
     pub authors: Vec<String>,
+    /// Editors, from the `editor` field
+    pub editors: Vec<String>,
     pub year: Option<String>,
+    /// Month of publication (1-12), from the `month` field
+    pub month: Option<u8>,
+    /// Day of the month, from the `day` field or a `month` like "March 15"
+    pub day: Option<u8>,
     pub journal: Option<String>,
     pub booktitle: Option<String>,
     pub publisher: Option<String>,
     pub volume: Option<String>,
     pub number: Option<String>,
     pub pages: Option<String>,
+    /// Edition, from the `edition` field (e.g. "2nd")
+    pub edition: Option<String>,
+    /// Free-form note, from the `note` field
+    pub note: Option<String>,
     pub doi: Option<String>,
     pub url: Option<String>,
     /// All other fields
     pub extra: HashMap<String, String>,
 }
+
+/// English month names, January first.
+pub(crate) const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+impl BibEntry {
+    /// English name of the publication month.
+    pub fn month_name(&self) -> Option<&'static str> {
+        MONTH_NAMES
+            .get(usize::from(self.month?).checked_sub(1)?)
+            .copied()
+    }
+
+    /// The publication date for display: the year, followed by the month
+    /// and day when known ("2020, March 15").
+    pub fn date_text(&self) -> Option<String> {
+        let year = self.year.as_deref()?;
+        Some(match (self.month_name(), self.day) {
+            (Some(month), Some(day)) => format!("{}, {} {}", year, month, day),
+            (Some(month), None) => format!("{}, {}", year, month),
+            _ => year.to_string(),
+        })
+    }
+
+    /// The editors for display, marked as such ("A. Editor (Ed.)",
+    /// "A. Editor, B. Editor (Eds.)").
+    pub fn editors_text(&self) -> Option<String> {
+        let marker = match self.editors.len() {
+            0 => return None,
+            1 => "Ed.",
+            _ => "Eds.",
+        };
+        Some(format!("{} ({})", self.editors.join(", "), marker))
+    }
+}
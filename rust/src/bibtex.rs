@@ -1,6 +1,6 @@
 //! BibTeX parser for bibliography support.
 
-use crate::ast::BibEntry;
+use crate::ast::{BibEntry, MONTH_NAMES};
 use crate::error::Result;
 use nom::{
     branch::alt,
@@ -11,8 +11,13 @@ use nom::{
 use std::collections::HashMap;
 
 /// Parse a BibTeX file and return a map of citation keys to entries.
+///
+/// As in BibTeX itself, when a key is defined more than once the first
+/// definition wins and later ones are ignored.
 pub fn parse_bibtex(input: &str) -> Result<HashMap<String, BibEntry>> {
     let mut entries = HashMap::new();
+    // `@string` macros defined so far, by lowercase name
+    let mut strings = HashMap::new();
 
     // Find all @type{...} entries
     let mut remaining = input;
@@ -26,13 +31,13 @@ pub fn parse_bibtex(input: &str) -> Result<HashMap<String, BibEntry>> {
         }
 
         if remaining.starts_with('@') {
-            match parse_entry(remaining) {
+            match parse_entry(remaining, &mut strings) {
                 Ok((rest, Some(entry))) => {
-                    entries.insert(entry.key.clone(), entry);
+                    entries.entry(entry.key.clone()).or_insert(entry);
                     remaining = rest;
                 }
                 Ok((rest, None)) => {
-                    // @comment or @preamble - skip
+                    // @comment, @preamble or @string - nothing to add
                     remaining = rest;
                 }
                 Err(_) => {
@@ -78,7 +83,10 @@ fn skip_whitespace_and_comments(input: &str) -> &str {
     s
 }
 
-fn parse_entry(input: &str) -> IResult<&str, Option<BibEntry>> {
+fn parse_entry<'a>(
+    input: &'a str,
+    strings: &mut HashMap<String, String>,
+) -> IResult<&'a str, Option<BibEntry>> {
     let (input, _) = char('@')(input)?;
     let (input, entry_type) = take_while1(|c: char| c.is_alphanumeric())(input)?;
     let (input, _) = multispace0(input)?;
@@ -86,15 +94,22 @@ fn parse_entry(input: &str) -> IResult<&str, Option<BibEntry>> {
     let entry_type_lower = entry_type.to_lowercase();
 
     // Handle special entries
-    if entry_type_lower == "comment"
-        || entry_type_lower == "preamble"
-        || entry_type_lower == "string"
-    {
+    if entry_type_lower == "comment" || entry_type_lower == "preamble" {
         // Skip to matching brace
         let (input, _) = skip_braced_content(input)?;
         return Ok((input, None));
     }
 
+    // `@string{jcp = "J. Chem. Phys."}` defines a macro for later values
+    if entry_type_lower == "string" {
+        let (input, _) = char('{')(input)?;
+        let (input, (name, value)) = parse_field(input, strings)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = char('}')(input)?;
+        strings.insert(name.to_lowercase(), value);
+        return Ok((input, None));
+    }
+
     let (input, _) = char('{')(input)?;
     let (input, _) = multispace0(input)?;
 
@@ -107,7 +122,7 @@ fn parse_entry(input: &str) -> IResult<&str, Option<BibEntry>> {
     let (input, _) = char(',')(input)?;
 
     // Parse fields
-    let (input, fields) = parse_fields(input)?;
+    let (input, fields) = parse_fields(input, strings)?;
 
     let (input, _) = multispace0(input)?;
     let (input, _) = char('}')(input)?;
@@ -138,7 +153,10 @@ fn skip_braced_content(input: &str) -> IResult<&str, ()> {
     Ok(("", ()))
 }
 
-fn parse_fields(input: &str) -> IResult<&str, HashMap<String, String>> {
+fn parse_fields<'a>(
+    input: &'a str,
+    strings: &HashMap<String, String>,
+) -> IResult<&'a str, HashMap<String, String>> {
     let mut fields = HashMap::new();
     let mut remaining = input;
 
@@ -149,7 +167,7 @@ fn parse_fields(input: &str) -> IResult<&str, HashMap<String, String>> {
             break;
         }
 
-        match parse_field(remaining) {
+        match parse_field(remaining, strings) {
             Ok((rest, (name, value))) => {
                 fields.insert(name.to_lowercase(), value);
                 remaining = rest.trim_start();
@@ -166,19 +184,55 @@ fn parse_fields(input: &str) -> IResult<&str, HashMap<String, String>> {
     Ok((remaining, fields))
 }
 
-fn parse_field(input: &str) -> IResult<&str, (String, String)> {
+fn parse_field<'a>(
+    input: &'a str,
+    strings: &HashMap<String, String>,
+) -> IResult<&'a str, (String, String)> {
     let (input, _) = multispace0(input)?;
     let (input, name) = take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '-')(input)?;
     let (input, _) = multispace0(input)?;
     let (input, _) = char('=')(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, value) = parse_value(input)?;
+    let (input, value) = parse_value(input, strings)?;
+
+    // Verbatim fields keep backslashes and braces as written
+    let value = if VERBATIM_FIELDS.contains(&name.to_lowercase().as_str()) {
+        value
+    } else {
+        clean_bibtex_value(&decode_latex_accents(&value))
+    };
 
     Ok((input, (name.to_string(), value)))
 }
 
-fn parse_value(input: &str) -> IResult<&str, String> {
-    alt((parse_braced_value, parse_quoted_value, parse_number_value)).parse(input)
+/// Fields holding identifiers or paths rather than text, where `\~` is not
+/// an accent.
+const VERBATIM_FIELDS: &[&str] = &["url", "doi", "eprint", "file"];
+
+fn parse_value<'a>(input: &'a str, strings: &HashMap<String, String>) -> IResult<&'a str, String> {
+    let (mut input, mut value) = parse_value_part(input, strings)?;
+
+    // `#` concatenates parts (`month = mar # " 15"`)
+    while let Some(rest) = input.trim_start().strip_prefix('#') {
+        let (rest, part) = parse_value_part(rest.trim_start(), strings)?;
+        value.push_str(&part);
+        input = rest;
+    }
+
+    Ok((input, value))
+}
+
+fn parse_value_part<'a>(
+    input: &'a str,
+    strings: &HashMap<String, String>,
+) -> IResult<&'a str, String> {
+    alt((
+        parse_braced_value,
+        parse_quoted_value,
+        parse_number_value,
+        |input| parse_macro_value(input, strings),
+    ))
+    .parse(input)
 }
 
 fn parse_braced_value(input: &str) -> IResult<&str, String> {
@@ -203,7 +257,7 @@ fn parse_braced_value(input: &str) -> IResult<&str, String> {
     let value = &input[..end];
     let rest = &input[end + 1..];
 
-    Ok((rest, clean_bibtex_value(value)))
+    Ok((rest, value.to_string()))
 }
 
 fn parse_quoted_value(input: &str) -> IResult<&str, String> {
@@ -231,7 +285,7 @@ fn parse_quoted_value(input: &str) -> IResult<&str, String> {
     let value = &input[..end];
     let rest = &input[end + 1..];
 
-    Ok((rest, clean_bibtex_value(value)))
+    Ok((rest, value.to_string()))
 }
 
 fn parse_number_value(input: &str) -> IResult<&str, String> {
@@ -239,38 +293,239 @@ fn parse_number_value(input: &str) -> IResult<&str, String> {
     Ok((input, value.to_string()))
 }
 
+/// A bare macro name, expanded from the `@string` definitions (names are
+/// case-insensitive), then the standard month macros (`jan` .. `dec`).
+/// Unknown macros expand to nothing.
+fn parse_macro_value<'a>(
+    input: &'a str,
+    strings: &HashMap<String, String>,
+) -> IResult<&'a str, String> {
+    let (input, name) = take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)?;
+    if let Some(value) = strings.get(&name.to_lowercase()) {
+        return Ok((input, value.clone()));
+    }
+    let month = MONTH_NAMES
+        .iter()
+        .find(|month| month[..3].eq_ignore_ascii_case(name) && name.len() == 3);
+    Ok((input, month.map_or("", |month| *month).to_string()))
+}
+
+/// Strip the braces that protect capitalization (`{DNA}`), at any depth,
+/// keeping the text they protect. Braces that belong to a LaTeX command,
+/// `{\"o}`, `\"{o}` or `{\emph{word}}`, are kept along with everything
+/// inside them.
 fn clean_bibtex_value(value: &str) -> String {
-    // Remove LaTeX braces used for capitalization preservation
     let mut result = String::with_capacity(value.len());
-    let mut depth = 0;
+    // For each open brace, whether it is kept in the output
+    let mut kept: Vec<bool> = Vec::new();
+    let mut after_command = false;
     let mut chars = value.chars().peekable();
 
     while let Some(c) = chars.next() {
         match c {
+            '\\' => {
+                // A control word (`\emph`) or control symbol (`\"`)
+                result.push(c);
+                if let Some(next) = chars.next() {
+                    result.push(next);
+                    if next.is_ascii_alphabetic() {
+                        while let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+                            result.push(letter);
+                        }
+                    }
+                }
+                after_command = true;
+                continue;
+            }
             '{' => {
-                // Check if it's a LaTeX command
-                if chars.peek() == Some(&'\\') {
+                let keep =
+                    after_command || chars.peek() == Some(&'\\') || kept.last() == Some(&true);
+                kept.push(keep);
+                if keep {
                     result.push(c);
-                    depth += 1;
-                } else {
-                    // Skip opening brace
                 }
             }
             '}' => {
-                if depth > 0 {
+                if kept.pop() == Some(true) {
                     result.push(c);
-                    depth -= 1;
                 }
-                // Skip closing brace of non-command braces
             }
             _ => result.push(c),
         }
+        after_command = false;
     }
 
     // Normalize whitespace
     result.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+/// Precomposed characters for each accent command, as base/accented pairs.
+const ACCENTS: &[(char, &str)] = &[
+    ('`', "aàeèiìoòuùAÀEÈIÌOÒUÙ"),
+    ('\'', "aáeéiíoóuúyýcćnńsśzźAÁEÉIÍOÓUÚYÝCĆNŃSŚZŹ"),
+    ('^', "aâeêiîoôuûAÂEÊIÎOÔUÛ"),
+    ('~', "aãnñoõAÃNÑOÕ"),
+    ('"', "aäeëiïoöuüyÿAÄEËIÏOÖUÜYŸ"),
+    ('=', "aāeēiīoōuūAĀEĒIĪOŌUŪ"),
+    ('.', "eėzżEĖIİZŻ"),
+    ('u', "aăgğAĂGĞ"),
+    ('H', "oőuűOŐUŰ"),
+    ('v', "cčeěnňrřsšzžCČEĚNŇRŘSŠZŽ"),
+    ('c', "cçsştţCÇSŞTŢ"),
+    ('k', "aąeęAĄEĘ"),
+    ('r', "aåuůAÅUŮ"),
+];
+
+/// Combining marks for accents on letters without a precomposed form.
+const COMBINING: &[(char, char)] = &[
+    ('`', '\u{300}'),
+    ('\'', '\u{301}'),
+    ('^', '\u{302}'),
+    ('~', '\u{303}'),
+    ('=', '\u{304}'),
+    ('u', '\u{306}'),
+    ('.', '\u{307}'),
+    ('"', '\u{308}'),
+    ('r', '\u{30A}'),
+    ('H', '\u{30B}'),
+    ('v', '\u{30C}'),
+    ('d', '\u{323}'),
+    ('c', '\u{327}'),
+    ('k', '\u{328}'),
+    ('b', '\u{331}'),
+];
+
+/// Letters written as control words (`\ss`, `\o`).
+const LETTERS: &[(&str, &str)] = &[
+    ("ss", "ß"),
+    ("o", "ø"),
+    ("O", "Ø"),
+    ("aa", "å"),
+    ("AA", "Å"),
+    ("ae", "æ"),
+    ("AE", "Æ"),
+    ("oe", "œ"),
+    ("OE", "Œ"),
+    ("l", "ł"),
+    ("L", "Ł"),
+    ("i", "ı"),
+    ("j", "ȷ"),
+];
+
+/// Replace LaTeX accent commands (`\"o`, `{\"o}`, `\H{o}`, `\c c`) and
+/// letter commands (`\ss`, `\o`, `\aa`) with the Unicode characters they
+/// stand for. Other commands, and accents with no base letter, are kept.
+fn decode_latex_accents(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('\\') {
+        result.push_str(&rest[..start]);
+        let command = &rest[start + 1..];
+        let name_len = command
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(command.len());
+        let (name, after) = match command.chars().next() {
+            Some(c) if name_len == 0 => command.split_at(c.len_utf8()),
+            _ => command.split_at(name_len),
+        };
+
+        let accent = name.chars().next().filter(|_| name.chars().count() == 1);
+        let decoded = match accent {
+            Some(accent) if COMBINING.iter().any(|(c, _)| *c == accent) => {
+                accent_argument(after).map(|(base, after)| (apply_accent(accent, base), after))
+            }
+            _ => LETTERS.iter().find(|(n, _)| *n == name).map(|(_, letter)| {
+                // An empty group or a space ends the control word
+                let after = after
+                    .strip_prefix("{}")
+                    .or_else(|| after.strip_prefix(' '))
+                    .unwrap_or(after);
+                (letter.to_string(), after)
+            }),
+        };
+
+        match decoded {
+            Some((text, after)) => {
+                result.push_str(&text);
+                rest = after;
+            }
+            None => {
+                result.push('\\');
+                result.push_str(name);
+                rest = after;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// The letter an accent applies to, `o`, `{o}` or a dotless `\i`, and the
+/// text after it.
+fn accent_argument(input: &str) -> Option<(char, &str)> {
+    let input = input.trim_start_matches(' ');
+    let (argument, after) = match input.strip_prefix('{') {
+        Some(inner) => {
+            let end = inner.find('}')?;
+            (inner[..end].trim(), &inner[end + 1..])
+        }
+        None => {
+            let len = match input.strip_prefix('\\') {
+                Some(word) => {
+                    1 + word
+                        .find(|c: char| !c.is_ascii_alphabetic())
+                        .unwrap_or(word.len())
+                }
+                None => input.chars().next()?.len_utf8(),
+            };
+            input.split_at(len)
+        }
+    };
+
+    let base = match argument {
+        "\\i" => 'i',
+        "\\j" => 'j',
+        _ => {
+            let mut chars = argument.chars();
+            let base = chars.next().filter(|c| c.is_alphabetic())?;
+            if chars.next().is_some() {
+                return None;
+            }
+            base
+        }
+    };
+    Some((base, after))
+}
+
+/// Apply an accent to a letter, preferring the precomposed character.
+fn apply_accent(accent: char, base: char) -> String {
+    let precomposed = ACCENTS
+        .iter()
+        .find(|(c, _)| *c == accent)
+        .and_then(|(_, pairs)| {
+            let mut chars = pairs.chars();
+            while let (Some(plain), Some(accented)) = (chars.next(), chars.next()) {
+                if plain == base {
+                    return Some(accented);
+                }
+            }
+            None
+        });
+    let mut text = String::new();
+    match precomposed {
+        Some(c) => text.push(c),
+        None => {
+            text.push(base);
+            if let Some((_, mark)) = COMBINING.iter().find(|(c, _)| *c == accent) {
+                text.push(*mark);
+            }
+        }
+    }
+    text
+}
+
 fn build_entry(key: &str, entry_type: &str, fields: HashMap<String, String>) -> BibEntry {
     let mut entry = BibEntry {
         key: key.to_string(),
@@ -285,9 +540,19 @@ fn build_entry(key: &str, entry_type: &str, fields: HashMap<String, String>) ->
     if let Some(v) = fields.get("author") {
         entry.authors = parse_authors(v);
     }
+    if let Some(v) = fields.get("editor") {
+        entry.editors = parse_authors(v);
+    }
     if let Some(v) = fields.get("year") {
         entry.year = Some(v.clone());
     }
+    if let Some((month, day)) = fields.get("month").and_then(|v| parse_month(v)) {
+        entry.month = Some(month);
+        entry.day = day;
+    }
+    if let Some(day) = fields.get("day").and_then(|v| v.parse().ok()) {
+        entry.day = Some(day);
+    }
     if let Some(v) = fields.get("journal") {
         entry.journal = Some(v.clone());
     }
@@ -306,6 +571,12 @@ fn build_entry(key: &str, entry_type: &str, fields: HashMap<String, String>) ->
     if let Some(v) = fields.get("pages") {
         entry.pages = Some(v.clone());
     }
+    if let Some(v) = fields.get("edition") {
+        entry.edition = Some(v.clone());
+    }
+    if let Some(v) = fields.get("note") {
+        entry.note = Some(v.clone());
+    }
     if let Some(v) = fields.get("doi") {
         entry.doi = Some(v.clone());
     }
@@ -319,13 +590,18 @@ fn build_entry(key: &str, entry_type: &str, fields: HashMap<String, String>) ->
             k.as_str(),
             "title"
                 | "author"
+                | "editor"
                 | "year"
+                | "month"
+                | "day"
                 | "journal"
                 | "booktitle"
                 | "publisher"
                 | "volume"
                 | "number"
                 | "pages"
+                | "edition"
+                | "note"
                 | "doi"
                 | "url"
         ) {
@@ -336,6 +612,33 @@ fn build_entry(key: &str, entry_type: &str, fields: HashMap<String, String>) ->
     entry
 }
 
+/// Parse a month given as a number, an abbreviation or a full name,
+/// optionally followed by a day ("3", "mar", "March 15"). Returns the month
+/// (1-12) and day.
+fn parse_month(value: &str) -> Option<(u8, Option<u8>)> {
+    let mut parts = value
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '.')
+        .filter(|part| !part.is_empty());
+    let month_part = parts.next()?;
+
+    let month = match month_part.parse::<u8>() {
+        Ok(n) if (1..=12).contains(&n) => n,
+        Ok(_) => return None,
+        Err(_) => {
+            let lower = month_part.to_lowercase();
+            let index = MONTH_NAMES
+                .iter()
+                .position(|name| lower.len() >= 3 && name.to_lowercase().starts_with(&lower))?;
+            index as u8 + 1
+        }
+    };
+    let day = parts
+        .next()
+        .and_then(|part| part.parse::<u8>().ok())
+        .filter(|day| (1..=31).contains(day));
+    Some((month, day))
+}
+
 fn parse_authors(input: &str) -> Vec<String> {
     // Authors are separated by " and "
     input
@@ -402,6 +705,83 @@ mod tests {
         assert_eq!(entries.len(), 1);
     }
 
+    #[test]
+    fn test_parse_month() {
+        let input = r#"
+@article{a, year = 2020, month = mar}
+@article{b, year = {2021}, month = {September}}
+@article{c, year = 2022, month = "11"}
+@article{d, year = 2023, month = jan # " 15"}
+"#;
+        let entries = parse_bibtex(input).unwrap();
+        assert_eq!(entries["a"].month, Some(3));
+        assert_eq!(entries["a"].month_name(), Some("March"));
+        assert_eq!(entries["a"].date_text().as_deref(), Some("2020, March"));
+        assert_eq!(entries["b"].month, Some(9));
+        assert_eq!(entries["c"].month, Some(11));
+        assert_eq!((entries["d"].month, entries["d"].day), (Some(1), Some(15)));
+        assert!(!entries["a"].extra.contains_key("month"));
+    }
+
+    #[test]
+    fn test_parse_editor_edition_note() {
+        let input = r#"
+@incollection{c,
+    author = {Jane Doe},
+    editor = {John Smith and Ann Lee},
+    title = {A Chapter},
+    booktitle = {The Handbook},
+    edition = {2nd},
+    note = {Reprinted 2010},
+    isbn = {123}
+}
+"#;
+        let entries = parse_bibtex(input).unwrap();
+        let entry = &entries["c"];
+        assert_eq!(entry.editors, ["John Smith", "Ann Lee"]);
+        assert_eq!(entry.edition.as_deref(), Some("2nd"));
+        assert_eq!(entry.note.as_deref(), Some("Reprinted 2010"));
+        assert_eq!(entry.extra.keys().collect::<Vec<_>>(), ["isbn"]);
+        assert_eq!(
+            entry.editors_text().as_deref(),
+            Some("John Smith, Ann Lee (Eds.)")
+        );
+    }
+
+    #[test]
+    fn test_string_macros() {
+        let input = r#"
+@string{jcp = "J. Chem. Phys."}
+@String{ACS = {American Chemical Society}}
+@article{a, journal = jcp, publisher = acs # " Publications", year = 2001}
+@article{b, journal = JCP # { } # "Letters", note = undefined, year = 2002}
+"#;
+        let entries = parse_bibtex(input).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries["a"].journal.as_deref(), Some("J. Chem. Phys."));
+        assert_eq!(
+            entries["a"].publisher.as_deref(),
+            Some("American Chemical Society Publications")
+        );
+        assert_eq!(
+            entries["b"].journal.as_deref(),
+            Some("J. Chem. Phys. Letters")
+        );
+        assert_eq!(entries["b"].note.as_deref(), Some(""));
+        assert_eq!(entries["b"].year.as_deref(), Some("2002"));
+    }
+
+    #[test]
+    fn test_duplicate_key_first_wins() {
+        let input = r#"
+@book{knuth1984, title = {The TeXbook}, year = {1984}}
+@book{knuth1984, title = {Duplicate}, year = {1986}}
+"#;
+        let entries = parse_bibtex(input).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries["knuth1984"].title.as_deref(), Some("The TeXbook"));
+    }
+
     #[test]
     fn test_clean_bibtex_value() {
         assert_eq!(clean_bibtex_value("{DNA} Sequencing"), "DNA Sequencing");
@@ -409,5 +789,52 @@ mod tests {
             clean_bibtex_value("The {Art} of Programming"),
             "The Art of Programming"
         );
+        assert_eq!(
+            clean_bibtex_value("{The {DNA} Sequence of {E. coli}}"),
+            "The DNA Sequence of E. coli"
+        );
+        assert_eq!(
+            clean_bibtex_value(r#"{\emph{Drosophila}} and Sch\"{o}n's {{RNA}}"#),
+            r#"{\emph{Drosophila}} and Sch\"{o}n's RNA"#
+        );
+        assert_eq!(clean_bibtex_value(r#"{\"O}zt{\"u}rk"#), r#"{\"O}zt{\"u}rk"#);
+    }
+
+    #[test]
+    fn test_verbatim_fields_not_decoded() {
+        let input = r#"
+@misc{w,
+    title = {Se{\~n}or},
+    url = {http://x.org/\~user},
+    doi = {10.1000/{ABC}\_1},
+    eprint = {math/\~0101}
+}
+"#;
+        let entries = parse_bibtex(input).unwrap();
+        let entry = &entries["w"];
+        assert_eq!(entry.title.as_deref(), Some("Señor"));
+        assert_eq!(entry.url.as_deref(), Some(r"http://x.org/\~user"));
+        assert_eq!(entry.doi.as_deref(), Some(r"10.1000/{ABC}\_1"));
+        assert_eq!(entry.extra["eprint"], r"math/\~0101");
+    }
+
+    #[test]
+    fn test_decode_latex_accents() {
+        let decode = |value: &str| clean_bibtex_value(&decode_latex_accents(value));
+        assert_eq!(decode(r#"G{\"o}del"#), "Gödel");
+        assert_eq!(decode(r"Erd{\H{o}}s"), "Erdős");
+        assert_eq!(decode(r#"Sch\"{o}n and Fran\c cois"#), "Schön and François");
+        assert_eq!(
+            decode(r#"Stra{\ss}e, \o{}st, \AA ngstr\"om"#),
+            "Straße, øst, Ångström"
+        );
+        assert_eq!(decode(r"Ma\'{\i}a and \v{S}koda"), "Maía and Škoda");
+        // Letters without a precomposed form get a combining mark
+        assert_eq!(decode(r"\d{h}"), "h\u{323}");
+        assert_eq!(decode(r"{\emph{word}} \&"), r"{\emph{word}} \&");
+
+        let entries =
+            parse_bibtex(r#"@book{g, author = "Kurt G\"{o}del and Paul Erd{\H{o}}s"}"#).unwrap();
+        assert_eq!(entries["g"].authors, ["Kurt Gödel", "Paul Erdős"]);
     }
 }
@@ -2,12 +2,23 @@
 //!
 //! Run with: cargo run --bin mda-preview --features editor
 //! Or: cargo run --bin mda-preview --features editor -- path/to/file.mda
+//! Or, to print the feature showcase as HTML without opening a window:
+//! cargo run --bin mda-preview --features editor -- --self-test
 
 use eframe::egui;
-use markdown_academic::{render, HtmlConfig, ResolveConfig};
+use markdown_academic::examples::{feature_showcase, showcase_bibliography};
+use markdown_academic::{
+    render, render_html, resolve_with_diagnostics, Diagnostic, HtmlConfig, ResolveConfig,
+};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 fn main() -> eframe::Result<()> {
+    if std::env::args().nth(1).as_deref() == Some("--self-test") {
+        self_test();
+        return Ok(());
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1200.0, 800.0])
@@ -26,6 +37,37 @@ fn main() -> eframe::Result<()> {
     )
 }
 
+/// Render the feature showcase to stdout, exiting with status 1 if any
+/// step fails.
+fn self_test() {
+    let run = || -> markdown_academic::Result<String> {
+        let config = ResolveConfig {
+            bibliography: Some(Arc::new(markdown_academic::bibtex::parse_bibtex(
+                showcase_bibliography(),
+            )?)),
+            strict_citations: true,
+            strict_references: true,
+            ..Default::default()
+        };
+        let resolved =
+            markdown_academic::resolve(markdown_academic::parse(feature_showcase())?, &config)?;
+        render_html(
+            &resolved,
+            &HtmlConfig {
+                standalone: true,
+                ..Default::default()
+            },
+        )
+    };
+    match run() {
+        Ok(html) => println!("{}", html),
+        Err(e) => {
+            eprintln!("self-test failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 struct MdaPreviewApp {
     /// The source text being edited
     source: String,
@@ -37,6 +79,10 @@ struct MdaPreviewApp {
     dirty: bool,
     /// Error message to display (if any)
     error_message: Option<String>,
+    /// Problems found in the document by the last refresh
+    diagnostics: Vec<Diagnostic>,
+    /// Source line the editor should scroll to on its next frame
+    scroll_to_line: Option<usize>,
     /// Split ratio between editor and preview
     split_ratio: f32,
     /// Show rendered HTML source instead of parsed preview
@@ -57,6 +103,8 @@ impl MdaPreviewApp {
             current_file: None,
             dirty: false,
             error_message: None,
+            diagnostics: Vec::new(),
+            scroll_to_line: None,
             split_ratio: 0.5,
             show_html_source: false,
             font_size: 14.0,
@@ -76,59 +124,7 @@ impl MdaPreviewApp {
     }
 
     fn default_content() -> String {
-        r#"+++
-title = "Untitled Document"
-author = "Author Name"
-date = "2026-02-03"
-
-[macros]
-R = "\\mathbb{R}"
-+++
-
-# Introduction {#sec:intro}
-
-Welcome to **markdown-academic**! This is a live preview editor for `.mda` files.
-
-## Mathematics {#sec:math}
-
-Inline math works like this: $E = mc^2$
-
-Display math with labels:
-
-$$
-\int_{-\infty}^{\infty} e^{-x^2} dx = \sqrt{\pi}
-$$ {#eq:gaussian}
-
-Reference equations: see @eq:gaussian.
-
-## Environments {#sec:env}
-
-::: theorem {#thm:example}
-For all $\epsilon > 0$, there exists $\delta > 0$ such that the desired property holds.
-:::
-
-::: proof
-By construction. Choose $\delta = \epsilon / 2$.
-:::
-
-## Features
-
-- **Bold** and *italic* text
-- `inline code`
-- Cross-references like @sec:intro and @thm:example
-- Citations like [@knuth1984]
-- Footnotes^[This is an inline footnote]
-
-| Column 1 | Column 2 | Column 3 |
-|----------|:--------:|---------:|
-| Left     | Center   | Right    |
-| Data     | Data     | Data     |
-
-Table: A sample table. {#tab:sample}
-
-See @tab:sample for the table.
-"#
-        .to_string()
+        feature_showcase().to_string()
     }
 
     fn refresh_preview(&mut self) {
@@ -137,13 +133,20 @@ See @tab:sample for the table.
             ..Default::default()
         };
 
-        match render(&self.source, Some(&ResolveConfig::default()), Some(&config)) {
-            Ok(html) => {
-                self.rendered_html = html;
-                self.error_message = None;
-            }
-            Err(e) => {
-                self.error_message = Some(format!("Render error: {}", e));
+        // A document that fails to resolve keeps its last preview; the
+        // failure is listed with the other diagnostics
+        let (resolved, diagnostics) =
+            resolve_with_diagnostics(&self.source, &ResolveConfig::default());
+        self.diagnostics = diagnostics;
+        if let Some(resolved) = resolved {
+            match render_html(&resolved, &config) {
+                Ok(html) => {
+                    self.rendered_html = html;
+                    self.error_message = None;
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("Render error: {}", e));
+                }
             }
         }
         self.needs_refresh = false;
@@ -387,6 +390,36 @@ impl eframe::App for MdaPreviewApp {
             });
         }
 
+        // Diagnostics panel; clicking one jumps the editor to its line
+        if !self.diagnostics.is_empty() {
+            egui::TopBottomPanel::bottom("diagnostics_panel")
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.label(
+                        egui::RichText::new(format!("{} problem(s)", self.diagnostics.len()))
+                            .strong(),
+                    );
+                    egui::ScrollArea::vertical()
+                        .id_salt("diagnostics_scroll")
+                        .max_height(120.0)
+                        .show(ui, |ui| {
+                            for diagnostic in &self.diagnostics {
+                                let text = match diagnostic.line {
+                                    Some(line) => format!("Line {}: {}", line, diagnostic.message),
+                                    None => diagnostic.message.clone(),
+                                };
+                                let response = ui.selectable_label(
+                                    false,
+                                    egui::RichText::new(text).color(egui::Color32::LIGHT_RED),
+                                );
+                                if response.clicked() && diagnostic.line.is_some() {
+                                    self.scroll_to_line = diagnostic.line;
+                                }
+                            }
+                        });
+                });
+        }
+
         // Main content area with split panels
         egui::CentralPanel::default().show(ctx, |ui| {
             let available_width = ui.available_width();
@@ -416,14 +449,35 @@ impl eframe::App for MdaPreviewApp {
                     egui::ScrollArea::vertical()
                         .id_salt("editor_scroll")
                         .show(ui, |ui| {
-                            let response = ui.add(
-                                egui::TextEdit::multiline(&mut self.source)
-                                    .font(egui::TextStyle::Monospace)
-                                    .code_editor()
-                                    .desired_width(f32::INFINITY)
-                                    .desired_rows(30)
-                                    .lock_focus(true),
-                            );
+                            let mut output = egui::TextEdit::multiline(&mut self.source)
+                                .font(egui::TextStyle::Monospace)
+                                .code_editor()
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(30)
+                                .lock_focus(true)
+                                .show(ui);
+                            let response = &output.response;
+
+                            if let Some(line) = self.scroll_to_line.take() {
+                                let index: usize = self
+                                    .source
+                                    .lines()
+                                    .take(line.saturating_sub(1))
+                                    .map(|l| l.chars().count() + 1)
+                                    .sum();
+                                let cursor = egui::text::CCursor::new(index);
+                                output
+                                    .state
+                                    .cursor
+                                    .set_char_range(Some(egui::text::CCursorRange::one(cursor)));
+                                output.state.store(ui.ctx(), response.id);
+                                response.request_focus();
+                                let rect = output
+                                    .galley
+                                    .pos_from_cursor(cursor)
+                                    .translate(output.galley_pos.to_vec2());
+                                ui.scroll_to_rect(rect, Some(egui::Align::Center));
+                            }
 
                             if response.changed() {
                                 self.dirty = true;
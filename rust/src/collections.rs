@@ -0,0 +1,10 @@
+//! Map and set types shared by the `no_std` core.
+//!
+//! With `std` these are the standard library collections; in `alloc`-only
+//! builds they come from `hashbrown`.
+
+#[cfg(feature = "std")]
+pub use std::collections::{HashMap, HashSet};
+
+#[cfg(not(feature = "std"))]
+pub use hashbrown::{HashMap, HashSet};
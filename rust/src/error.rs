@@ -1,9 +1,11 @@
 //! Error types for the markdown-latex library.
 
+use alloc::string::String;
+use alloc::vec::Vec;
 use thiserror::Error;
 
 /// Result type alias for this library.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// Main error type for the library.
 #[derive(Debug, Error)]
@@ -17,6 +19,7 @@ pub enum Error {
     #[error("Render error: {0}")]
     Render(#[from] RenderError),
 
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -30,9 +33,15 @@ pub enum ParseError {
     #[error("Invalid BibTeX: {0}")]
     BibTeX(String),
 
+    #[error("Invalid render profile: {0}")]
+    Profile(String),
+
     #[error("Syntax error at line {line}: {message}")]
     Syntax { line: usize, message: String },
 
+    #[error("Inline elements nested more than {0} levels deep")]
+    NestingTooDeep(usize),
+
     #[error("Unexpected end of input")]
     UnexpectedEof,
 
@@ -60,6 +69,9 @@ pub enum ResolutionError {
 
     #[error("Failed to read bibliography file: {0}")]
     BibliographyRead(String),
+
+    #[error("Invalid math: {}", .0.join("; "))]
+    InvalidMath(Vec<String>),
 }
 
 /// Errors that occur during rendering.
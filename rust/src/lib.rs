@@ -61,6 +61,10 @@
 //! +++
 //! ```
 //!
+//! YAML front matter between `---` lines works the same way (with the
+//! default `yaml` feature), as long as the opening `---` is the document's
+//! first line, the next line is not blank and the block is a mapping.
+//!
 //! ### Math
 //!
 //! - Inline: `$E = mc^2$`
@@ -71,11 +75,14 @@
 //! - Single: `[@knuth1984]`
 //! - Multiple: `[@knuth1984; @lamport1994]`
 //! - With locator: `[@knuth1984, p. 42]`
+//! - Per-key locators: `[@knuth1984, p. 42; @lamport1994, p. 10]`
+//! - With prefix: `[see @knuth1984, p. 42]`
 //!
 //! ### Cross-References
 //!
 //! - Define label: `# Section {#sec:intro}` or `$$ ... $$ {#eq:euler}`
 //! - Reference: `@sec:intro`, `@eq:euler`, `@thm:main`
+//! - Display hint: `@eq:euler{bare}` for just the number, `@eq:euler{full}` for "Equation (1)"
 //!
 //! ### Environments
 //!
@@ -124,17 +131,34 @@
 //! - `mathml`: Enable MathML rendering backend (requires `latex2mathml` crate)
 //! - `wasm`: Enable WebAssembly bindings (requires `wasm-bindgen`)
 //! - `pdf`: Enable PDF output (requires `genpdf` crate)
+//! - `std` (default): Everything above the parser: resolution, rendering, and FFI
+//! - `alloc`: With `default-features = false`, build only the `parser` and `ast`
+//!   modules for `no_std` targets (maps come from `hashbrown`)
+
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("either the `std` or the `alloc` feature must be enabled");
+
+extern crate alloc;
 
 // Re-export main types and functions for public API
 pub mod ast;
+#[cfg(feature = "std")]
 pub mod bibtex;
+mod collections;
 pub mod error;
+pub mod examples;
 pub mod parser;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "std")]
 pub mod render;
+#[cfg(feature = "std")]
 pub mod resolve;
 
 // FFI module (only for non-WASM builds)
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
 pub mod ffi;
 
 // WASM module (only with feature)
@@ -142,11 +166,19 @@ pub mod ffi;
 pub mod wasm;
 
 // Convenience re-exports
-pub use ast::{Block, Document, Inline, ResolvedDocument};
+pub use ast::{Block, CitationFormat, Document, Inline, OutlineEntry, ResolvedDocument};
 pub use error::{Error, ParseError, RenderError, ResolutionError, Result};
-pub use parser::parse;
-pub use render::{render_html, HtmlConfig, MathBackend};
-pub use resolve::{resolve, ResolveConfig};
+pub use parser::{parse, parse_with_options, ParseOptions};
+#[cfg(feature = "std")]
+pub use profile::RenderProfile;
+#[cfg(feature = "std")]
+pub use render::{
+    render_html, render_html_with_index, BibliographySort, CitationHook, CodeOverflow,
+    EquationNumberSide, FootnoteStyle, HtmlConfig, MathBackend, RenderIndex, SlugMode,
+    TextDirection, UnresolvedReferenceStyle,
+};
+#[cfg(feature = "std")]
+pub use resolve::{resolve, resolve_with_diagnostics, Diagnostic, ResolveConfig};
 
 // PDF exports (feature-gated)
 #[cfg(feature = "pdf")]
@@ -155,6 +187,8 @@ pub use render::{render_pdf, render_pdf_to_file, PageMargins, PaperSize, PdfConf
 /// Parse, resolve, and render Markdown to HTML in one step.
 ///
 /// This is a convenience function that combines `parse`, `resolve`, and `render_html`.
+/// An empty document renders to `""` in fragment mode and to a complete HTML
+/// document with an empty body in standalone mode.
 ///
 /// # Example
 ///
@@ -164,6 +198,7 @@ pub use render::{render_pdf, render_pdf_to_file, PageMargins, PaperSize, PdfConf
 /// let html = render("# Hello *world*", None, None).unwrap();
 /// assert!(html.contains("<h1>"));
 /// ```
+#[cfg(feature = "std")]
 pub fn render(
     input: &str,
     resolve_config: Option<&ResolveConfig>,
@@ -222,7 +257,7 @@ pub fn render_to_pdf_file(
     render_pdf_to_file(&resolved, pdf_config.unwrap_or(&PdfConfig::default()), path)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -254,6 +289,24 @@ As shown in @thm:main, this is true.
         assert!(html.contains("theorem"));
     }
 
+    #[test]
+    fn test_empty_and_whitespace_input() {
+        for input in ["", "   ", "\n\n\t\n  \n"] {
+            let doc = parse(input).unwrap();
+            assert!(doc.blocks.is_empty());
+            assert_eq!(render(input, None, None).unwrap(), "");
+
+            let standalone = HtmlConfig {
+                standalone: true,
+                ..Default::default()
+            };
+            let html = render(input, None, Some(&standalone)).unwrap();
+            assert!(html.starts_with("<!DOCTYPE html>"));
+            assert!(html.contains("<article class=\"mdlatex-document\">\n</article>"));
+            assert!(html.ends_with("</html>"));
+        }
+    }
+
     #[test]
     fn test_simple_markdown() {
         let input = "# Hello\n\n**Bold** and *italic* text.";
@@ -313,3 +366,17 @@ As shown in @thm:main, this is true.
         assert!(html.contains("<td>"));
     }
 }
+
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_without_std() {
+        let input = "+++\ntitle = \"Core\"\n[macros]\nR = \"\\\\mathbb{R}\"\n+++\n\n# Intro {#sec:intro}\n\nSee @sec:intro.";
+        let doc = parse(input).unwrap();
+        assert_eq!(doc.metadata.title.as_deref(), Some("Core"));
+        assert!(doc.metadata.macros.contains_key("R"));
+        assert!(matches!(doc.blocks[0], Block::Heading { .. }));
+    }
+}
@@ -2,14 +2,35 @@
 
 use crate::ast::{Alignment, Block, DescriptionItem, EnvironmentKind, ListItem};
 use crate::error::Result;
-use crate::parser::inline::parse_inlines;
+use crate::parser::inline::parse_inlines_with_options;
 use crate::parser::lexer::{
-    environment_start, fenced_code_start, heading, list_item_marker, thematic_break, ListMarker,
-    Token,
+    environment_start, fenced_code_start, find_math_close, heading, inline_math, list_item_marker,
+    thematic_break, ListMarker, Token,
 };
+use crate::parser::{FenceTracker, ParseOptions};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 /// Parse all blocks from content.
 pub fn parse_blocks(input: &str) -> Result<Vec<Block>> {
+    parse_blocks_with_options(input, &ParseOptions::default())
+}
+
+/// Parse all blocks from content with the given options.
+pub fn parse_blocks_with_options(input: &str, options: &ParseOptions) -> Result<Vec<Block>> {
+    parse_blocks_with_lines(input, options).map(|(blocks, _)| blocks)
+}
+
+/// Parse all blocks, also returning the 0-based line of `input` on which
+/// each one starts.
+pub(crate) fn parse_blocks_with_lines(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<(Vec<Block>, Vec<usize>)> {
     let mut blocks = Vec::new();
+    let mut block_lines = Vec::new();
+    let input = expand_indent_tabs(input, options.tab_width);
     let lines: Vec<&str> = input.lines().collect();
     let mut i = 0;
 
@@ -24,7 +45,19 @@ pub fn parse_blocks(input: &str) -> Result<Vec<Block>> {
         }
 
         // Try parsing different block types
-        if let Some((block, consumed)) = try_parse_heading(line)? {
+        let start = i;
+        if let Some((block, consumed)) = try_parse_indented_code(&lines[i..], options) {
+            blocks.push(block);
+            i += consumed;
+        } else if is_escaped_block_marker(trimmed) {
+            // `\:::` or `\[[toc]]` is text; the inline parser drops the backslash
+            let (block, consumed) = parse_paragraph(&lines[i..], options)?;
+            blocks.push(block);
+            i += consumed;
+        } else if let Some((block, consumed)) = try_parse_lonely_math(&lines, i, options) {
+            blocks.push(block);
+            i += consumed;
+        } else if let Some((block, consumed)) = try_parse_heading(line, options)? {
             blocks.push(block);
             i += consumed;
         } else if let Some((block, consumed)) = try_parse_page_break(line)? {
@@ -41,39 +74,47 @@ pub fn parse_blocks(input: &str) -> Result<Vec<Block>> {
         } else if let Some((block, consumed)) = try_parse_toc(line)? {
             blocks.push(block);
             i += consumed;
-        } else if let Some((block, consumed)) = try_parse_fenced_code(&lines[i..])? {
+        } else if let Some((block, consumed)) = try_parse_fenced_code(&lines[i..], options)? {
             blocks.push(block);
             i += consumed;
-        } else if let Some((block, consumed)) = try_parse_display_math(&lines[i..])? {
+        } else if let Some((block, consumed)) = try_parse_display_math(&lines[i..], options)? {
             blocks.push(block);
             i += consumed;
-        } else if let Some((block, consumed)) = try_parse_environment(&lines[i..])? {
+        } else if let Some((block, consumed)) = try_parse_environment(&lines[i..], options)? {
             blocks.push(block);
             i += consumed;
-        } else if let Some((block, consumed)) = try_parse_block_quote(&lines[i..])? {
+        } else if let Some((block, consumed)) = try_parse_block_quote(&lines[i..], options)? {
             blocks.push(block);
             i += consumed;
-        } else if let Some((block, consumed)) = try_parse_list(&lines[i..])? {
+        } else if let Some((block, consumed)) = try_parse_list(&lines[i..], options)? {
             blocks.push(block);
             i += consumed;
-        } else if let Some((block, consumed)) = try_parse_table(&lines[i..])? {
+        } else if let Some((block, consumed)) = try_parse_table(&lines[i..], options)? {
             blocks.push(block);
             i += consumed;
-        } else if let Some((block, consumed)) = try_parse_description_list(&lines[i..])? {
+        } else if let Some((block, consumed)) = try_parse_description_list(&lines[i..], options)? {
             blocks.push(block);
             i += consumed;
         } else {
             // Default: paragraph
-            let (block, consumed) = parse_paragraph(&lines[i..])?;
+            let (block, consumed) = parse_paragraph(&lines[i..], options)?;
             blocks.push(block);
             i += consumed;
         }
+        block_lines.resize(blocks.len(), start);
     }
 
-    Ok(blocks)
+    Ok((blocks, block_lines))
 }
 
-fn try_parse_heading(line: &str) -> Result<Option<(Block, usize)>> {
+/// Whether a (left-trimmed) line starts with a backslash-escaped block
+/// marker such as `\:::`, `\[[toc]]` or `\#`.
+fn is_escaped_block_marker(line: &str) -> bool {
+    line.strip_prefix('\\')
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_punctuation()))
+}
+
+fn try_parse_heading(line: &str, options: &ParseOptions) -> Result<Option<(Block, usize)>> {
     if !line.trim_start().starts_with('#') {
         return Ok(None);
     }
@@ -82,7 +123,7 @@ fn try_parse_heading(line: &str) -> Result<Option<(Block, usize)>> {
         Ok((_rest, Token::Heading(level, content))) => {
             // Check for label at end
             let (content, label) = extract_label(content);
-            let inlines = parse_inlines(content)?;
+            let inlines = parse_inlines_with_options(content, options)?;
             Ok(Some((
                 Block::Heading {
                     level,
@@ -114,7 +155,113 @@ fn try_parse_toc(line: &str) -> Result<Option<(Block, usize)>> {
     }
 }
 
-fn try_parse_fenced_code(lines: &[&str]) -> Result<Option<(Block, usize)>> {
+/// Expand tabs in each line's leading whitespace to the next multiple of
+/// `tab_width` columns. Fenced code is left alone.
+fn expand_indent_tabs(input: &str, tab_width: usize) -> Cow<'_, str> {
+    if !input.contains('\t') {
+        return Cow::Borrowed(input);
+    }
+
+    let tab_width = tab_width.max(1);
+    let mut result = String::with_capacity(input.len());
+    let mut fences = FenceTracker::default();
+    for line in input.split_inclusive('\n') {
+        let rest = line.trim_start_matches([' ', '\t']);
+        // Fence lines themselves are expanded, the code between them is not
+        let was_fenced = fences.open.is_some();
+        if fences.in_fence(rest) && was_fenced && fences.open.is_some() {
+            result.push_str(line);
+            continue;
+        }
+
+        let mut column = 0;
+        for c in line[..line.len() - rest.len()].chars() {
+            let width = if c == '\t' {
+                tab_width - column % tab_width
+            } else {
+                1
+            };
+            result.extend(core::iter::repeat_n(' ', width));
+            column += width;
+        }
+        result.push_str(rest);
+    }
+    Cow::Owned(result)
+}
+
+/// Parse a classic indented code block (four columns of indentation).
+fn try_parse_indented_code(lines: &[&str], options: &ParseOptions) -> Option<(Block, usize)> {
+    if !options.indented_code {
+        return None;
+    }
+
+    strip_code_indent(lines[0])?;
+
+    let mut code_lines = Vec::new();
+    let mut consumed = 0;
+    let mut i = 0;
+
+    while i < lines.len() {
+        if let Some(content) = strip_code_indent(lines[i]) {
+            code_lines.push(content);
+            i += 1;
+            consumed = i;
+        } else if lines[i].trim().is_empty() {
+            // Blank lines only belong to the block if more code follows
+            code_lines.push("");
+            i += 1;
+        } else {
+            break;
+        }
+    }
+
+    code_lines.truncate(consumed);
+    Some((
+        Block::CodeBlock {
+            language: None,
+            content: code_lines.join("\n"),
+        },
+        consumed,
+    ))
+}
+
+/// Promote a line holding only one `$...$` span, with blank lines (or the
+/// start/end of input) on both sides, to display math.
+fn try_parse_lonely_math(
+    lines: &[&str],
+    i: usize,
+    options: &ParseOptions,
+) -> Option<(Block, usize)> {
+    if !options.promote_lonely_math {
+        return None;
+    }
+
+    let blank_before = i == 0 || lines[i - 1].trim().is_empty();
+    let blank_after = lines.get(i + 1).is_none_or(|l| l.trim().is_empty());
+    if !blank_before || !blank_after {
+        return None;
+    }
+
+    let (content, label) = extract_label(lines[i].trim());
+    match inline_math(content.trim_end()) {
+        Ok(("", Token::InlineMath(math))) => Some((
+            Block::DisplayMath {
+                content: if options.trim_math { math.trim() } else { math }.to_string(),
+                label,
+                numbered: true,
+            },
+            1,
+        )),
+        _ => None,
+    }
+}
+
+/// Strip one level of code indentation (four spaces).
+fn strip_code_indent(line: &str) -> Option<&str> {
+    line.strip_prefix("    ")
+}
+
+fn try_parse_fenced_code(lines: &[&str], options: &ParseOptions) -> Result<Option<(Block, usize)>> {
     let first = lines[0].trim_start();
 
     if !first.starts_with("```") && !first.starts_with("~~~") {
@@ -130,23 +277,17 @@ fn try_parse_fenced_code(lines: &[&str]) -> Result<Option<(Block, usize)>> {
 
     match fenced_code_start(first) {
         Ok((_, Token::FencedCodeStart(lang))) => {
+            let attrs = BlockAttributes::parse(&first[fence.len()..]);
             let mut content = String::new();
+            // Unclosed fence - treat rest as code
+            let mut consumed = lines.len();
             let mut i = 1;
 
             while i < lines.len() {
                 let line = lines[i];
                 if line.trim_start().starts_with(fence) {
-                    return Ok(Some((
-                        Block::CodeBlock {
-                            language: if lang.is_empty() {
-                                None
-                            } else {
-                                Some(lang.to_string())
-                            },
-                            content,
-                        },
-                        i + 1,
-                    )));
+                    consumed = i + 1;
+                    break;
                 }
                 if !content.is_empty() {
                     content.push('\n');
@@ -155,24 +296,144 @@ fn try_parse_fenced_code(lines: &[&str]) -> Result<Option<(Block, usize)>> {
                 i += 1;
             }
 
-            // Unclosed fence - treat rest as code
+            let block = build_fenced_block(lang, &attrs, content);
+            if !matches!(block, Block::CodeBlock { .. }) {
+                return Ok(Some((block, consumed)));
+            }
+
+            // A `Listing:` line or listing attributes make a numbered listing
+            let caption_line = lines
+                .get(consumed)
+                .and_then(|line| line.trim().strip_prefix("Listing:"));
+            let (caption, label, extra_consumed) = match caption_line {
+                Some(text) => {
+                    let (text, label) = extract_label(text.trim());
+                    (Some(parse_inlines_with_options(text, options)?), label, 1)
+                }
+                None => {
+                    let caption = match attrs.get("caption") {
+                        Some(text) => Some(parse_inlines_with_options(text, options)?),
+                        None => None,
+                    };
+                    (caption, attrs.id.clone(), 0)
+                }
+            };
+            let is_listing = caption.is_some()
+                || label.as_deref().is_some_and(|l| l.starts_with("lst:"))
+                || attrs.has_class("listing");
+            if !is_listing {
+                return Ok(Some((block, consumed)));
+            }
+
             Ok(Some((
-                Block::CodeBlock {
-                    language: if lang.is_empty() {
-                        None
-                    } else {
-                        Some(lang.to_string())
-                    },
-                    content,
+                Block::Environment {
+                    kind: EnvironmentKind::Listing,
+                    label,
+                    title: None,
+                    proves: None,
+                    content: vec![block],
+                    caption,
                 },
-                lines.len(),
+                consumed + extra_consumed,
             )))
         }
         _ => Ok(None),
     }
 }
 
-fn try_parse_display_math(lines: &[&str]) -> Result<Option<(Block, usize)>> {
+/// Pandoc-style attributes on a fence info string or fenced div
+/// (`{.math #eq:x title="Name"}`).
+#[derive(Debug, Default)]
+pub(crate) struct BlockAttributes {
+    classes: Vec<String>,
+    id: Option<String>,
+    pub(crate) pairs: Vec<(String, String)>,
+}
+
+impl BlockAttributes {
+    pub(crate) fn parse(info: &str) -> Self {
+        let mut attrs = Self::default();
+
+        let Some(open) = info.find('{') else {
+            return attrs;
+        };
+        let inner = &info[open + 1..];
+        let inner = inner.rsplit_once('}').map_or(inner, |(i, _)| i);
+
+        for token in split_attribute_tokens(inner) {
+            if let Some(class) = token.strip_prefix('.') {
+                attrs.classes.push(class.to_string());
+            } else if let Some(id) = token.strip_prefix('#') {
+                attrs.id = Some(id.to_string());
+            } else if let Some((key, value)) = token.split_once('=') {
+                let value = value.trim_matches('"');
+                attrs.pairs.push((key.to_string(), value.to_string()));
+            }
+        }
+
+        attrs
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.classes.iter().any(|c| c == class)
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Split attribute text on whitespace, keeping quoted values together.
+fn split_attribute_tokens(input: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut in_quotes = false;
+
+    for (i, c) in input.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        if c.is_whitespace() && !in_quotes {
+            if let Some(s) = start.take() {
+                tokens.push(&input[s..i]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&input[s..]);
+    }
+
+    tokens
+}
+
+/// Build the block for a fenced region; `math` fences become display math.
+fn build_fenced_block(lang: &str, attrs: &BlockAttributes, content: String) -> Block {
+    if lang == "math" || attrs.has_class("math") {
+        return Block::DisplayMath {
+            content: content.trim().to_string(),
+            label: attrs.id.clone(),
+            numbered: !attrs.has_class("unnumbered") && !attrs.has_class("nonumber"),
+        };
+    }
+
+    let language = if lang.is_empty() {
+        attrs.classes.first().cloned()
+    } else {
+        Some(lang.to_string())
+    };
+
+    Block::CodeBlock { language, content }
+}
+
+fn try_parse_display_math(
+    lines: &[&str],
+    options: &ParseOptions,
+) -> Result<Option<(Block, usize)>> {
     let first = lines[0].trim_start();
 
     if !first.starts_with("$$") {
@@ -181,11 +442,24 @@ fn try_parse_display_math(lines: &[&str]) -> Result<Option<(Block, usize)>> {
 
     // Check for single-line display math
     let after_open = &first[2..];
-    if let Some(end_pos) = after_open.find("$$") {
-        let content = after_open[..end_pos].to_string();
+    let mut depth = 0;
+    if let Some(end_pos) = find_math_close(after_open, "$$", &mut depth) {
+        let content = &after_open[..end_pos];
+        let content = if options.trim_math {
+            content.trim()
+        } else {
+            content
+        };
         let rest = &after_open[end_pos + 2..];
         let label = extract_label(rest).1;
-        return Ok(Some((Block::DisplayMath { content, label }, 1)));
+        return Ok(Some((
+            Block::DisplayMath {
+                content: content.to_string(),
+                label,
+                numbered: true,
+            },
+            1,
+        )));
     }
 
     // Multi-line display math
@@ -194,7 +468,7 @@ fn try_parse_display_math(lines: &[&str]) -> Result<Option<(Block, usize)>> {
 
     while i < lines.len() {
         let line = lines[i];
-        if let Some(end_pos) = line.find("$$") {
+        if let Some(end_pos) = find_math_close(line, "$$", &mut depth) {
             content.push('\n');
             content.push_str(&line[..end_pos]);
             let rest = &line[end_pos + 2..];
@@ -203,6 +477,7 @@ fn try_parse_display_math(lines: &[&str]) -> Result<Option<(Block, usize)>> {
                 Block::DisplayMath {
                     content: content.trim().to_string(),
                     label,
+                    numbered: true,
                 },
                 i + 1,
             )));
@@ -217,79 +492,114 @@ fn try_parse_display_math(lines: &[&str]) -> Result<Option<(Block, usize)>> {
         Block::DisplayMath {
             content: content.trim().to_string(),
             label: None,
+            numbered: true,
         },
         lines.len(),
     )))
 }
 
-fn try_parse_environment(lines: &[&str]) -> Result<Option<(Block, usize)>> {
+fn try_parse_environment(lines: &[&str], options: &ParseOptions) -> Result<Option<(Block, usize)>> {
     let first = lines[0].trim_start();
 
     if !first.starts_with(":::") {
         return Ok(None);
     }
 
-    // Check for environment start (not just :::)
-    match environment_start(first) {
-        Ok((_, Token::EnvironmentStart(kind, label))) => {
-            let env_kind = EnvironmentKind::from_str(kind);
-            let mut inner_lines = Vec::new();
-            let mut i = 1;
-            let mut depth = 1;
-
-            while i < lines.len() {
-                let line = lines[i];
-                let trimmed = line.trim_start();
-
-                if trimmed == ":::" {
-                    depth -= 1;
-                    if depth == 0 {
-                        let inner_content = inner_lines.join("\n");
-                        let (content, caption) =
-                            parse_environment_content(&inner_content, &env_kind)?;
-                        return Ok(Some((
-                            Block::Environment {
-                                kind: env_kind,
-                                label: label.map(String::from),
-                                content,
-                                caption,
-                            },
-                            i + 1,
-                        )));
-                    }
-                } else if trimmed.starts_with("::: ") {
-                    depth += 1;
+    // Pandoc fenced div (`::: {.theorem #thm:x title="Name"}`) or the short
+    // form (`::: theorem {#thm:x}`), which may also carry attributes
+    let after_colons = first[3..].trim_start();
+    let (kind, label, title, proves) = if after_colons.starts_with('{') {
+        let attrs = BlockAttributes::parse(after_colons);
+        let Some(kind) = attrs.classes.first().cloned() else {
+            return Ok(None);
+        };
+        let title = environment_title(&attrs);
+        let proves = attrs.get("for").map(String::from);
+        (kind, attrs.id, title, proves)
+    } else {
+        match environment_start(first) {
+            Ok((_, Token::EnvironmentStart(kind, label))) => {
+                if after_colons.contains('{') {
+                    let attrs = BlockAttributes::parse(after_colons);
+                    let title = environment_title(&attrs);
+                    let proves = attrs.get("for").map(String::from);
+                    (kind.to_string(), attrs.id, title, proves)
+                } else {
+                    (kind.to_string(), label.map(String::from), None, None)
                 }
-
-                inner_lines.push(line);
-                i += 1;
             }
+            _ => return Ok(None),
+        }
+    };
 
-            // Unclosed environment
-            let inner_content = inner_lines.join("\n");
-            let (content, caption) = parse_environment_content(&inner_content, &env_kind)?;
-            Ok(Some((
-                Block::Environment {
-                    kind: env_kind,
-                    label: label.map(String::from),
-                    content,
-                    caption,
-                },
-                lines.len(),
-            )))
+    let env_kind = EnvironmentKind::from_str(&kind);
+    let mut inner_lines = Vec::new();
+    // Unclosed environment - take the rest
+    let mut consumed = lines.len();
+    let mut i = 1;
+    let mut depth = 1;
+    // Fence of a code block being skipped, so its `:::` lines stay code
+    let mut code_fence: Option<&str> = None;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if let Some(fence) = code_fence {
+            if trimmed.starts_with(fence) {
+                code_fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            code_fence = Some(&trimmed[..3]);
+        } else if trimmed == ":::" {
+            depth -= 1;
+            if depth == 0 {
+                consumed = i + 1;
+                break;
+            }
+        } else if trimmed.starts_with("::: ") || trimmed.starts_with(":::{") {
+            depth += 1;
         }
-        _ => Ok(None),
+
+        inner_lines.push(line);
+        i += 1;
     }
+
+    let inner_content = inner_lines.join("\n");
+    let (content, caption) = parse_environment_content(&inner_content, &env_kind, options)?;
+    Ok(Some((
+        Block::Environment {
+            kind: env_kind,
+            label,
+            title,
+            proves,
+            content,
+            caption,
+        },
+        consumed,
+    )))
+}
+
+/// The environment's name from a `title` or `name` attribute.
+fn environment_title(attrs: &BlockAttributes) -> Option<String> {
+    attrs
+        .get("title")
+        .or_else(|| attrs.get("name"))
+        .map(String::from)
 }
 
 fn parse_environment_content(
     content: &str,
     kind: &EnvironmentKind,
+    options: &ParseOptions,
 ) -> Result<(Vec<Block>, Option<Vec<crate::ast::Inline>>)> {
     // For figures/tables, look for a caption at the end
-    let blocks = parse_blocks(content)?;
+    let blocks = parse_blocks_with_options(content, options)?;
 
-    if matches!(kind, EnvironmentKind::Figure | EnvironmentKind::Table) {
+    if matches!(
+        kind,
+        EnvironmentKind::Figure | EnvironmentKind::Table | EnvironmentKind::Listing
+    ) {
         // Check if last block is a paragraph that looks like a caption
         if let Some(Block::Paragraph(inlines)) = blocks.last() {
             if blocks.len() > 1 {
@@ -303,7 +613,7 @@ fn parse_environment_content(
     Ok((blocks, None))
 }
 
-fn try_parse_block_quote(lines: &[&str]) -> Result<Option<(Block, usize)>> {
+fn try_parse_block_quote(lines: &[&str], options: &ParseOptions) -> Result<Option<(Block, usize)>> {
     let first = lines[0].trim_start();
 
     if !first.starts_with('>') {
@@ -329,18 +639,25 @@ fn try_parse_block_quote(lines: &[&str]) -> Result<Option<(Block, usize)>> {
             // Blank line within quote
             quote_lines.push("");
             i += 1;
+        } else if !trimmed.is_empty()
+            && !interrupts_paragraph(trimmed)
+            && quote_lines.last().is_some_and(|l| continues_paragraph(l))
+        {
+            // Lazy continuation of a paragraph inside the quote
+            quote_lines.push(trimmed);
+            i += 1;
         } else {
             break;
         }
     }
 
     let inner_content = quote_lines.join("\n");
-    let inner_blocks = parse_blocks(&inner_content)?;
+    let inner_blocks = parse_blocks_with_options(&inner_content, options)?;
 
     Ok(Some((Block::BlockQuote(inner_blocks), i)))
 }
 
-fn try_parse_list(lines: &[&str]) -> Result<Option<(Block, usize)>> {
+fn try_parse_list(lines: &[&str], options: &ParseOptions) -> Result<Option<(Block, usize)>> {
     let first = lines[0];
     let trimmed = first.trim_start();
     let indent = first.len() - trimmed.len();
@@ -419,7 +736,7 @@ fn try_parse_list(lines: &[&str]) -> Result<Option<(Block, usize)>> {
                 }
 
                 let content = item_lines.join("\n");
-                let content_blocks = parse_blocks(&content)?;
+                let content_blocks = parse_blocks_with_options(&content, options)?;
                 let checked = if let ListMarker::Checkbox(c) = m {
                     Some(c)
                 } else {
@@ -455,7 +772,7 @@ fn try_parse_list(lines: &[&str]) -> Result<Option<(Block, usize)>> {
     )))
 }
 
-fn try_parse_table(lines: &[&str]) -> Result<Option<(Block, usize)>> {
+fn try_parse_table(lines: &[&str], options: &ParseOptions) -> Result<Option<(Block, usize)>> {
     // Check for pipe table
     let first = lines[0];
     if !first.contains('|') {
@@ -474,8 +791,8 @@ fn try_parse_table(lines: &[&str]) -> Result<Option<(Block, usize)>> {
     }
 
     // Parse header
-    let headers = parse_table_row(first)?;
-    let alignments = parse_alignments(second);
+    let headers = parse_table_row(first, options)?;
+    let (alignments, widths) = parse_alignments(second);
 
     // Parse body rows
     let mut rows = Vec::new();
@@ -486,7 +803,7 @@ fn try_parse_table(lines: &[&str]) -> Result<Option<(Block, usize)>> {
         if !line.contains('|') || line.trim().is_empty() {
             break;
         }
-        rows.push(parse_table_row(line)?);
+        rows.push(parse_table_row(line, options)?);
         i += 1;
     }
 
@@ -496,7 +813,7 @@ fn try_parse_table(lines: &[&str]) -> Result<Option<(Block, usize)>> {
         if next.starts_with("Table:") || next.starts_with("Caption:") {
             let caption_text = next.split_once(':').map(|(_, t)| t.trim()).unwrap_or("");
             let (caption_text, label) = extract_label(caption_text);
-            let caption_inlines = parse_inlines(caption_text)?;
+            let caption_inlines = parse_inlines_with_options(caption_text, options)?;
             (Some(caption_inlines), label, 1)
         } else {
             (None, None, 0)
@@ -509,6 +826,7 @@ fn try_parse_table(lines: &[&str]) -> Result<Option<(Block, usize)>> {
         Block::Table {
             headers,
             alignments,
+            widths,
             rows,
             label,
             caption,
@@ -542,12 +860,17 @@ fn is_table_delimiter(line: &str) -> bool {
     true
 }
 
-fn parse_alignments(line: &str) -> Vec<Alignment> {
+/// Parse column alignments from a delimiter row, along with relative column
+/// widths derived from the length of each delimiter cell (pandoc-style).
+///
+/// Widths are `None` when every cell has the same length.
+fn parse_alignments(line: &str) -> (Vec<Alignment>, Option<Vec<f64>>) {
     let trimmed = line.trim().trim_matches('|');
-    trimmed
-        .split('|')
+    let cells: Vec<&str> = trimmed.split('|').map(str::trim).collect();
+
+    let alignments = cells
+        .iter()
         .map(|cell| {
-            let cell = cell.trim();
             let left = cell.starts_with(':');
             let right = cell.ends_with(':');
             match (left, right) {
@@ -556,18 +879,30 @@ fn parse_alignments(line: &str) -> Vec<Alignment> {
                 _ => Alignment::Left,
             }
         })
-        .collect()
+        .collect();
+
+    let lengths: Vec<usize> = cells.iter().map(|cell| cell.len()).collect();
+    let total: usize = lengths.iter().sum();
+    let uniform = lengths.windows(2).all(|pair| pair[0] == pair[1]);
+    let widths = (!uniform && total > 0).then(|| {
+        lengths
+            .iter()
+            .map(|&len| len as f64 / total as f64)
+            .collect()
+    });
+
+    (alignments, widths)
 }
 
-fn parse_table_row(line: &str) -> Result<Vec<Vec<crate::ast::Inline>>> {
+fn parse_table_row(line: &str, options: &ParseOptions) -> Result<Vec<Vec<crate::ast::Inline>>> {
     let trimmed = line.trim().trim_matches('|');
     trimmed
         .split('|')
-        .map(|cell| parse_inlines(cell.trim()))
+        .map(|cell| parse_inlines_with_options(cell.trim(), options))
         .collect()
 }
 
-fn parse_paragraph(lines: &[&str]) -> Result<(Block, usize)> {
+fn parse_paragraph(lines: &[&str], options: &ParseOptions) -> Result<(Block, usize)> {
     let mut para_lines = Vec::new();
     let mut i = 0;
 
@@ -581,22 +916,7 @@ fn parse_paragraph(lines: &[&str]) -> Result<(Block, usize)> {
         }
 
         // End paragraph on block-level element
-        if trimmed.starts_with('#')
-            || trimmed.starts_with("```")
-            || trimmed.starts_with("~~~")
-            || trimmed.starts_with(":::")
-            || trimmed.starts_with("$$")
-            || trimmed.starts_with('>')
-            || trimmed == "---"
-            || trimmed == "***"
-            || trimmed == "___"
-            || trimmed == "[[toc]]"
-        {
-            break;
-        }
-
-        // Check for list markers
-        if list_item_marker(trimmed).is_ok() {
+        if interrupts_paragraph(trimmed) {
             break;
         }
 
@@ -605,9 +925,58 @@ fn parse_paragraph(lines: &[&str]) -> Result<(Block, usize)> {
     }
 
     let content = para_lines.join("\n");
-    let inlines = parse_inlines(&content)?;
+    let block = match trailing_attributes(&content) {
+        Some((text, attrs)) => Block::AttributedParagraph {
+            content: parse_inlines_with_options(text, options)?,
+            id: attrs.id,
+            classes: attrs.classes,
+        },
+        None => Block::Paragraph(parse_inlines_with_options(&content, options)?),
+    };
+
+    Ok((block, i.max(1)))
+}
+
+/// Split a trailing attribute block (`{#id .class}`, possibly on its own
+/// line) off paragraph text. Only ids and classes are accepted, and the
+/// block must follow whitespace, so prose or image attributes that merely
+/// end in braces are left alone.
+fn trailing_attributes(text: &str) -> Option<(&str, BlockAttributes)> {
+    let trimmed = text.trim_end();
+    let open = trimmed.strip_suffix('}')?.rfind('{')?;
+    let before = trimmed[..open].trim_end();
+    if before.len() == open || before.is_empty() {
+        return None;
+    }
+
+    let tokens = split_attribute_tokens(&trimmed[open + 1..trimmed.len() - 1]);
+    let valid = !tokens.is_empty()
+        && tokens
+            .iter()
+            .all(|t| t.len() > 1 && (t.starts_with('.') || t.starts_with('#')));
+    valid.then(|| (before, BlockAttributes::parse(&trimmed[open..])))
+}
+
+/// Whether a (trimmed) line starts a block that ends a running paragraph.
+fn interrupts_paragraph(trimmed: &str) -> bool {
+    trimmed.starts_with('#')
+        || trimmed.starts_with("```")
+        || trimmed.starts_with("~~~")
+        || trimmed.starts_with(":::")
+        || trimmed.starts_with("$$")
+        || trimmed.starts_with('>')
+        || trimmed == "---"
+        || trimmed == "***"
+        || trimmed == "___"
+        || trimmed == "[[toc]]"
+        || list_item_marker(trimmed).is_ok()
+}
 
-    Ok((Block::Paragraph(inlines), i.max(1)))
+/// Whether a quote line (with one `>` already stripped) ends in paragraph
+/// text, so that a following unmarked line is a lazy continuation.
+fn continues_paragraph(line: &str) -> bool {
+    let text = line.trim_start_matches(|c: char| c == '>' || c.is_whitespace());
+    !text.is_empty() && !interrupts_paragraph(text)
 }
 
 /// Extract a label from the end of a string ({#label}).
@@ -634,7 +1003,10 @@ fn extract_label(s: &str) -> (&str, Option<String>) {
 /// : Definition of term 2
 /// : Additional paragraph for term 2
 /// ```
-fn try_parse_description_list(lines: &[&str]) -> Result<Option<(Block, usize)>> {
+fn try_parse_description_list(
+    lines: &[&str],
+    options: &ParseOptions,
+) -> Result<Option<(Block, usize)>> {
     // Look ahead for a term followed by a definition line starting with ':'
     if lines.len() < 2 {
         return Ok(None);
@@ -671,7 +1043,7 @@ fn try_parse_description_list(lines: &[&str]) -> Result<Option<(Block, usize)>>
         }
 
         // Parse the term
-        let term = parse_inlines(term_line)?;
+        let term = parse_inlines_with_options(term_line, options)?;
         i += 1;
 
         // Collect all definition lines
@@ -698,7 +1070,7 @@ fn try_parse_description_list(lines: &[&str]) -> Result<Option<(Block, usize)>>
         }
 
         let def_content = def_lines.join("\n");
-        let description = parse_blocks(&def_content)?;
+        let description = parse_blocks_with_options(&def_content, options)?;
 
         items.push(DescriptionItem { term, description });
     }
@@ -710,9 +1082,10 @@ fn try_parse_description_list(lines: &[&str]) -> Result<Option<(Block, usize)>>
     Ok(Some((Block::DescriptionList(items), i)))
 }
 
-/// Parse a page break marker.
+/// Parse a page break or float barrier marker.
 ///
-/// Syntax: `---pagebreak---` or `\\pagebreak` or `\\newpage`
+/// Syntax: `---pagebreak---` or `\\pagebreak` or `\\newpage`; a float
+/// barrier is `\\clearpage` or `---clearpage---`
 fn try_parse_page_break(line: &str) -> Result<Option<(Block, usize)>> {
     let trimmed = line.trim();
 
@@ -725,6 +1098,10 @@ fn try_parse_page_break(line: &str) -> Result<Option<(Block, usize)>> {
         return Ok(Some((Block::PageBreak, 1)));
     }
 
+    if trimmed == "\\clearpage" || trimmed == "---clearpage---" || trimmed == "<!-- clearpage -->" {
+        return Ok(Some((Block::ClearPage, 1)));
+    }
+
     Ok(None)
 }
 
@@ -780,11 +1157,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nested_block_quotes() {
+        let blocks = parse_blocks("> outer\n> > inner").unwrap();
+        match &blocks[..] {
+            [Block::BlockQuote(outer)] => {
+                assert!(matches!(&outer[0], Block::Paragraph(_)));
+                assert!(matches!(&outer[1], Block::BlockQuote(inner) if inner.len() == 1));
+            }
+            other => panic!("expected one block quote, got {:?}", other),
+        }
+
+        // Unmarked lines continue the innermost paragraph
+        let blocks = parse_blocks(">> deep\n> back\nlazy\n\nafter").unwrap();
+        assert_eq!(blocks.len(), 2);
+        match &blocks[0] {
+            Block::BlockQuote(outer) => match &outer[..] {
+                [Block::BlockQuote(inner)] => match &inner[..] {
+                    [Block::Paragraph(inlines)] => {
+                        assert!(
+                            matches!(inlines.last(), Some(crate::ast::Inline::Text(t)) if t.ends_with("lazy"))
+                        )
+                    }
+                    other => panic!("unexpected inner content {:?}", other),
+                },
+                other => panic!("unexpected outer content {:?}", other),
+            },
+            other => panic!("expected a block quote, got {:?}", other),
+        }
+
+        let blocks = parse_blocks("> # Title\nnot lazy").unwrap();
+        assert_eq!(blocks.len(), 2);
+    }
+
+    #[test]
+    fn test_display_math_skips_braced_dollars() {
+        let blocks = parse_blocks("$$\\text{price: $$} + x$$\n\n$$\na \\text{$$\n} b\n$$").unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert!(matches!(
+            &blocks[0],
+            Block::DisplayMath { content, .. } if content == "\\text{price: $$} + x"
+        ));
+        assert!(matches!(
+            &blocks[1],
+            Block::DisplayMath { content, .. } if content == "a \\text{$$\n} b"
+        ));
+
+        // An unbalanced brace outside text does not swallow the document
+        let blocks = parse_blocks("$$ f(x) = { x $$\n\n# Heading\n\nPara.").unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert!(matches!(
+            &blocks[0],
+            Block::DisplayMath { content, .. } if content.trim() == "f(x) = { x"
+        ));
+    }
+
     #[test]
     fn test_parse_display_math() {
         let input = "$$\n\\int_0^1 x dx\n$$";
         let blocks = parse_blocks(input).unwrap();
-        if let Block::DisplayMath { content, label } = &blocks[0] {
+        if let Block::DisplayMath { content, label, .. } = &blocks[0] {
             assert!(content.contains("\\int"));
             assert_eq!(*label, None);
         } else {
@@ -792,6 +1224,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_math_fence() {
+        let input = "```{.math #eq:energy}\nE = mc^2\n```\n\n```math {.unnumbered}\nx\n```";
+        let blocks = parse_blocks(input).unwrap();
+        assert_eq!(
+            blocks[0],
+            Block::DisplayMath {
+                content: "E = mc^2".to_string(),
+                label: Some("eq:energy".to_string()),
+                numbered: true,
+            }
+        );
+        assert!(matches!(
+            &blocks[1],
+            Block::DisplayMath {
+                numbered: false,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_parse_environment() {
         let input = "::: theorem {#thm:main}\nStatement here.\n:::";
@@ -804,10 +1257,254 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tilde_code_inside_environment() {
+        let input = "::: example\n~~~markdown\nA lone fence:\n:::\n~~~\nAfter.\n:::\n\nOutside.";
+        let blocks = parse_blocks(input).unwrap();
+        assert_eq!(blocks.len(), 2);
+        let Block::Environment { content, .. } = &blocks[0] else {
+            panic!("Expected environment");
+        };
+        assert_eq!(
+            content[0],
+            Block::CodeBlock {
+                language: Some("markdown".to_string()),
+                content: "A lone fence:\n:::".to_string(),
+            }
+        );
+        assert!(matches!(content[1], Block::Paragraph(_)));
+    }
+
+    #[test]
+    fn test_table_column_widths() {
+        let (_, widths) = parse_alignments("|------|:--:|--|");
+        assert_eq!(widths, Some(vec![0.5, 4.0 / 12.0, 2.0 / 12.0]));
+
+        let (_, widths) = parse_alignments("| --- | :-: | --: |");
+        assert_eq!(widths, None);
+    }
+
+    #[test]
+    fn test_environment_title_attribute() {
+        let input = "::: {.theorem #thm:py title=\"Pythagoras' theorem\"}\na^2 + b^2 = c^2\n:::\n\n::: lemma {#lem:x name=\"Zorn\"}\nChains.\n:::";
+        let blocks = parse_blocks(input).unwrap();
+        let Block::Environment {
+            kind, label, title, ..
+        } = &blocks[0]
+        else {
+            panic!("Expected environment");
+        };
+        assert_eq!(*kind, EnvironmentKind::Theorem);
+        assert_eq!(label.as_deref(), Some("thm:py"));
+        assert_eq!(title.as_deref(), Some("Pythagoras' theorem"));
+
+        let Block::Environment { label, title, .. } = &blocks[1] else {
+            panic!("Expected environment");
+        };
+        assert_eq!(label.as_deref(), Some("lem:x"));
+        assert_eq!(title.as_deref(), Some("Zorn"));
+    }
+
+    #[test]
+    fn test_promote_lonely_math() {
+        let options = ParseOptions {
+            promote_lonely_math: true,
+            ..Default::default()
+        };
+        let input = "Consider\n\n$x^2$\n\nand $y$ inline.\n$z$\n\n$a$ {#eq:a}";
+        let blocks = parse_blocks_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            blocks[1],
+            Block::DisplayMath {
+                content: "x^2".to_string(),
+                label: None,
+                numbered: true,
+            }
+        );
+        assert!(matches!(blocks[2], Block::Paragraph(_)));
+        assert!(matches!(
+            &blocks[3],
+            Block::DisplayMath { label: Some(l), .. } if l == "eq:a"
+        ));
+
+        let blocks = parse_blocks(input).unwrap();
+        assert!(matches!(blocks[1], Block::Paragraph(_)));
+
+        // Content is kept verbatim unless `trim_math` is set
+        let content = |trim_math| {
+            let options = ParseOptions {
+                promote_lonely_math: true,
+                trim_math,
+                ..Default::default()
+            };
+            match &parse_blocks_with_options("$ x^2 $", &options).unwrap()[..] {
+                [Block::DisplayMath { content, .. }] => content.clone(),
+                other => panic!("Expected display math, got {:?}", other),
+            }
+        };
+        assert_eq!(content(false), " x^2 ");
+        assert_eq!(content(true), "x^2");
+    }
+
+    #[test]
+    fn test_listing_fence_attributes() {
+        let blocks =
+            parse_blocks("```python {#lst:sort caption=\"Sorting\"}\nsorted(xs)\n```").unwrap();
+        assert_eq!(
+            blocks,
+            vec![Block::Environment {
+                kind: EnvironmentKind::Listing,
+                label: Some("lst:sort".to_string()),
+                title: None,
+                proves: None,
+                content: vec![Block::CodeBlock {
+                    language: Some("python".to_string()),
+                    content: "sorted(xs)".to_string(),
+                }],
+                caption: Some(vec![crate::ast::Inline::Text("Sorting".to_string())]),
+            }]
+        );
+
+        // A plain labeled code block stays a code block
+        let blocks = parse_blocks("```python {#code}\nsorted(xs)\n```").unwrap();
+        assert!(matches!(blocks[0], Block::CodeBlock { .. }));
+    }
+
+    #[test]
+    fn test_paragraph_attributes() {
+        let blocks = parse_blocks("Opening words\nof the paper.\n{#intro .lead .wide}").unwrap();
+        assert_eq!(
+            blocks,
+            vec![Block::AttributedParagraph {
+                content: vec![
+                    crate::ast::Inline::Text("Opening words".to_string()),
+                    crate::ast::Inline::Text("\nof the paper.".to_string()),
+                ],
+                id: Some("intro".to_string()),
+                classes: vec!["lead".to_string(), "wide".to_string()],
+            }]
+        );
+
+        // Braces that aren't an attribute block stay text
+        for input in ["The set {a, b}", "Image ![a](b.png){.wide}", "{.lead}"] {
+            let blocks = parse_blocks(input).unwrap();
+            assert!(matches!(blocks[0], Block::Paragraph(_)), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_escaped_block_markers() {
+        let text = |s: &str| crate::ast::Inline::Text(s.to_string());
+        let blocks =
+            parse_blocks("\\:::theorem\nBody.\n\\:::\n\n\\[[toc]]\n\n\\| a | b |\n|---|---|")
+                .unwrap();
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Paragraph(vec![text(":::theorem"), text("\nBody."), text("\n:::")]),
+                Block::Paragraph(vec![
+                    text("["),
+                    crate::ast::Inline::LinkReference {
+                        content: vec![text("toc")],
+                        id: "toc".to_string(),
+                        closing: "]".to_string(),
+                    },
+                    text("]"),
+                ]),
+                Block::Paragraph(vec![text("| a | b |"), text("\n|---|---|")]),
+            ]
+        );
+    }
+
     #[test]
     fn test_table_delimiter() {
         assert!(is_table_delimiter("| --- | :---: | ---: |"));
         assert!(is_table_delimiter("|---|:---:|---:|"));
         assert!(!is_table_delimiter("| not | a | delimiter |"));
     }
+
+    #[test]
+    fn test_indented_code() {
+        let input = "Intro.\n\n    fn main() {\n\n        run();\n    }\n\nAfter.";
+        let blocks = parse_blocks(input).unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            blocks[1],
+            Block::CodeBlock {
+                language: None,
+                content: "fn main() {\n\n    run();\n}".to_string(),
+            }
+        );
+
+        // An indented line can't interrupt a paragraph
+        let blocks = parse_blocks("Intro\n    continued.").unwrap();
+        assert!(matches!(&blocks[..], [Block::Paragraph(_)]));
+
+        let options = ParseOptions {
+            indented_code: false,
+            ..Default::default()
+        };
+        let blocks = parse_blocks_with_options(input, &options).unwrap();
+        assert!(!blocks.iter().any(|b| matches!(b, Block::CodeBlock { .. })));
+    }
+
+    #[test]
+    fn test_clear_page() {
+        let blocks = parse_blocks("Before.\n\n\\clearpage\n\n\\pagebreak\n\nAfter.").unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[1], Block::ClearPage);
+        assert_eq!(blocks[2], Block::PageBreak);
+    }
+
+    #[test]
+    fn test_tabs_kept_in_mixed_fences() {
+        // A ``` line inside a ~~~ fence does not close it
+        let input = "~~~\n```\n\tkept\n~~~\n\t- expanded";
+        assert_eq!(
+            expand_indent_tabs(input, 4),
+            "~~~\n```\n\tkept\n~~~\n    - expanded"
+        );
+    }
+
+    #[test]
+    fn test_tab_indented_sublist() {
+        // The tab reaches column 4, past the two-space indent of "Parent"
+        let input = "  - Parent\n\t- Child\n  - Sibling";
+        let blocks = parse_blocks(input).unwrap();
+        let Block::List { items, .. } = &blocks[0] else {
+            panic!("Expected list");
+        };
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            &items[0].content[..],
+            [Block::Paragraph(_), Block::List { items: sub, .. }] if sub.len() == 1
+        ));
+
+        // With one-column tabs the tab is shallower, so "Child" is a sibling
+        let options = ParseOptions {
+            tab_width: 1,
+            ..Default::default()
+        };
+        let blocks = parse_blocks_with_options(input, &options).unwrap();
+        let Block::List { items, .. } = &blocks[0] else {
+            panic!("Expected list");
+        };
+        assert_eq!(items.len(), 3);
+    }
+
+    #[test]
+    fn test_indented_code_ignores_list_continuations() {
+        let input = "- First item\n    continues here\n- Second\n\n    more of second";
+        let blocks = parse_blocks(input).unwrap();
+        assert_eq!(blocks.len(), 1);
+        let Block::List { items, .. } = &blocks[0] else {
+            panic!("Expected list");
+        };
+        assert_eq!(items.len(), 2);
+        assert!(items
+            .iter()
+            .flat_map(|item| &item.content)
+            .all(|b| !matches!(b, Block::CodeBlock { .. })));
+    }
 }
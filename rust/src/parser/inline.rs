@@ -1,25 +1,93 @@
 //! Inline-level parsing for Markdown.
 
-use crate::ast::{Citation, CitationStyle, FootnoteKind, Inline};
-use crate::error::Result;
+use crate::ast::{Citation, CitationStyle, FootnoteKind, Inline, ReferenceForm};
+use crate::error::{ParseError, Result};
+use crate::parser::block::BlockAttributes;
 use crate::parser::lexer::{
     citation, display_math, emphasis, footnote_inline, footnote_ref, inline_code, inline_math,
-    label, reference, strong, Token,
+    label, lang_span, reference, small_caps, strong, Token,
 };
+use crate::parser::units::parse_unit_command;
+use crate::parser::ParseOptions;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Default limit on how deeply inline elements may nest.
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 64;
+
+/// Default limit on the length in bytes of an inline math span.
+pub const DEFAULT_MAX_INLINE_MATH_LEN: usize = 4096;
+
+/// Default limit on the length in bytes of a code span.
+pub const DEFAULT_MAX_CODE_SPAN_LEN: usize = 2048;
 
 /// Parse inline content from a string.
 pub fn parse_inlines(input: &str) -> Result<Vec<Inline>> {
+    parse_inlines_with_options(input, &ParseOptions::default())
+}
+
+/// Parse inline content with the given options, failing with
+/// [`ParseError::NestingTooDeep`] when elements nest more than
+/// `options.max_nesting_depth` levels deep.
+pub fn parse_inlines_with_options(input: &str, options: &ParseOptions) -> Result<Vec<Inline>> {
+    parse_nested(input, Nesting { depth: 0, options })
+}
+
+/// Current nesting depth of the inline being parsed, and the options in effect.
+#[derive(Clone, Copy)]
+struct Nesting<'a> {
+    depth: usize,
+    options: &'a ParseOptions,
+}
+
+impl<'a> Nesting<'a> {
+    fn enter(self) -> Result<Nesting<'a>> {
+        let limit = self.options.max_nesting_depth;
+        if self.depth >= limit {
+            return Err(ParseError::NestingTooDeep(limit).into());
+        }
+        Ok(Nesting {
+            depth: self.depth + 1,
+            options: self.options,
+        })
+    }
+
+    /// Whether inline math content is within `max_inline_math_len`.
+    fn math_fits(self, content: &str) -> bool {
+        content.len() <= self.options.max_inline_math_len
+    }
+
+    /// Math content as stored in the AST.
+    fn math(self, content: &str) -> String {
+        if self.options.trim_math {
+            content.trim().to_string()
+        } else {
+            content.to_string()
+        }
+    }
+}
+
+fn parse_nested(input: &str, nesting: Nesting<'_>) -> Result<Vec<Inline>> {
     let mut inlines = Vec::new();
     let mut remaining = input;
 
     while !remaining.is_empty() {
-        // Try to parse special inline elements
-        if let Some((inline, rest)) = try_parse_inline(remaining)? {
+        if let Some((c, rest)) = try_parse_escape(remaining) {
+            // Backslash escape: the character is always literal text
+            push_text(&mut inlines, c.encode_utf8(&mut [0; 4]));
+            remaining = rest;
+        } else if let Some((inline, rest)) = try_parse_inline(remaining, nesting)? {
+            // Try to parse special inline elements
             // Skip empty text nodes from labels
             if !matches!(&inline, Inline::Text(t) if t.is_empty()) {
                 inlines.push(inline);
             }
             remaining = rest;
+        } else if let Some(rest) = try_parse_tie(input, remaining) {
+            inlines.push(Inline::NonBreakingSpace);
+            remaining = rest;
         } else {
             // Consume plain text until the next special character or end
             let (text, rest) = consume_text(remaining);
@@ -41,7 +109,7 @@ pub fn parse_inlines(input: &str) -> Result<Vec<Inline>> {
                         }
                     }
                 } else {
-                    inlines.push(Inline::Text(text.to_string()));
+                    push_text(&mut inlines, text);
                 }
                 remaining = rest;
             } else if rest == remaining {
@@ -58,28 +126,54 @@ pub fn parse_inlines(input: &str) -> Result<Vec<Inline>> {
     Ok(inlines)
 }
 
-fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
+/// Parse a CommonMark backslash escape (`\*`, `\#`, `\[`, ...).
+///
+/// Any ASCII punctuation character may be escaped; a backslash before
+/// anything else is literal.
+fn try_parse_escape(input: &str) -> Option<(char, &str)> {
+    let rest = input.strip_prefix('\\')?;
+    let c = rest.chars().next().filter(char::is_ascii_punctuation)?;
+    Some((c, &rest[1..]))
+}
+
+/// Append text, merging with a preceding text node.
+fn push_text(inlines: &mut Vec<Inline>, text: &str) {
+    if let Some(Inline::Text(last)) = inlines.last_mut() {
+        last.push_str(text);
+    } else {
+        inlines.push(Inline::Text(text.to_string()));
+    }
+}
+
+fn try_parse_inline<'a>(input: &'a str, nesting: Nesting<'_>) -> Result<Option<(Inline, &'a str)>> {
     // Order matters - try more specific patterns first
 
-    // Display math ($$...$$)
+    // Display math ($$...$$). Math and code spans over the length limit
+    // leave their opening delimiter as text.
     if input.starts_with("$$") {
-        if let Ok((rest, Token::DisplayMath(content))) = display_math(input) {
-            // Display math in inline context - treat as inline math
-            return Ok(Some((Inline::InlineMath(content.to_string()), rest)));
+        match display_math(input) {
+            Ok((rest, Token::DisplayMath(content))) if nesting.math_fits(content) => {
+                // Display math in inline context - treat as inline math
+                return Ok(Some((Inline::InlineMath(nesting.math(content)), rest)));
+            }
+            _ => {}
         }
     }
 
     // Inline math ($...$)
     if input.starts_with('$') && !input.starts_with("$$") {
-        if let Ok((rest, Token::InlineMath(content))) = inline_math(input) {
-            return Ok(Some((Inline::InlineMath(content.to_string()), rest)));
+        match inline_math(input) {
+            Ok((rest, Token::InlineMath(content))) if nesting.math_fits(content) => {
+                return Ok(Some((Inline::InlineMath(nesting.math(content)), rest)));
+            }
+            _ => {}
         }
     }
 
     // Strong (**...** or __...__)
     if input.starts_with("**") || input.starts_with("__") {
         if let Ok((rest, Token::Strong(content))) = strong(input) {
-            let inner = parse_inlines(content)?;
+            let inner = parse_nested(content, nesting.enter()?)?;
             return Ok(Some((Inline::Strong(inner), rest)));
         }
     }
@@ -89,7 +183,7 @@ fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
         || (input.starts_with('_') && !input.starts_with("__"))
     {
         if let Ok((rest, Token::Emphasis(content))) = emphasis(input) {
-            let inner = parse_inlines(content)?;
+            let inner = parse_nested(content, nesting.enter()?)?;
             return Ok(Some((Inline::Emphasis(inner), rest)));
         }
     }
@@ -99,91 +193,101 @@ fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
         if let Some(end) = after.find("~~") {
             let content = &after[..end];
             let rest = &after[end + 2..];
-            let inner = parse_inlines(content)?;
+            let inner = parse_nested(content, nesting.enter()?)?;
             return Ok(Some((Inline::Strikethrough(inner), rest)));
         }
     }
 
-    // Inline code (`...`)
+    // Inline code (`...`), or raw text (`...`{.raw}) that bypasses all
+    // inline parsing without code styling
     if input.starts_with('`') && !input.starts_with("```") {
-        if let Ok((rest, Token::InlineCode(content))) = inline_code(input) {
-            return Ok(Some((Inline::Code(content.to_string()), rest)));
-        }
-    }
-
-    // Year-only citation ([-@key])
-    if let Some(after) = input.strip_prefix("[-@") {
-        if let Some(end) = after.find(']') {
-            let key = &after[..end];
-            let rest = &after[end + 1..];
-            // Parse optional locator
-            let (key, locator) = if let Some(comma) = key.find(',') {
-                (&key[..comma], Some(key[comma + 1..].trim().to_string()))
-            } else {
-                (key.trim(), None)
-            };
-            let cite = Citation {
-                keys: vec![key.to_string()],
-                style: CitationStyle::YearOnly,
-                prefix: None,
-                locator,
-            };
-            return Ok(Some((Inline::Citation(cite), rest)));
+        match inline_code(input) {
+            Ok((rest, Token::InlineCode(content)))
+                if content.len() <= nesting.options.max_code_span_len =>
+            {
+                if let Some(rest) = rest.strip_prefix("{.raw}") {
+                    return Ok(Some((Inline::Verbatim(content.to_string()), rest)));
+                }
+                return Ok(Some((Inline::Code(content.to_string()), rest)));
+            }
+            _ => {}
         }
     }
 
-    // Parenthetical citation ([@key] or [@key1; @key2])
-    if input.starts_with("[@") {
-        if let Ok((rest, Token::Citation(cites))) = citation(input) {
+    // Parenthetical ([@key] or [@key1; @key2]) or year-only ([-@key]) citation,
+    // optionally with a prefix ([see @key, p. 42])
+    if input.starts_with('[') && !input.starts_with("[^") {
+        if let Ok((rest, Token::Citation(prefix, cites, year_only))) = citation(input) {
             let cite = Citation {
                 keys: cites.iter().map(|c| c.key.to_string()).collect(),
-                style: CitationStyle::Parenthetical,
-                prefix: None,
-                locator: cites.first().and_then(|c| c.locator.map(String::from)),
+                style: if year_only {
+                    CitationStyle::YearOnly
+                } else {
+                    CitationStyle::Parenthetical
+                },
+                prefix: prefix.map(String::from),
+                locators: cites.iter().map(|c| c.locator.map(String::from)).collect(),
             };
-            return Ok(Some((Inline::Citation(cite), rest)));
+            // With a prefix the brackets may instead be link text
+            // (`[see @sec:intro]`, `[mail @bob](url)`)
+            let prefixed_other = prefix.is_some()
+                && (rest.starts_with(['(', '[', '{'])
+                    || cite.keys.iter().any(|key| is_reference_label(key)));
+            if !prefixed_other {
+                return Ok(Some((Inline::Citation(cite), rest)));
+            }
         }
     }
 
     // Subscript (H~2~O)
     if input.starts_with('~') && !input.starts_with("~~") {
         if let Some(end) = input[1..].find('~') {
-            // Make sure this isn't a strikethrough
-            if end > 0 && !input[1..1 + end].contains('~') {
-                let content = &input[1..1 + end];
+            // Subscripts may not contain whitespace (so `Fig.~1 and Dr.~Smith` is two ties)
+            let content = &input[1..1 + end];
+            if end > 0 && !content.contains(char::is_whitespace) {
                 let rest = &input[1 + end + 1..];
-                let inner = parse_inlines(content)?;
+                let inner = parse_nested(content, nesting.enter()?)?;
                 return Ok(Some((Inline::Subscript(inner), rest)));
             }
         }
     }
 
-    // Superscript (x^2^ or e^iπ^)
+    // Superscript (x^2^ or e^iπ^), which like a subscript may not contain
+    // whitespace (so `a^2 + b^2` stays text)
     if input.starts_with('^') && !input.starts_with("^[") {
         if let Some(end) = input[1..].find('^') {
-            if end > 0 {
-                let content = &input[1..1 + end];
+            let content = &input[1..1 + end];
+            if end > 0 && !content.contains(char::is_whitespace) {
                 let rest = &input[1 + end + 1..];
-                let inner = parse_inlines(content)?;
+                let inner = parse_nested(content, nesting.enter()?)?;
                 return Ok(Some((Inline::Superscript(inner), rest)));
             }
         }
     }
 
-    // Small caps ([sc]...[/sc])
-    if let Some(after) = input.strip_prefix("[sc]") {
-        if let Some(end) = after.find("[/sc]") {
-            let content = &after[..end];
-            let rest = &after[end + 5..];
-            let inner = parse_inlines(content)?;
+    // Small caps ([sc]...[/sc] or [...]{.smallcaps}); unclosed, the
+    // brackets fall through to link parsing and stay text
+    if input.starts_with('[') {
+        if let Ok((rest, Token::SmallCaps(content))) = small_caps(input) {
+            let inner = parse_nested(content, nesting.enter()?)?;
             return Ok(Some((Inline::SmallCaps(inner), rest)));
         }
     }
 
+    // Language or direction span ([...]{lang=fr}, [...]{dir=rtl})
+    if input.starts_with('[') {
+        if let Ok((rest, Token::LangSpan(content, lang, dir))) = lang_span(input) {
+            let content = parse_nested(content, nesting.enter()?)?;
+            let lang = lang.map(str::to_string);
+            let dir = dir.map(str::to_string);
+            return Ok(Some((Inline::Lang { lang, dir, content }, rest)));
+        }
+    }
+
     // Footnote inline (^[...])
     if input.starts_with("^[") {
         if let Ok((rest, Token::FootnoteInline(content))) = footnote_inline(input) {
-            let inner = parse_inlines(content)?;
+            let inner = parse_nested(content, nesting.enter()?)?;
             return Ok(Some((Inline::Footnote(FootnoteKind::Inline(inner)), rest)));
         }
     }
@@ -201,8 +305,11 @@ fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
     // Cross-reference or textual citation (@label or @citationkey)
     // Textual citations produce "Author (Year)" style
     if input.starts_with('@') && !input.starts_with("[@") && !input.starts_with("[-@") {
-        if let Ok((rest, Token::Reference(lbl))) = reference(input) {
+        if let Ok((hinted_rest, Token::Reference(lbl, hint))) = reference(input) {
             let label_str = lbl.to_string();
+            // A display hint only applies to cross-references; otherwise the
+            // braces stay in the text
+            let rest = &input[1 + lbl.len()..];
 
             // Check if this ends with a hyphen (author-only citation: @author-)
             if label_str.ends_with('-') {
@@ -211,31 +318,24 @@ fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
                     keys: vec![key.to_string()],
                     style: CitationStyle::AuthorOnly,
                     prefix: None,
-                    locator: None,
+                    locators: Vec::new(),
                 };
                 return Ok(Some((Inline::Citation(cite), rest)));
             }
 
             // First try as cross-reference (sec:, fig:, thm:, eq:, tab:, etc.)
             // These prefixes indicate a reference, not a citation
-            let is_reference = label_str.starts_with("sec:")
-                || label_str.starts_with("fig:")
-                || label_str.starts_with("thm:")
-                || label_str.starts_with("eq:")
-                || label_str.starts_with("tab:")
-                || label_str.starts_with("lem:")
-                || label_str.starts_with("def:")
-                || label_str.starts_with("prop:")
-                || label_str.starts_with("cor:")
-                || label_str.starts_with("algo:")
-                || label_str.starts_with("ex:")
-                || label_str.starts_with("rem:")
-                || label_str.starts_with("app:");
+            let is_reference = is_reference_label(&label_str);
 
             if is_reference {
+                let (form, rest) = match hint.and_then(ReferenceForm::from_hint) {
+                    Some(form) => (form, hinted_rest),
+                    None => (ReferenceForm::Default, rest),
+                };
                 return Ok(Some((
                     Inline::Reference {
                         label: label_str,
+                        form,
                         resolved: None,
                     },
                     rest,
@@ -247,12 +347,19 @@ fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
                 keys: vec![label_str],
                 style: CitationStyle::Textual,
                 prefix: None,
-                locator: None,
+                locators: Vec::new(),
             };
             return Ok(Some((Inline::Citation(cite), rest)));
         }
     }
 
+    // Quantities (\SI{9.8}{m/s^2}, \num{12345}, \si{kg})
+    if input.starts_with('\\') {
+        if let Some((text, rest)) = parse_unit_command(input) {
+            return Ok(Some((Inline::Text(text), rest)));
+        }
+    }
+
     // Label ({#...})
     if input.starts_with("{#") {
         if let Ok((rest, Token::Label(_))) = label(input) {
@@ -261,16 +368,29 @@ fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
         }
     }
 
+    // Keyboard input ([[Ctrl+C]])
+    if let Some(after) = input.strip_prefix("[[") {
+        if let Some(end) = after.find("]]") {
+            let keys = &after[..end];
+            if !keys.trim().is_empty() && !keys.contains('[') {
+                return Ok(Some((
+                    Inline::Kbd(keys.trim().to_string()),
+                    &after[end + 2..],
+                )));
+            }
+        }
+    }
+
     // Link ([text](url "title"))
     if input.starts_with('[') && !input.starts_with("[^") && !input.starts_with("[@") {
-        if let Some((inline, rest)) = try_parse_link(input)? {
+        if let Some((inline, rest)) = try_parse_link(input, nesting)? {
             return Ok(Some((inline, rest)));
         }
     }
 
     // Image (![alt](url "title"))
     if input.starts_with("![") {
-        if let Some((inline, rest)) = try_parse_image(input)? {
+        if let Some((inline, rest)) = try_parse_image(input, nesting)? {
             return Ok(Some((inline, rest)));
         }
     }
@@ -285,7 +405,32 @@ fn try_parse_inline(input: &str) -> Result<Option<(Inline, &str)>> {
     Ok(None)
 }
 
-fn try_parse_link(input: &str) -> Result<Option<(Inline, &str)>> {
+/// Whether a label's prefix marks it as a cross-reference (`sec:`, `fig:`,
+/// `thm:`, ...) rather than a citation key.
+fn is_reference_label(label: &str) -> bool {
+    const PREFIXES: [&str; 14] = [
+        "sec:", "fig:", "thm:", "eq:", "tab:", "lem:", "def:", "prop:", "cor:", "algo:", "lst:",
+        "ex:", "rem:", "app:",
+    ];
+    PREFIXES.iter().any(|prefix| label.starts_with(prefix))
+}
+
+/// Parse a LaTeX-style tie (`Fig.~1`): a single `~` between two non-space characters.
+///
+/// Tried only after strikethrough (`~~`) and subscript (`~x~`) have failed to match.
+fn try_parse_tie<'a>(input: &str, remaining: &'a str) -> Option<&'a str> {
+    let rest = remaining.strip_prefix('~')?;
+    let prev = input[..input.len() - remaining.len()].chars().next_back()?;
+    let next = rest.chars().next()?;
+
+    if prev.is_whitespace() || prev == '~' || next.is_whitespace() || next == '~' {
+        return None;
+    }
+
+    Some(rest)
+}
+
+fn try_parse_link<'a>(input: &'a str, nesting: Nesting<'_>) -> Result<Option<(Inline, &'a str)>> {
     // [text](url "title")
     if !input.starts_with('[') {
         return Ok(None);
@@ -317,7 +462,7 @@ fn try_parse_link(input: &str) -> Result<Option<(Inline, &str)>> {
     let after_text = &input[text_end + 1..];
 
     if !after_text.starts_with('(') {
-        return Ok(None);
+        return try_parse_link_reference(text, after_text, nesting);
     }
 
     // Find closing paren, handling nested parens
@@ -349,7 +494,7 @@ fn try_parse_link(input: &str) -> Result<Option<(Inline, &str)>> {
     // Parse URL and optional title
     let (url, title) = parse_url_and_title(url_part);
 
-    let content = parse_inlines(text)?;
+    let content = parse_nested(text, nesting.enter()?)?;
 
     Ok(Some((
         Inline::Link {
@@ -361,7 +506,40 @@ fn try_parse_link(input: &str) -> Result<Option<(Inline, &str)>> {
     )))
 }
 
-fn try_parse_image(input: &str) -> Result<Option<(Inline, &str)>> {
+/// Parse the reference-style forms `[text][id]`, `[text][]` and `[text]`,
+/// given the bracketed text and what follows it.
+fn try_parse_link_reference<'a>(
+    text: &str,
+    after_text: &'a str,
+    nesting: Nesting<'_>,
+) -> Result<Option<(Inline, &'a str)>> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let explicit = after_text.strip_prefix('[').and_then(|after| {
+        let end = after.find(']')?;
+        let id = &after[..end];
+        (!id.contains('[')).then_some((id, &after[end + 1..]))
+    });
+    let (id, closing, rest) = match explicit {
+        Some(("", rest)) => (text, "][]".to_string(), rest),
+        Some((id, rest)) => (id, format!("][{}]", id), rest),
+        None => (text, "]".to_string(), after_text),
+    };
+
+    let content = parse_nested(text, nesting.enter()?)?;
+    Ok(Some((
+        Inline::LinkReference {
+            content,
+            id: id.to_string(),
+            closing,
+        },
+        rest,
+    )))
+}
+
+fn try_parse_image<'a>(input: &'a str, nesting: Nesting<'_>) -> Result<Option<(Inline, &'a str)>> {
     // ![alt](url "title")
     if !input.starts_with("![") {
         return Ok(None);
@@ -385,21 +563,61 @@ fn try_parse_image(input: &str) -> Result<Option<(Inline, &str)>> {
     };
 
     let url_part = &after_alt[1..close_paren];
-    let rest = &after_alt[close_paren + 1..];
+    let mut rest = &after_alt[close_paren + 1..];
 
     let (url, title) = parse_url_and_title(url_part);
 
+    // Optional attributes directly after the image (`{srcset="..."}`)
+    let mut attributes = Vec::new();
+    if rest.starts_with('{') {
+        if let Some(close) = rest.find('}') {
+            attributes = BlockAttributes::parse(&rest[..=close]).pairs;
+            rest = &rest[close + 1..];
+        }
+    }
+
+    // Formatted alt text (`![A *bold* claim]`) is flattened to plain text
+    let alt = plain_text(&parse_nested(alt, nesting.enter()?)?);
+
     Ok(Some((
         Inline::Image {
             url: url.to_string(),
-            alt: alt.to_string(),
+            alt,
             title: title.map(String::from),
+            attributes,
         },
         rest,
     )))
 }
 
-fn parse_url_and_title(input: &str) -> (&str, Option<&str>) {
+/// Flatten inlines to plain text (markup dropped, math and code kept as source).
+fn plain_text(inlines: &[Inline]) -> String {
+    let mut text = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(s)
+            | Inline::Code(s)
+            | Inline::InlineMath(s)
+            | Inline::Kbd(s)
+            | Inline::Verbatim(s) => text.push_str(s),
+            Inline::Emphasis(inner)
+            | Inline::Strong(inner)
+            | Inline::Strikethrough(inner)
+            | Inline::Superscript(inner)
+            | Inline::Subscript(inner)
+            | Inline::SmallCaps(inner)
+            | Inline::Lang { content: inner, .. }
+            | Inline::Link { content: inner, .. }
+            | Inline::LinkReference { content: inner, .. } => text.push_str(&plain_text(inner)),
+            Inline::Abbreviation { abbr, .. } => text.push_str(abbr),
+            Inline::SoftBreak | Inline::HardBreak | Inline::NonBreakingSpace => text.push(' '),
+            _ => {}
+        }
+    }
+    text
+}
+
+pub(super) fn parse_url_and_title(input: &str) -> (&str, Option<&str>) {
     let input = input.trim();
 
     // Check for title in quotes
@@ -454,13 +672,20 @@ fn consume_text(input: &str) -> (&str, &str) {
     let mut chars = input.char_indices().peekable();
 
     while let Some((i, c)) = chars.next() {
-        if SPECIAL.contains(&c) {
-            // Check for escaped character
-            if i > 0 && input.as_bytes()[i - 1] == b'\\' {
-                end = i + c.len_utf8();
-                continue;
-            }
+        if c == '\\'
+            && chars
+                .peek()
+                .is_some_and(|&(_, next)| next.is_ascii_punctuation())
+        {
+            // Leave escapes to `try_parse_escape`
+            return (&input[..i], &input[i..]);
+        }
 
+        if c == '\\' && i > 0 && parse_unit_command(&input[i..]).is_some() {
+            return (&input[..i], &input[i..]);
+        }
+
+        if SPECIAL.contains(&c) {
             // Special handling for potential inline elements
             if c == '*' || c == '_' {
                 // Check if followed by non-space (potential emphasis/strong)
@@ -479,8 +704,8 @@ fn consume_text(input: &str) -> (&str, &str) {
             if c == '~' {
                 // Check for strikethrough (~~) or subscript (~text~)
                 if let Some(&(_, next)) = chars.peek() {
-                    // Stop if next is ~ (strikethrough) or alphanumeric (subscript)
-                    if next == '~' || next.is_alphanumeric() {
+                    // Stop if next is ~ (strikethrough) or non-space (subscript or tie)
+                    if !next.is_whitespace() {
                         if end == 0 && i == 0 {
                             return ("", input);
                         }
@@ -623,6 +848,54 @@ mod tests {
         assert_eq!(cite_count, 1);
     }
 
+    #[test]
+    fn test_citation_styles() {
+        let inlines = parse_inlines("[@a; @b], [-@c; -@d, p. 2], @e says and @f- argues.").unwrap();
+        let cites: Vec<(CitationStyle, Vec<&str>)> = inlines
+            .iter()
+            .filter_map(|inline| match inline {
+                Inline::Citation(cite) => {
+                    Some((cite.style, cite.keys.iter().map(String::as_str).collect()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            cites,
+            [
+                (CitationStyle::Parenthetical, vec!["a", "b"]),
+                (CitationStyle::YearOnly, vec!["c", "d"]),
+                (CitationStyle::Textual, vec!["e"]),
+                (CitationStyle::AuthorOnly, vec!["f"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_citation_prefix() {
+        let inlines = parse_inlines("As shown [see @knuth1984, p. 42; @lamport1994].").unwrap();
+        let Some(Inline::Citation(cite)) = inlines.get(1) else {
+            panic!("Expected citation, got {:?}", inlines);
+        };
+        assert_eq!(cite.prefix.as_deref(), Some("see"));
+        assert_eq!(cite.keys, ["knuth1984", "lamport1994"]);
+        assert_eq!(cite.locator(0), Some("p. 42"));
+
+        // Cross-references, links and e-mail addresses stay what they were
+        for input in [
+            "[see @sec:intro]",
+            "[mail @bob](https://x.org)",
+            "[me@x.org]",
+            "[ask @bob about it]",
+        ] {
+            let inlines = parse_inlines(input).unwrap();
+            assert!(
+                !inlines.iter().any(|i| matches!(i, Inline::Citation(_))),
+                "{input}: {inlines:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_reference() {
         let inlines = parse_inlines("See @eq:euler for details.").unwrap();
@@ -644,6 +917,188 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_raw_inline_span() {
+        let inlines = parse_inlines("Write `*$@[x`{.raw} or `code`.").unwrap();
+        assert_eq!(
+            inlines,
+            vec![
+                Inline::Text("Write ".to_string()),
+                Inline::Verbatim("*$@[x".to_string()),
+                Inline::Text(" or ".to_string()),
+                Inline::Code("code".to_string()),
+                Inline::Text(".".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kbd() {
+        let inlines = parse_inlines("Press [[Ctrl+C]] to copy.").unwrap();
+        assert_eq!(inlines[1], Inline::Kbd("Ctrl+C".to_string()));
+    }
+
+    #[test]
+    fn test_image_alt_formatting() {
+        let inlines = parse_inlines("![A *bold* claim](a.png) ![plain](b.png)").unwrap();
+        let Inline::Image { alt, .. } = &inlines[0] else {
+            panic!("Expected image");
+        };
+        assert_eq!(alt, "A bold claim");
+
+        let Inline::Image { alt, .. } = &inlines[2] else {
+            panic!("Expected image");
+        };
+        assert_eq!(alt, "plain");
+    }
+
+    #[test]
+    fn test_backslash_escapes() {
+        let inlines = parse_inlines(r"\*not emphasis\* and \# hash").unwrap();
+        assert_eq!(
+            inlines,
+            vec![Inline::Text("*not emphasis* and # hash".to_string())]
+        );
+
+        // Only ASCII punctuation is escapable
+        let inlines = parse_inlines(r"C:\path \[x\]").unwrap();
+        assert_eq!(inlines, vec![Inline::Text(r"C:\path [x]".to_string())]);
+    }
+
+    #[test]
+    fn test_tilde_disambiguation() {
+        let inlines = parse_inlines("Fig.~1 and Dr.~Smith").unwrap();
+        let ties = inlines
+            .iter()
+            .filter(|i| matches!(i, Inline::NonBreakingSpace))
+            .count();
+        assert_eq!(ties, 2);
+
+        let inlines = parse_inlines("H~2~O and ~~gone~~ ~ alone").unwrap();
+        assert!(matches!(&inlines[1], Inline::Subscript(_)));
+        assert!(inlines
+            .iter()
+            .any(|i| matches!(i, Inline::Strikethrough(_))));
+        assert!(!inlines
+            .iter()
+            .any(|i| matches!(i, Inline::NonBreakingSpace)));
+    }
+
+    #[test]
+    fn test_subscript_and_superscript() {
+        let text = |s: &str| Inline::Text(s.to_string());
+        assert_eq!(
+            parse_inlines("H~2~O").unwrap(),
+            vec![text("H"), Inline::Subscript(vec![text("2")]), text("O")]
+        );
+        assert_eq!(
+            parse_inlines("x^2^").unwrap(),
+            vec![text("x"), Inline::Superscript(vec![text("2")])]
+        );
+        assert_eq!(
+            parse_inlines("a~~b~~c").unwrap(),
+            vec![text("a"), Inline::Strikethrough(vec![text("b")]), text("c")]
+        );
+        assert_eq!(
+            parse_inlines("a~b~c").unwrap(),
+            vec![text("a"), Inline::Subscript(vec![text("b")]), text("c")]
+        );
+        assert!(!parse_inlines("a^2 + b^2 = c^2")
+            .unwrap()
+            .iter()
+            .any(|inline| matches!(inline, Inline::Superscript(_))));
+    }
+
+    #[test]
+    fn test_small_caps() {
+        let text = |s: &str| Inline::Text(s.to_string());
+        let expected = vec![
+            text("By "),
+            Inline::SmallCaps(vec![text("Knuth "), Inline::Strong(vec![text("TeX")])]),
+            text("."),
+        ];
+        assert_eq!(
+            parse_inlines("By [sc]Knuth **TeX**[/sc].").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_inlines("By [Knuth **TeX**]{.smallcaps}.").unwrap(),
+            expected
+        );
+
+        // Unclosed forms keep their characters
+        let inlines = parse_inlines("[sc]open and [x]{.small}").unwrap();
+        assert!(!inlines
+            .iter()
+            .any(|inline| matches!(inline, Inline::SmallCaps(_))));
+    }
+
+    #[test]
+    fn test_lang_span() {
+        let text = |s: &str| Inline::Text(s.to_string());
+        assert_eq!(
+            parse_inlines(r#"[*bonjour*]{lang=fr} and [hallo]{lang="de-AT"}"#).unwrap(),
+            vec![
+                Inline::Lang {
+                    lang: Some("fr".to_string()),
+                    dir: None,
+                    content: vec![Inline::Emphasis(vec![text("bonjour")])],
+                },
+                text(" and "),
+                Inline::Lang {
+                    lang: Some("de-AT".to_string()),
+                    dir: None,
+                    content: vec![text("hallo")],
+                },
+            ]
+        );
+
+        assert_eq!(
+            parse_inlines(r#"[שלום]{dir=rtl} [مرحبا]{lang=ar dir="rtl"}"#).unwrap(),
+            vec![
+                Inline::Lang {
+                    lang: None,
+                    dir: Some("rtl".to_string()),
+                    content: vec![text("שלום")],
+                },
+                text(" "),
+                Inline::Lang {
+                    lang: Some("ar".to_string()),
+                    dir: Some("rtl".to_string()),
+                    content: vec![text("مرحبا")],
+                },
+            ]
+        );
+
+        // Unknown keys and directions are not spans
+        for input in ["[a]{dir=up}", "[a]{lang=fr class=x}", "[a]{}"] {
+            assert!(!parse_inlines(input)
+                .unwrap()
+                .iter()
+                .any(|inline| matches!(inline, Inline::Lang { .. })));
+        }
+    }
+
+    #[test]
+    fn test_adjacent_footnote_references() {
+        let inlines = parse_inlines("text[^1][^note-2]").unwrap();
+        assert_eq!(
+            inlines,
+            vec![
+                Inline::Text("text".to_string()),
+                Inline::Footnote(FootnoteKind::Reference("1".to_string())),
+                Inline::Footnote(FootnoteKind::Reference("note-2".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_math_escaped_dollar() {
+        let inlines = parse_inlines(r"Cost $a \$ b$ total.").unwrap();
+        assert_eq!(inlines[1], Inline::InlineMath(r"a \$ b".to_string()));
+        assert_eq!(inlines.len(), 3);
+    }
+
     #[test]
     fn test_footnote_inline() {
         let inlines = parse_inlines("Some text^[This is a note].").unwrap();
@@ -653,4 +1108,46 @@ mod tests {
             .count();
         assert_eq!(fn_count, 1);
     }
+
+    #[test]
+    fn test_nesting_depth_limit() {
+        let nested = |n: usize| format!("{}x{}", "[".repeat(n), "](u)".repeat(n));
+
+        let options = ParseOptions {
+            max_nesting_depth: 8,
+            ..Default::default()
+        };
+        assert!(parse_inlines_with_options(&nested(8), &options).is_ok());
+        assert!(matches!(
+            parse_inlines_with_options(&nested(9), &options),
+            Err(crate::error::Error::Parse(ParseError::NestingTooDeep(8)))
+        ));
+
+        // Far past the default limit: an error, not a stack overflow
+        assert!(parse_inlines(&nested(10_000)).is_err());
+    }
+
+    #[test]
+    fn test_span_length_limits() {
+        // A stray backtick far from the next one stays literal
+        let long = "word ".repeat(1000);
+        let input = format!("Use ` for code. {}and `x` here.", long);
+        let inlines = parse_inlines(&input).unwrap();
+        let code: Vec<_> = inlines
+            .iter()
+            .filter(|inline| matches!(inline, Inline::Code(_)))
+            .collect();
+        assert_eq!(code, [&Inline::Code("x".to_string())]);
+
+        let options = ParseOptions {
+            max_inline_math_len: 3,
+            ..Default::default()
+        };
+        let inlines = parse_inlines_with_options("$abc$ and $abcd$", &options).unwrap();
+        let math: Vec<_> = inlines
+            .iter()
+            .filter(|inline| matches!(inline, Inline::InlineMath(_)))
+            .collect();
+        assert_eq!(math, [&Inline::InlineMath("abc".to_string())]);
+    }
 }
@@ -4,10 +4,11 @@
 //! and are not all consumed by the current block parser yet.
 #![allow(dead_code)]
 
+use alloc::vec::Vec;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_until, take_while1},
-    character::complete::{char, line_ending, not_line_ending, space0, space1},
+    character::complete::{alpha1, char, line_ending, not_line_ending, space0, space1},
     combinator::{map, opt, peek, recognize, value},
     multi::many0,
     sequence::{delimited, pair},
@@ -37,13 +38,15 @@ pub enum Token<'a> {
     InlineCode(&'a str),
     InlineMath(&'a str),
     DisplayMath(&'a str),
-    Citation(Vec<CitationToken<'a>>),
-    Reference(&'a str),                       // @label
-    FootnoteInline(&'a str),                  // ^[content]
-    FootnoteRef(&'a str),                     // [^id]
-    Link(&'a str, &'a str, Option<&'a str>),  // text, url, title
-    Image(&'a str, &'a str, Option<&'a str>), // alt, url, title
-    Label(&'a str),                           // {#label}
+    Citation(Option<&'a str>, Vec<CitationToken<'a>>, bool), // [see @a; @b], year-only [-@a] when true
+    Reference(&'a str, Option<&'a str>),                     // @label, {hint}
+    FootnoteInline(&'a str),                                 // ^[content]
+    SmallCaps(&'a str),                                      // [sc]text[/sc] or [text]{.smallcaps}
+    LangSpan(&'a str, Option<&'a str>, Option<&'a str>), // [text]{lang=ar dir=rtl}: content, lang, dir
+    FootnoteRef(&'a str),                                // [^id]
+    Link(&'a str, &'a str, Option<&'a str>),             // text, url, title
+    Image(&'a str, &'a str, Option<&'a str>),            // alt, url, title
+    Label(&'a str),                                      // {#label}
     SoftBreak,
     HardBreak,
     RawHtml(&'a str),
@@ -187,7 +190,7 @@ pub fn list_item_marker(input: &str) -> IResult<&str, Token<'_>> {
 pub fn inline_math(input: &str) -> IResult<&str, Token<'_>> {
     let (input, _) = char('$')(input)?;
     let (input, _) = peek(nom::combinator::not(char('$'))).parse(input)?; // Not display math
-    let (input, content) = take_until("$")(input)?;
+    let (input, content) = math_until("$")(input)?;
     let (input, _) = char('$')(input)?;
     Ok((input, Token::InlineMath(content)))
 }
@@ -195,22 +198,99 @@ pub fn inline_math(input: &str) -> IResult<&str, Token<'_>> {
 /// Parse display math ($$...$$).
 pub fn display_math(input: &str) -> IResult<&str, Token<'_>> {
     let (input, _) = tag("$$")(input)?;
-    let (input, content) = take_until("$$")(input)?;
+    let (input, content) = math_until("$$")(input)?;
     let (input, _) = tag("$$")(input)?;
     Ok((input, Token::DisplayMath(content)))
 }
 
+/// Take math content up to the closing delimiter (see [`find_math_close`]).
+fn math_until(delim: &'static str) -> impl Fn(&str) -> IResult<&str, &str> {
+    move |input: &str| match find_math_close(input, delim, &mut 0) {
+        Some(end) => Ok((&input[end..], &input[..end])),
+        None => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::TakeUntil,
+        ))),
+    }
+}
+
+/// Commands whose braced argument is text, where a `$` does not end the math.
+const TEXT_COMMANDS: &[&str] = &[
+    "text",
+    "textrm",
+    "textit",
+    "textbf",
+    "textsf",
+    "texttt",
+    "textnormal",
+    "mbox",
+    "hbox",
+];
+
+/// Find the closing math delimiter in `input`, skipping backslash escapes
+/// (`\$`) and the arguments of text commands (`\text{costs $$}`). Other
+/// braces are ignored, so an unbalanced `{` does not hide the delimiter.
+///
+/// `depth` is the brace depth inside a text argument at the start of
+/// `input`; it is updated so a scan can continue across lines.
+pub fn find_math_close(input: &str, delim: &str, depth: &mut usize) -> Option<usize> {
+    let mut chars = input.char_indices();
+    // Bytes up to here belong to a command name already handled
+    let mut skip = 0;
+    // Whether the next `{` opens a text command's argument
+    let mut text_arg = false;
+    while let Some((i, c)) = chars.next() {
+        if i < skip {
+            continue;
+        }
+        match c {
+            '\\' => {
+                let rest = &input[i + 1..];
+                let name_len = rest
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(rest.len());
+                if name_len == 0 {
+                    chars.next();
+                } else {
+                    text_arg = TEXT_COMMANDS.contains(&&rest[..name_len])
+                        && rest[name_len..].starts_with('{');
+                    skip = i + 1 + name_len;
+                }
+                continue;
+            }
+            '{' if *depth > 0 || text_arg => *depth += 1,
+            '}' if *depth > 0 => *depth -= 1,
+            _ if *depth == 0 && input[i..].starts_with(delim) => return Some(i),
+            _ => {}
+        }
+        text_arg = false;
+    }
+    None
+}
+
 /// Parse a citation ([@key] or [@key, p. 42]).
 pub fn citation(input: &str) -> IResult<&str, Token<'_>> {
-    let (input, _) = tag("[@")(input)?;
+    let (input, _) = char('[')(input)?;
     let (input, content) = take_until("]")(input)?;
     let (input, _) = char(']')(input)?;
 
+    // Text before the first key marker is the prefix (`[see @knuth1984]`)
+    let Some(marker) = citation_key_marker(content).filter(|&m| !content[..m].contains('[')) else {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            content,
+            nom::error::ErrorKind::Char,
+        )));
+    };
+    let prefix = content[..marker].trim();
+    let content = &content[marker..];
+    let year_only = content.starts_with('-');
+
     // Parse citation content: key1; key2, locator
     let citations: Vec<CitationToken> = content
         .split(';')
         .map(|part| {
-            let part = part.trim();
+            // Keys after the first carry their own `@` (`[@a; @b]`, `[-@a; -@b]`)
+            let part = part.trim().trim_start_matches('-').trim_start_matches('@');
             if let Some((key, locator)) = part.split_once(',') {
                 CitationToken {
                     key: key.trim(),
@@ -225,17 +305,51 @@ pub fn citation(input: &str) -> IResult<&str, Token<'_>> {
         })
         .collect();
 
-    Ok((input, Token::Citation(citations)))
+    // Bracketed prose that happens to contain `@` (`[ask @bob about it]`)
+    if citations
+        .iter()
+        .any(|c| c.key.is_empty() || c.key.contains(char::is_whitespace))
+    {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            content,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+
+    let prefix = (!prefix.is_empty()).then_some(prefix);
+    Ok((input, Token::Citation(prefix, citations, year_only)))
+}
+
+/// Byte offset of the first citation key marker (`@key` or `-@key`) in
+/// bracket content. The `@` must start a word and be followed by an
+/// alphanumeric, so `user@example.com` is not a key.
+fn citation_key_marker(content: &str) -> Option<usize> {
+    let bytes = content.as_bytes();
+    content.match_indices('@').find_map(|(at, _)| {
+        let starts_key = content[at + 1..]
+            .chars()
+            .next()
+            .is_some_and(char::is_alphanumeric);
+        let start = if at > 0 && bytes[at - 1] == b'-' {
+            at - 1
+        } else {
+            at
+        };
+        let word_start = start == 0 || bytes[start - 1].is_ascii_whitespace();
+        (starts_key && word_start).then_some(start)
+    })
 }
 
-/// Parse a cross-reference (@label).
+/// Parse a cross-reference (@label), with an optional display hint
+/// (`@label{bare}`).
 pub fn reference(input: &str) -> IResult<&str, Token<'_>> {
     let (input, _) = char('@')(input)?;
     // Ensure it's not a citation
     let (input, _) = peek(nom::combinator::not(char('['))).parse(input)?;
     let (input, label) =
         take_while1(|c: char| c.is_alphanumeric() || c == ':' || c == '-' || c == '_')(input)?;
-    Ok((input, Token::Reference(label)))
+    let (input, hint) = opt(delimited(char('{'), alpha1, char('}'))).parse(input)?;
+    Ok((input, Token::Reference(label, hint)))
 }
 
 /// Parse an inline footnote (^[content]).
@@ -246,6 +360,69 @@ pub fn footnote_inline(input: &str) -> IResult<&str, Token<'_>> {
     Ok((input, Token::FootnoteInline(content)))
 }
 
+/// Parse small caps, written `[sc]text[/sc]` or as the attribute span
+/// `[text]{.smallcaps}`.
+pub fn small_caps(input: &str) -> IResult<&str, Token<'_>> {
+    if let Some(after) = input.strip_prefix("[sc]") {
+        let (input, content) = take_until("[/sc]")(after)?;
+        let (input, _) = tag("[/sc]")(input)?;
+        return Ok((input, Token::SmallCaps(content)));
+    }
+    let (input, _) = char('[')(input)?;
+    let (input, content) = take_until("]{.smallcaps}")(input)?;
+    if content.is_empty() || content.contains(['[', ']']) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+    let (input, _) = tag("]{.smallcaps}")(input)?;
+    Ok((input, Token::SmallCaps(content)))
+}
+
+/// Parse a language or direction span: `[text]{lang=fr}`, `[text]{dir=rtl}`
+/// or both, space-separated (values may be quoted).
+pub fn lang_span(input: &str) -> IResult<&str, Token<'_>> {
+    let verify_error =
+        |input| nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify));
+
+    let (input, _) = char('[')(input)?;
+    let (input, content) = take_until("]{")(input)?;
+    if content.is_empty() || content.contains(['[', ']']) {
+        return Err(verify_error(input));
+    }
+    let (mut input, _) = tag("]{")(input)?;
+
+    let mut lang = None;
+    let mut dir = None;
+    loop {
+        input = input.trim_start_matches(' ');
+        if let Some(rest) = input.strip_prefix('}') {
+            input = rest;
+            break;
+        }
+        let (rest, key) = alt((tag("lang"), tag("dir"))).parse(input)?;
+        let (rest, _) = char('=')(rest)?;
+        let (rest, quoted) = opt(char('"')).parse(rest)?;
+        let (rest, value) = take_while1(|c: char| c.is_ascii_alphanumeric() || c == '-')(rest)?;
+        let (rest, _) = match quoted {
+            Some(_) => char('"')(rest)?,
+            None => (rest, ' '),
+        };
+        match key {
+            "lang" => lang = Some(value),
+            _ if matches!(value, "ltr" | "rtl" | "auto") => dir = Some(value),
+            _ => return Err(verify_error(input)),
+        }
+        input = rest;
+    }
+
+    if lang.is_none() && dir.is_none() {
+        return Err(verify_error(input));
+    }
+    Ok((input, Token::LangSpan(content, lang, dir)))
+}
+
 /// Parse a footnote reference ([^id]).
 pub fn footnote_ref(input: &str) -> IResult<&str, Token<'_>> {
     let (input, _) = tag("[^")(input)?;
@@ -333,23 +510,46 @@ mod tests {
     fn test_citation() {
         let result = citation("[@knuth1984]");
         assert!(result.is_ok());
-        if let Ok((_, Token::Citation(cites))) = result {
+        if let Ok((_, Token::Citation(_, cites, _))) = result {
             assert_eq!(cites.len(), 1);
             assert_eq!(cites[0].key, "knuth1984");
         }
 
         let result = citation("[@knuth1984, p. 42]");
         assert!(result.is_ok());
-        if let Ok((_, Token::Citation(cites))) = result {
+        if let Ok((_, Token::Citation(_, cites, _))) = result {
             assert_eq!(cites[0].locator, Some("p. 42"));
         }
+
+        let (_, token) = citation("[-@knuth1984; -@lamport1994, p. 3]").unwrap();
+        let Token::Citation(_, cites, year_only) = token else {
+            panic!("Expected citation");
+        };
+        assert!(year_only);
+        assert_eq!(cites[1].key, "lamport1994");
+        assert_eq!(cites[1].locator, Some("p. 3"));
+
+        let (_, token) = citation("[see @knuth1984, p. 42]").unwrap();
+        let Token::Citation(prefix, cites, _) = token else {
+            panic!("Expected citation");
+        };
+        assert_eq!(prefix, Some("see"));
+        assert_eq!(cites[0].key, "knuth1984");
+        assert_eq!(cites[0].locator, Some("p. 42"));
+
+        assert!(citation("[mail user@example.com]").is_err());
+        assert!(citation("[@ 42]").is_err());
     }
 
     #[test]
     fn test_reference() {
         assert_eq!(
             reference("@eq:euler"),
-            Ok(("", Token::Reference("eq:euler")))
+            Ok(("", Token::Reference("eq:euler", None)))
+        );
+        assert_eq!(
+            reference("@eq:euler{bare} holds"),
+            Ok((" holds", Token::Reference("eq:euler", Some("bare"))))
         );
     }
 
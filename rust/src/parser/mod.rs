@@ -3,27 +3,290 @@
 mod block;
 mod inline;
 mod lexer;
+mod units;
 
-pub use block::parse_blocks;
-pub use inline::parse_inlines;
+pub use block::{parse_blocks, parse_blocks_with_options};
+pub use inline::{
+    parse_inlines, parse_inlines_with_options, DEFAULT_MAX_CODE_SPAN_LEN,
+    DEFAULT_MAX_INLINE_MATH_LEN, DEFAULT_MAX_NESTING_DEPTH,
+};
 
-use crate::ast::{Document, Macro, Metadata};
+use crate::ast::{Author, CitationFormat, Document, Macro, Metadata, NumberingScheme};
+use crate::collections::HashMap;
 use crate::error::{ParseError, Result};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use serde::Deserialize;
-use std::collections::HashMap;
+
+/// Options controlling which syntax the parser recognizes.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Whether runs of lines indented by four columns outside of lists are
+    /// parsed as code blocks. On by default.
+    pub indented_code: bool,
+    /// Whether a line holding only a single `$...$` span, surrounded by
+    /// blank lines, is promoted to display math.
+    pub promote_lonely_math: bool,
+    /// How deeply inline elements (emphasis, links, footnotes, ...) may
+    /// nest before parsing fails with [`ParseError::NestingTooDeep`].
+    pub max_nesting_depth: usize,
+    /// Whether leading and trailing whitespace is trimmed from inline and
+    /// display math. By default math content is kept verbatim.
+    pub trim_math: bool,
+    /// Longest inline math span, in bytes. A `$` whose span would run longer
+    /// is literal text, so a stray `$` can't swallow the rest of a paragraph.
+    pub max_inline_math_len: usize,
+    /// Longest code span, in bytes; a longer one leaves its backtick literal.
+    pub max_code_span_len: usize,
+    /// Columns between tab stops. Tabs in a line's indentation are expanded
+    /// to spaces before block parsing, so indentation is measured in
+    /// columns; tabs elsewhere, and inside fenced code, are kept.
+    pub tab_width: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            indented_code: true,
+            promote_lonely_math: false,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+            trim_math: false,
+            max_inline_math_len: DEFAULT_MAX_INLINE_MATH_LEN,
+            max_code_span_len: DEFAULT_MAX_CODE_SPAN_LEN,
+            tab_width: 4,
+        }
+    }
+}
 
 /// Parse a complete document from source text.
+///
+/// Empty or whitespace-only input yields a document with no blocks.
 pub fn parse(input: &str) -> Result<Document> {
-    let (metadata, content) = parse_front_matter(input)?;
-    let blocks = parse_blocks(content)?;
+    parse_with_options(input, &ParseOptions::default())
+}
+
+/// Parse a complete document from source text with the given options.
+pub fn parse_with_options(input: &str, options: &ParseOptions) -> Result<Document> {
+    let (mut metadata, content) = parse_front_matter(input)?;
+    // 1-based source line of the first content line; the extractors below
+    // keep every line in place
+    let first_line = input[..input.len() - content.len()].matches('\n').count() + 1;
+    let (content, abbreviations) = extract_abbreviations(content);
+    metadata.abbreviations = abbreviations;
+    let (content, link_definitions) = extract_link_definitions(&content);
+    metadata.link_definitions = link_definitions;
+    let (content, footnote_definitions) = extract_footnote_definitions(&content);
+    for (id, (line, text)) in footnote_definitions {
+        let inlines = parse_inlines_with_options(&text, options)?;
+        metadata
+            .footnote_lines
+            .insert(id.clone(), first_line + line);
+        metadata.footnote_definitions.insert(id, inlines);
+    }
+    let (blocks, lines) = block::parse_blocks_with_lines(&content, options)?;
+    let block_lines = lines.into_iter().map(|line| first_line + line).collect();
+
+    Ok(Document {
+        metadata,
+        blocks,
+        block_lines,
+    })
+}
+
+/// Tracks fenced code blocks line by line, so the definition extractors and
+/// tab expansion leave their contents alone. As in block parsing, a fence
+/// opened with ```` ``` ```` is closed only by ```` ``` ````, and `~~~` only
+/// by `~~~`.
+#[derive(Default)]
+struct FenceTracker {
+    open: Option<&'static str>,
+}
+
+impl FenceTracker {
+    /// Advance past a line (without its indentation), returning whether it
+    /// is a fence line or inside fenced code.
+    fn in_fence(&mut self, trimmed: &str) -> bool {
+        let fence = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f));
+        match (self.open, fence) {
+            (Some(open), Some(fence)) if open == fence => self.open = None,
+            (Some(_), _) => {}
+            (None, Some(fence)) => self.open = Some(fence),
+            (None, None) => return false,
+        }
+        true
+    }
+}
+
+/// Blank out definition lines in the source. `define` sees each line
+/// outside fenced code with its 0-based index and whether it starts a block
+/// (follows a blank line, a closing fence, another definition or the start
+/// of input), and
+/// returns whether the line belongs to a definition.
+///
+/// Definition lines become empty rather than being removed, so later lines
+/// keep their source line numbers.
+fn blank_definitions<'a>(
+    input: &'a str,
+    mut define: impl FnMut(usize, &'a str, bool) -> bool,
+) -> String {
+    let mut lines = Vec::new();
+    let mut fences = FenceTracker::default();
+    let mut block_start = true;
+
+    for (index, line) in input.lines().enumerate() {
+        if fences.open.is_none() && define(index, line, block_start) {
+            lines.push("");
+            block_start = true;
+            continue;
+        }
+
+        let was_fenced = fences.open.is_some();
+        fences.in_fence(line.trim_start());
+        // A closing fence ends its block too
+        block_start = line.trim().is_empty() || (was_fenced && fences.open.is_none());
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+/// Blank out abbreviation definition lines (`*[HTML]: HyperText Markup
+/// Language`) in the source and collect them.
+fn extract_abbreviations(input: &str) -> (String, HashMap<String, String>) {
+    let mut abbreviations = HashMap::new();
+    let content = blank_definitions(input, |_, line, _| {
+        match parse_abbreviation_definition(line.trim_start()) {
+            Some((abbr, title)) => {
+                abbreviations.insert(abbr.to_string(), title.to_string());
+                true
+            }
+            None => false,
+        }
+    });
+
+    (content, abbreviations)
+}
+
+fn parse_abbreviation_definition(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("*[")?;
+    let (abbr, rest) = rest.split_once("]:")?;
+    let abbr = abbr.trim();
+    if abbr.is_empty() || abbr.contains(']') {
+        return None;
+    }
+    Some((abbr, rest.trim()))
+}
+
+/// Blank out link definition lines (`[id]: https://example.com "Title"`) in
+/// the source and collect them by normalized id. The first definition of an
+/// id wins. As in CommonMark, a definition can't interrupt a paragraph.
+fn extract_link_definitions(input: &str) -> (String, HashMap<String, (String, Option<String>)>) {
+    let mut definitions = HashMap::new();
+    let content = blank_definitions(input, |_, line, block_start| {
+        if !block_start {
+            return false;
+        }
+        match parse_link_definition(line.trim_start()) {
+            Some((id, url, title)) => {
+                definitions
+                    .entry(normalize_link_id(id))
+                    .or_insert_with(|| (url.to_string(), title.map(String::from)));
+                true
+            }
+            None => false,
+        }
+    });
 
-    Ok(Document { metadata, blocks })
+    (content, definitions)
 }
 
-/// Parse TOML front matter delimited by `+++`.
+fn parse_link_definition(line: &str) -> Option<(&str, &str, Option<&str>)> {
+    if line.starts_with("[^") || line.starts_with("[@") || line.starts_with("[[") {
+        return None;
+    }
+    let rest = line.strip_prefix('[')?;
+    let (id, rest) = rest.split_once("]:")?;
+    if id.trim().is_empty() || id.contains(['[', ']']) {
+        return None;
+    }
+    let (url, title) = inline::parse_url_and_title(rest);
+    let url = url
+        .strip_prefix('<')
+        .and_then(|u| u.strip_suffix('>'))
+        .unwrap_or(url);
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((id, url, title))
+}
+
+/// Blank out footnote definitions (`[^id]: text`) in the source and collect
+/// their 0-based line and raw text by id. A definition continues over the
+/// indented lines that follow it, including ones after blank lines, so a
+/// note can hold several paragraphs (joined into one). The first definition
+/// of an id wins.
+fn extract_footnote_definitions(input: &str) -> (String, HashMap<String, (usize, String)>) {
+    let mut definitions = HashMap::new();
+    // Id, 0-based line and text so far of the definition being read
+    let mut current: Option<(&str, usize, String)> = None;
+
+    let content = blank_definitions(input, |index, line, _| {
+        if let Some((id, start, text)) = current.take() {
+            if line.trim().is_empty() {
+                current = Some((id, start, text));
+                return false;
+            }
+            if line.starts_with([' ', '\t']) {
+                current = Some((id, start, text + "\n" + line.trim()));
+                return true;
+            }
+            definitions.entry(id.to_string()).or_insert((start, text));
+        }
+
+        match parse_footnote_definition(line.trim_start()) {
+            Some((id, text)) => {
+                current = Some((id, index, text.to_string()));
+                true
+            }
+            None => false,
+        }
+    });
+    if let Some((id, start, text)) = current {
+        definitions.entry(id.to_string()).or_insert((start, text));
+    }
+
+    (content, definitions)
+}
+
+fn parse_footnote_definition(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("[^")?;
+    let (id, text) = rest.split_once("]:")?;
+    if id.is_empty() || id.contains(|c: char| c.is_whitespace() || c == '[' || c == ']') {
+        return None;
+    }
+    Some((id, text.trim()))
+}
+
+/// Normalize a link definition id for matching: case-insensitive, with runs
+/// of whitespace collapsed to one space.
+pub fn normalize_link_id(id: &str) -> String {
+    id.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Parse TOML front matter delimited by `+++`, or YAML front matter
+/// delimited by `---` (with the `yaml` feature).
 fn parse_front_matter(input: &str) -> Result<(Metadata, &str)> {
     let trimmed = input.trim_start();
 
+    #[cfg(feature = "yaml")]
+    if let Some(parsed) = parse_yaml_front_matter(trimmed) {
+        return parsed;
+    }
+
     if !trimmed.starts_with("+++") {
         return Ok((Metadata::default(), input));
     }
@@ -40,18 +303,122 @@ fn parse_front_matter(input: &str) -> Result<(Metadata, &str)> {
     let raw: RawFrontMatter = toml::from_str(front_matter_str)
         .map_err(|e| ParseError::FrontMatter(format!("Invalid TOML: {}", e)))?;
 
-    let metadata = convert_front_matter(raw);
+    let metadata = convert_front_matter(raw)?;
 
     Ok((metadata, content))
 }
 
+/// Parse YAML front matter between `---` lines. As in Pandoc, only a `---`
+/// that is the first content of the document, is not followed by a blank
+/// line and has a closing `---` line counts, and the block between them must
+/// be a mapping; anything else is left to the block parser as a thematic
+/// break.
+#[cfg(feature = "yaml")]
+fn parse_yaml_front_matter(trimmed: &str) -> Option<Result<(Metadata, &str)>> {
+    use yaml_rust2::{Yaml, YamlLoader};
+
+    let first_line_end = trimmed.find('\n')?;
+    if trimmed[..first_line_end].trim_end() != "---" {
+        return None;
+    }
+
+    let body = &trimmed[first_line_end + 1..];
+    if body
+        .lines()
+        .next()
+        .is_some_and(|line| line.trim().is_empty())
+    {
+        return None;
+    }
+    let mut offset = 0;
+    let close = body.split_inclusive('\n').find_map(|line| {
+        let start = offset;
+        offset += line.len();
+        (line.trim_end() == "---").then_some((start, offset))
+    });
+    let (close_start, close_end) = close?;
+    let content = body[close_end..].trim_start_matches(['\r', '\n']);
+
+    let docs = match YamlLoader::load_from_str(&body[..close_start]) {
+        Ok(docs) => docs,
+        Err(e) => {
+            return Some(Err(
+                ParseError::FrontMatter(format!("Invalid YAML: {}", e)).into()
+            ))
+        }
+    };
+    let parsed = match docs.into_iter().next() {
+        None => Ok(Metadata::default()),
+        Some(yaml @ Yaml::Hash(_)) => yaml_to_toml(yaml).and_then(|value| {
+            let raw: RawFrontMatter = value
+                .try_into()
+                .map_err(|e| ParseError::FrontMatter(format!("Invalid YAML: {}", e)))?;
+            convert_front_matter(raw)
+        }),
+        Some(_) => return None,
+    };
+    Some(parsed.map(|metadata| (metadata, content)))
+}
+
+/// Convert a YAML value to the TOML value it corresponds to, so both kinds
+/// of front matter share [`RawFrontMatter`]. Null entries are dropped.
+#[cfg(feature = "yaml")]
+fn yaml_to_toml(yaml: yaml_rust2::Yaml) -> Result<toml::Value> {
+    use yaml_rust2::Yaml;
+
+    let value = match yaml {
+        Yaml::String(s) => toml::Value::String(s),
+        Yaml::Integer(i) => toml::Value::Integer(i),
+        Yaml::Real(r) => toml::Value::Float(
+            r.parse()
+                .map_err(|_| ParseError::FrontMatter(format!("Invalid YAML number: {}", r)))?,
+        ),
+        Yaml::Boolean(b) => toml::Value::Boolean(b),
+        Yaml::Array(items) => toml::Value::Array(
+            items
+                .into_iter()
+                .filter(|item| !item.is_null())
+                .map(yaml_to_toml)
+                .collect::<Result<_>>()?,
+        ),
+        Yaml::Hash(entries) => {
+            let mut table = toml::Table::new();
+            for (key, value) in entries {
+                if value.is_null() {
+                    continue;
+                }
+                let key = match key {
+                    Yaml::String(s) => s,
+                    Yaml::Integer(i) => i.to_string(),
+                    Yaml::Real(r) => r,
+                    Yaml::Boolean(b) => b.to_string(),
+                    other => {
+                        return Err(ParseError::FrontMatter(format!(
+                            "Invalid YAML: unsupported key {:?}",
+                            other
+                        ))
+                        .into())
+                    }
+                };
+                table.insert(key, yaml_to_toml(value)?);
+            }
+            toml::Value::Table(table)
+        }
+        Yaml::Null => toml::Value::Table(toml::Table::new()),
+        Yaml::Alias(_) | Yaml::BadValue => {
+            return Err(ParseError::FrontMatter("Invalid YAML value".into()).into())
+        }
+    };
+    Ok(value)
+}
+
 /// Raw front matter structure for deserialization.
 #[derive(Debug, Deserialize, Default)]
 struct RawFrontMatter {
     title: Option<String>,
     subtitle: Option<String>,
     #[serde(default)]
-    authors: Vec<String>,
+    authors: Vec<RawAuthor>,
     author: Option<String>,
     date: Option<String>,
     #[serde(rename = "abstract")]
@@ -65,6 +432,43 @@ struct RawFrontMatter {
     #[serde(default)]
     macros: HashMap<String, String>,
     bibliography: Option<BibliographyConfig>,
+    citation_style: Option<String>,
+    toc_depth: Option<u8>,
+    number_equations: Option<String>,
+    #[serde(default)]
+    nocite: Vec<String>,
+}
+
+/// An author given either as a plain name or as a table with details.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawAuthor {
+    Name(String),
+    Details {
+        name: String,
+        email: Option<String>,
+        institution: Option<String>,
+        institution_url: Option<String>,
+    },
+}
+
+impl From<RawAuthor> for Author {
+    fn from(raw: RawAuthor) -> Self {
+        match raw {
+            RawAuthor::Name(name) => Author::new(name),
+            RawAuthor::Details {
+                name,
+                email,
+                institution,
+                institution_url,
+            } => Author {
+                name,
+                email,
+                institution,
+                institution_url,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -75,7 +479,7 @@ enum BibliographyConfig {
 }
 
 /// Convert raw front matter to metadata.
-fn convert_front_matter(raw: RawFrontMatter) -> Metadata {
+fn convert_front_matter(raw: RawFrontMatter) -> Result<Metadata> {
     let macros = raw
         .macros
         .into_iter()
@@ -91,10 +495,10 @@ fn convert_front_matter(raw: RawFrontMatter) -> Metadata {
         })
         .collect();
 
-    let mut authors = raw.authors;
+    let mut authors: Vec<Author> = raw.authors.into_iter().map(Author::from).collect();
     if let Some(author) = raw.author {
         if authors.is_empty() {
-            authors.push(author);
+            authors.push(Author::new(author));
         }
     }
 
@@ -103,7 +507,24 @@ fn convert_front_matter(raw: RawFrontMatter) -> Metadata {
         BibliographyConfig::Config { path } => path,
     });
 
-    Metadata {
+    let citation_style = raw
+        .citation_style
+        .map(|name| {
+            CitationFormat::from_name(&name)
+                .ok_or_else(|| ParseError::FrontMatter(format!("Unknown citation_style: {}", name)))
+        })
+        .transpose()?;
+
+    let numbering_scheme = raw
+        .number_equations
+        .map(|name| {
+            NumberingScheme::from_name(&name).ok_or_else(|| {
+                ParseError::FrontMatter(format!("Unknown number_equations: {}", name))
+            })
+        })
+        .transpose()?;
+
+    Ok(Metadata {
         macros,
         bibliography_path,
         title: raw.title,
@@ -116,7 +537,15 @@ fn convert_front_matter(raw: RawFrontMatter) -> Metadata {
         department: raw.department,
         advisor: raw.advisor,
         lang: raw.lang,
-    }
+        abbreviations: HashMap::new(),
+        link_definitions: HashMap::new(),
+        footnote_definitions: HashMap::new(),
+        footnote_lines: HashMap::new(),
+        citation_style,
+        toc_depth: raw.toc_depth,
+        numbering_scheme,
+        nocite: raw.nocite,
+    })
 }
 
 /// Count the number of macro arguments (#1, #2, etc.) in a template.
@@ -166,13 +595,133 @@ Some text."#;
 
         let (meta, content) = parse_front_matter(input).unwrap();
         assert_eq!(meta.title, Some("My Document".to_string()));
-        assert_eq!(meta.authors, vec!["Jane Doe".to_string()]);
+        assert_eq!(meta.authors, vec![Author::new("Jane Doe")]);
         assert_eq!(meta.macros.get("R").unwrap().template, "\\mathbb{R}");
         assert_eq!(meta.macros.get("R").unwrap().arg_count, 0);
         assert_eq!(meta.macros.get("vec").unwrap().arg_count, 1);
         assert!(content.starts_with("# Hello"));
     }
 
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_yaml_front_matter() {
+        let input = r#"---
+title: My Document
+authors:
+  - Plain Name
+  - name: Ada Lovelace
+    email: ada@example.org
+date: 2024-01-15
+macros:
+  R: "\\mathbb{R}"
+bibliography: refs.bib
+---
+
+# Hello
+
+---
+
+More."#;
+
+        let (meta, content) = parse_front_matter(input).unwrap();
+        assert_eq!(meta.title, Some("My Document".to_string()));
+        assert_eq!(meta.authors[0], Author::new("Plain Name"));
+        assert_eq!(meta.authors[1].email, Some("ada@example.org".to_string()));
+        assert_eq!(meta.date, Some("2024-01-15".to_string()));
+        assert_eq!(meta.macros.get("R").unwrap().template, "\\mathbb{R}");
+        assert_eq!(meta.bibliography_path, Some("refs.bib".to_string()));
+        assert!(content.starts_with("# Hello"));
+
+        // A thematic break later in the document is not front matter
+        let (meta, content) = parse_front_matter("Intro.\n\n---\n\nMore.\n").unwrap();
+        assert_eq!(meta, Metadata::default());
+        assert_eq!(content, "Intro.\n\n---\n\nMore.\n");
+
+        // Nor is a leading break followed by a blank line or by prose
+        let input = "---\n\nIntro.\n\n---\n\nMore.";
+        let (meta, content) = parse_front_matter(input).unwrap();
+        assert_eq!(meta, Metadata::default());
+        assert_eq!(content, input);
+        let (_, content) = parse_front_matter("---\nIntro.\n---\n\nMore.").unwrap();
+        assert!(content.starts_with("---\nIntro."));
+
+        let err = parse_front_matter("---\ntitle: [unclosed\n---\n").unwrap_err();
+        assert!(err.to_string().contains("Invalid YAML"));
+    }
+
+    #[test]
+    fn test_structured_authors() {
+        let input = r#"+++
+authors = [
+    "Plain Name",
+    { name = "Ada Lovelace", email = "ada@example.org", institution = "Analytical Society" },
+]
++++
+"#;
+        let (meta, _) = parse_front_matter(input).unwrap();
+        assert_eq!(meta.authors[0], Author::new("Plain Name"));
+        assert_eq!(meta.authors[1].email.as_deref(), Some("ada@example.org"));
+        assert_eq!(
+            meta.authors[1].institution.as_deref(),
+            Some("Analytical Society")
+        );
+    }
+
+    #[test]
+    fn test_extract_abbreviations() {
+        let input = "The HTML spec.\n\n*[HTML]: HyperText Markup Language\n```\n*[X]: kept\n```";
+        let (content, abbrs) = extract_abbreviations(input);
+        assert_eq!(
+            abbrs.get("HTML").map(String::as_str),
+            Some("HyperText Markup Language")
+        );
+        assert!(!abbrs.contains_key("X"));
+        assert!(!content.contains("*[HTML]"));
+        assert!(content.contains("*[X]: kept"));
+    }
+
+    #[test]
+    fn test_block_lines() {
+        let doc = parse("+++\ntitle = \"T\"\n+++\n\n# One\n\n[a]: https://a.example\nText\nmore.\n\n[^n]: Note.\n\n$$\nx\n$$").unwrap();
+        assert_eq!(doc.block_lines, [5, 8, 13]);
+        assert_eq!(doc.metadata.footnote_lines["n"], 11);
+    }
+
+    #[test]
+    fn test_extract_link_definitions_keeps_lines() {
+        // A `~~~` fence is not closed by a ``` line
+        let input = "[a]: https://a.example\n~~~\n```\n[b]: https://b.example\n~~~\n\
+                     [c]: https://c.example";
+        let (content, definitions) = extract_link_definitions(input);
+        assert!(definitions.contains_key("a"));
+        assert!(!definitions.contains_key("b"));
+        assert!(definitions.contains_key("c"));
+        assert_eq!(content, "\n~~~\n```\n[b]: https://b.example\n~~~\n");
+    }
+
+    #[test]
+    fn test_link_definition_does_not_interrupt_paragraph() {
+        let input =
+            "Some text\n[a]: https://a.example\n\n[b]: https://b.example\n[c]: https://c.example";
+        let (content, definitions) = extract_link_definitions(input);
+        assert!(!definitions.contains_key("a"));
+        assert!(definitions.contains_key("b"));
+        assert!(definitions.contains_key("c"));
+        assert_eq!(content, "Some text\n[a]: https://a.example\n\n\n");
+    }
+
+    #[test]
+    fn test_extract_footnote_definitions_keeps_lines() {
+        let input = "Text[^a].\n\n[^a]: First,\n    continued.\n\n    Second.\nAfter.";
+        let (content, definitions) = extract_footnote_definitions(input);
+        assert_eq!(
+            definitions["a"],
+            (2, "First,\ncontinued.\nSecond.".to_string())
+        );
+        assert_eq!(content, "Text[^a].\n\n\n\n\n\nAfter.");
+        assert_eq!(content.lines().count(), input.lines().count());
+    }
+
     #[test]
     fn test_count_macro_args() {
         assert_eq!(count_macro_args("\\mathbb{R}"), 0);
@@ -180,4 +729,44 @@ Some text."#;
         assert_eq!(count_macro_args("\\frac{#1}{#2}"), 2);
         assert_eq!(count_macro_args("#1 + #2 + #3"), 3);
     }
+
+    #[test]
+    fn test_max_nesting_depth_option() {
+        let input = "Deep [[[link](a)](b)](c) text.";
+        assert!(parse(input).is_ok());
+
+        let options = ParseOptions {
+            max_nesting_depth: 2,
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_with_options(input, &options),
+            Err(crate::error::Error::Parse(ParseError::NestingTooDeep(2)))
+        ));
+    }
+
+    #[test]
+    fn test_trim_math() {
+        let input = "Inline $ a + b $ math.\n\n$$ x = 1 $$";
+        let options = ParseOptions {
+            trim_math: true,
+            ..Default::default()
+        };
+
+        for (options, inline, display) in [
+            (ParseOptions::default(), " a + b ", " x = 1 "),
+            (options, "a + b", "x = 1"),
+        ] {
+            let doc = parse_with_options(input, &options).unwrap();
+            assert!(matches!(
+                &doc.blocks[0],
+                crate::ast::Block::Paragraph(inlines)
+                    if inlines.contains(&crate::ast::Inline::InlineMath(inline.to_string()))
+            ));
+            assert!(matches!(
+                &doc.blocks[1],
+                crate::ast::Block::DisplayMath { content, .. } if content == display
+            ));
+        }
+    }
 }
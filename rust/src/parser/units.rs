@@ -0,0 +1,217 @@
+//! Formatting of `siunitx`-style quantities (`\SI{9.8}{m/s^2}`, `\num{12345}`).
+//!
+//! Quantities are formatted to plain text at parse time, so every renderer
+//! shows them the same way: digits grouped by thin spaces, exponents as
+//! `× 10ⁿ`, unit powers as superscripts, and a narrow no-break space
+//! between value and unit.
+
+use alloc::string::String;
+
+/// Narrow no-break space, used between value and unit and for digit groups.
+pub const THIN_SPACE: char = '\u{202F}';
+
+/// Parse a quantity command at the start of `input`, returning the formatted
+/// text and the remaining input.
+///
+/// Recognized commands are `\SI{value}{unit}` (and its newer name `\qty`),
+/// `\num{value}` and `\si{unit}`.
+pub fn parse_unit_command(input: &str) -> Option<(String, &str)> {
+    if let Some(rest) = input
+        .strip_prefix("\\SI")
+        .or_else(|| input.strip_prefix("\\qty"))
+    {
+        let (value, rest) = braced(rest)?;
+        let (unit, rest) = braced(rest)?;
+        let mut text = format_number(value);
+        text.push(THIN_SPACE);
+        text.push_str(&format_unit(unit));
+        return Some((text, rest));
+    }
+
+    if let Some(rest) = input.strip_prefix("\\num") {
+        let (value, rest) = braced(rest)?;
+        return Some((format_number(value), rest));
+    }
+
+    if let Some(rest) = input.strip_prefix("\\si") {
+        let (unit, rest) = braced(rest)?;
+        return Some((format_unit(unit), rest));
+    }
+
+    None
+}
+
+/// Split `{content}rest` into `content` and `rest` (no nested braces).
+fn braced(input: &str) -> Option<(&str, &str)> {
+    let inner = input.strip_prefix('{')?;
+    let end = inner.find('}')?;
+    Some((inner[..end].trim(), &inner[end + 1..]))
+}
+
+/// Format a number: group integer digits in threes when there are more than
+/// four, and render an `e` exponent as `× 10ⁿ`.
+pub fn format_number(value: &str) -> String {
+    let (mantissa, exponent) = match value.find(['e', 'E']) {
+        Some(pos) => (&value[..pos], Some(&value[pos + 1..])),
+        None => (value, None),
+    };
+
+    let (sign, digits) = match mantissa.strip_prefix(['-', '+']) {
+        Some(digits) => (&mantissa[..1], digits),
+        None => ("", mantissa),
+    };
+    let (integer, fraction) = match digits.find(['.', ',']) {
+        Some(pos) => (&digits[..pos], Some(&digits[pos + 1..])),
+        None => (digits, None),
+    };
+
+    let mut text = String::from(if sign == "-" { "\u{2212}" } else { sign });
+    text.push_str(&group_digits(integer, false));
+    if let Some(fraction) = fraction {
+        text.push('.');
+        text.push_str(&group_digits(fraction, true));
+    }
+
+    if let Some(exponent) = exponent {
+        if !mantissa.is_empty() {
+            text.push_str(" × ");
+        }
+        text.push_str("10");
+        text.push_str(&superscript(exponent.trim_start_matches('+')));
+    }
+
+    text
+}
+
+/// Group a digit run in threes with thin spaces, counting from the decimal
+/// point. Runs of four digits or fewer are left alone.
+fn group_digits(digits: &str, fraction: bool) -> String {
+    let count = digits.chars().count();
+    if count <= 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return String::from(digits);
+    }
+
+    let mut text = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        let boundary = if fraction { i } else { count - i };
+        if i > 0 && boundary % 3 == 0 {
+            text.push(THIN_SPACE);
+        }
+        text.push(c);
+    }
+    text
+}
+
+/// Format a unit: `^n` powers become superscripts, `.`/`*`/`~` products
+/// become a centered dot, and common `siunitx` macros become their symbols.
+pub fn format_unit(unit: &str) -> String {
+    let mut text = String::new();
+    let mut rest = unit;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '^' {
+            let after = &rest[1..];
+            let (power, remaining) = match braced(after) {
+                Some(braced) => braced,
+                None => {
+                    let len = after
+                        .char_indices()
+                        .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+                        .map_or(after.len(), |(i, _)| i);
+                    (&after[..len], &after[len..])
+                }
+            };
+            text.push_str(&superscript(power));
+            rest = remaining;
+        } else if c == '\\' {
+            let name_len = rest[1..]
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .map_or(rest.len() - 1, |i| i);
+            let name = &rest[1..1 + name_len];
+            text.push_str(unit_symbol(name).unwrap_or(name));
+            // As in TeX, a space after a control word is not output
+            rest = rest[1 + name_len..].trim_start();
+        } else {
+            match c {
+                '.' | '*' | '~' => text.push('·'),
+                _ => text.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    text
+}
+
+/// Symbol for a `siunitx` unit or prefix macro.
+fn unit_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "kilo" => "k",
+        "mega" => "M",
+        "giga" => "G",
+        "milli" => "m",
+        "micro" => "µ",
+        "nano" => "n",
+        "meter" | "metre" => "m",
+        "second" => "s",
+        "gram" => "g",
+        "kilogram" => "kg",
+        "kelvin" => "K",
+        "mole" => "mol",
+        "ampere" => "A",
+        "newton" => "N",
+        "joule" => "J",
+        "watt" => "W",
+        "volt" => "V",
+        "hertz" => "Hz",
+        "pascal" => "Pa",
+        "ohm" => "Ω",
+        "degree" => "°",
+        "celsius" | "degreeCelsius" => "°C",
+        "percent" => "%",
+        "angstrom" => "Å",
+        "per" => "/",
+        _ => return None,
+    })
+}
+
+/// Convert digits and signs to Unicode superscripts.
+fn superscript(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            '9' => '⁹',
+            '-' => '⁻',
+            '+' => '⁺',
+            other => other,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_quantities() {
+        assert_eq!(
+            parse_unit_command(r"\SI{9.8}{m/s^2} fall"),
+            Some((String::from("9.8\u{202F}m/s²"), " fall"))
+        );
+        assert_eq!(format_unit(r"kg.m^2.s^{-1}"), "kg·m²·s⁻¹");
+        assert_eq!(format_unit(r"\micro m"), "µm");
+        assert_eq!(format_unit(r"\kilo\meter\per\second"), "km/s");
+        assert_eq!(format_number("12345.678"), "12\u{202F}345.678");
+        assert_eq!(format_number("1234"), "1234");
+        assert_eq!(format_number("-1.5e-3"), "\u{2212}1.5 × 10⁻³");
+        assert_eq!(parse_unit_command(r"\section{x}"), None);
+    }
+}
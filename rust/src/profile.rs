@@ -0,0 +1,96 @@
+//! Render profiles: resolve and HTML settings loaded from a TOML file.
+//!
+//! A profile lets a project keep its rendering settings in one file instead
+//! of constructing configs in code:
+//!
+//! ```toml
+//! [resolve]
+//! strict_references = true
+//! citation_style = "numeric"
+//!
+//! [render]
+//! standalone = true
+//! math_backend = "mathml"
+//! footnote_style = "sidenotes"
+//! ```
+//!
+//! Both tables take the field names of [`ResolveConfig`] and [`HtmlConfig`];
+//! missing fields keep their defaults and unknown fields are an error. Enum
+//! values are written in kebab-case (`"author-year"`, `"end-notes"`), except
+//! math backends (`"katex"`, `"mathml"`, `"mathjax"`).
+
+use crate::error::{ParseError, Result};
+use crate::render::HtmlConfig;
+use crate::resolve::ResolveConfig;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Resolve and render settings for a project.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderProfile {
+    /// Settings for [`resolve`](crate::resolve()), from the `[resolve]` table.
+    pub resolve: ResolveConfig,
+    /// Settings for [`render_html`](crate::render_html), from the `[render]`
+    /// table.
+    pub render: HtmlConfig,
+}
+
+impl RenderProfile {
+    /// Parse a profile from TOML source.
+    pub fn from_toml(source: &str) -> Result<Self> {
+        toml::from_str(source).map_err(|e| ParseError::Profile(e.to_string()).into())
+    }
+
+    /// Read and parse a profile file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::CitationFormat;
+    use crate::render::{FootnoteStyle, MathBackend};
+    use crate::resolve::NumberingScheme;
+
+    #[test]
+    fn test_profile_file() {
+        let path = std::env::temp_dir().join(format!("mda-profile-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[resolve]\ncitation_style = \"numeric\"\n\
+             numbering_scheme = { by-section = { depth = 1 } }\n\n\
+             [render]\nstandalone = true\nmath_backend = \"mathml\"\n\
+             footnote_style = \"sidenotes\"\n",
+        )
+        .unwrap();
+        let profile = RenderProfile::from_file(&path);
+        std::fs::remove_file(&path).ok();
+        let profile = profile.unwrap();
+
+        assert!(profile.render.standalone);
+        assert_eq!(profile.render.math_backend, MathBackend::MathML);
+        assert_eq!(profile.render.footnote_style, FootnoteStyle::Sidenotes);
+        assert_eq!(
+            profile.render.class_prefix,
+            HtmlConfig::default().class_prefix
+        );
+        assert_eq!(
+            profile.resolve.citation_style,
+            Some(CitationFormat::Numeric)
+        );
+        assert_eq!(
+            profile.resolve.numbering_scheme,
+            Some(NumberingScheme::BySection { depth: 1 })
+        );
+        assert!(profile.resolve.bibliography_required);
+    }
+
+    #[test]
+    fn test_profile_rejects_unknown_fields() {
+        let err = RenderProfile::from_toml("[render]\nstandlone = true\n").unwrap_err();
+        assert!(err.to_string().contains("standlone"), "{err}");
+    }
+}
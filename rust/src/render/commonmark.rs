@@ -0,0 +1,458 @@
+//! Plain CommonMark output for tools that don't understand the academic
+//! extensions.
+//!
+//! Academic constructs degrade to portable Markdown: math stays as literal
+//! `$...$`, environments become block quotes led by a bold label, citations
+//! become `[Author Year]`, cross-references become their display text, and
+//! inline footnotes become `[^n]` references with definitions at the end.
+//! Tables use the widely supported GitHub pipe syntax.
+
+use crate::ast::{
+    Block, Citation, CitationStyle, EnvironmentKind, FootnoteKind, Inline, ListItem,
+    ResolvedDocument,
+};
+use crate::render::html::format_author_year;
+
+/// Marker choices for CommonMark output, so it can follow a style guide.
+#[derive(Debug, Clone)]
+pub struct MdConfig {
+    /// Bullet list marker: `-`, `*` or `+` (default: `-`).
+    pub bullet: char,
+    /// Ordered list delimiter: `.` or `)` (default: `.`).
+    pub ordered_delimiter: char,
+    /// Emphasis marker: `*` or `_` (default: `*`).
+    pub emphasis: char,
+}
+
+impl Default for MdConfig {
+    fn default() -> Self {
+        Self {
+            bullet: '-',
+            ordered_delimiter: '.',
+            emphasis: '*',
+        }
+    }
+}
+
+/// Render a resolved document as plain CommonMark.
+pub fn to_commonmark(doc: &ResolvedDocument) -> String {
+    to_commonmark_with(doc, &MdConfig::default())
+}
+
+/// Render a resolved document as plain CommonMark with the given markers.
+pub fn to_commonmark_with(doc: &ResolvedDocument, config: &MdConfig) -> String {
+    let mut writer = CommonMarkWriter {
+        doc,
+        config,
+        footnotes: Vec::new(),
+    };
+    let mut output = writer.blocks(&doc.document.blocks);
+
+    let mut n = 0;
+    while n < writer.footnotes.len() {
+        // Footnote content may itself hold footnotes, which get appended
+        let content = writer.footnotes[n].clone();
+        n += 1;
+        let text = writer.inlines(&content);
+        output.push_str(&format!("\n\n[^{}]: {}", n, text));
+    }
+
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+struct CommonMarkWriter<'a> {
+    doc: &'a ResolvedDocument,
+    config: &'a MdConfig,
+    /// Footnote contents, numbered in order of appearance
+    footnotes: Vec<Vec<Inline>>,
+}
+
+impl CommonMarkWriter<'_> {
+    fn blocks(&mut self, blocks: &[Block]) -> String {
+        blocks
+            .iter()
+            .filter_map(|block| self.block(block))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn block(&mut self, block: &Block) -> Option<String> {
+        Some(match block {
+            // CommonMark has no attribute syntax, so attributes are dropped
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            } => self.inlines(inlines),
+            Block::Heading {
+                level,
+                content,
+                label,
+            } => {
+                let mut text = "#".repeat(*level as usize);
+                text.push(' ');
+                if let Some(num) = label.as_ref().and_then(|l| self.doc.section_numbers.get(l)) {
+                    text.push_str(num);
+                    text.push(' ');
+                }
+                text.push_str(&self.inlines(content));
+                text
+            }
+            Block::CodeBlock { language, content } => {
+                let fence = if content.contains("```") {
+                    "~~~~"
+                } else {
+                    "```"
+                };
+                format!(
+                    "{}{}\n{}\n{}",
+                    fence,
+                    language.as_deref().unwrap_or(""),
+                    content,
+                    fence
+                )
+            }
+            Block::BlockQuote(blocks) => quote(&self.blocks(blocks)),
+            Block::List {
+                ordered,
+                start,
+                items,
+            } => self.list(*ordered, start.unwrap_or(1), items),
+            Block::ThematicBreak => "---".to_string(),
+            Block::DisplayMath { content, .. } => format!("$$\n{}\n$$", content.trim()),
+            Block::Environment {
+                kind,
+                label,
+                title,
+                proves,
+                content,
+                caption,
+            } => {
+                let mut header = kind.display_name().to_string();
+                if kind.is_numbered() {
+                    if let Some(num) = label.as_ref().and_then(|l| self.doc.env_numbers.get(l)) {
+                        header.push_str(&format!(" {}", num));
+                    }
+                } else if *kind == EnvironmentKind::Proof {
+                    if let Some(info) = proves.as_ref().and_then(|l| self.doc.labels.get(l)) {
+                        header.push_str(&format!(" of {}", info.display));
+                    }
+                }
+                let header = match title {
+                    Some(title) => format!("**{}** ({}).", header, title),
+                    None => format!("**{}.**", header),
+                };
+
+                let mut body = self.blocks(content);
+                if let Some(caption) = caption {
+                    if !body.is_empty() {
+                        body.push_str("\n\n");
+                    }
+                    body.push_str(&self.inlines(caption));
+                }
+                // The label leads the first paragraph, as in print
+                let text = match content.first() {
+                    Some(Block::Paragraph(_)) | None => format!("{} {}", header, body),
+                    Some(_) => format!("{}\n\n{}", header, body),
+                };
+                if *kind == EnvironmentKind::Proof {
+                    quote(&format!("{} ∎", text.trim_end()))
+                } else {
+                    quote(text.trim_end())
+                }
+            }
+            Block::TableOfContents
+            | Block::PageBreak
+            | Block::ClearPage
+            | Block::AppendixMarker => return None,
+            Block::RawHtml(html) => html.trim_end().to_string(),
+            Block::Table {
+                headers,
+                rows,
+                caption,
+                ..
+            } => {
+                let row = |writer: &mut Self, cells: &[Vec<Inline>]| {
+                    let cells: Vec<String> = cells
+                        .iter()
+                        .map(|cell| writer.inlines(cell).replace('|', "\\|"))
+                        .collect();
+                    format!("| {} |", cells.join(" | "))
+                };
+                let mut lines = vec![row(self, headers)];
+                lines.push(format!("|{}", " --- |".repeat(headers.len())));
+                for cells in rows {
+                    lines.push(row(self, cells));
+                }
+                let mut text = lines.join("\n");
+                if let Some(caption) = caption {
+                    text.push_str("\n\n");
+                    text.push_str(&self.inlines(caption));
+                }
+                text
+            }
+            Block::DescriptionList(items) => items
+                .iter()
+                .map(|item| {
+                    let term = format!("**{}**", self.inlines(&item.term));
+                    let description = self.blocks(&item.description);
+                    format!("{}\n\n{}", term, description)
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            Block::Abstract(blocks) => format!("**Abstract.** {}", self.blocks(blocks)),
+        })
+    }
+
+    fn list(&mut self, ordered: bool, start: u32, items: &[ListItem]) -> String {
+        let mut lines = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            let mut marker = if ordered {
+                format!("{}{} ", start as usize + i, self.config.ordered_delimiter)
+            } else {
+                format!("{} ", self.config.bullet)
+            };
+            let indent = " ".repeat(marker.len());
+            match item.checked {
+                Some(true) => marker.push_str("[x] "),
+                Some(false) => marker.push_str("[ ] "),
+                None => {}
+            }
+
+            let content = self.blocks(&item.content);
+            for (j, line) in content.lines().enumerate() {
+                if j == 0 {
+                    lines.push(format!("{}{}", marker, line));
+                } else if line.is_empty() {
+                    lines.push(String::new());
+                } else {
+                    lines.push(format!("{}{}", indent, line));
+                }
+            }
+            if content.is_empty() {
+                lines.push(marker.trim_end().to_string());
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) -> String {
+        let mut text = String::new();
+        for inline in inlines {
+            self.inline(inline, &mut text);
+        }
+        text
+    }
+
+    fn inline(&mut self, inline: &Inline, out: &mut String) {
+        match inline {
+            Inline::Text(text) => out.push_str(&escape_markdown(text)),
+            Inline::Emphasis(inner) => {
+                let marker = self.config.emphasis;
+                out.push_str(&format!("{}{}{}", marker, self.inlines(inner), marker))
+            }
+            Inline::Strong(inner) => out.push_str(&format!("**{}**", self.inlines(inner))),
+            Inline::Strikethrough(inner) => out.push_str(&format!("~~{}~~", self.inlines(inner))),
+            Inline::Subscript(inner) => {
+                out.push_str(&format!("<sub>{}</sub>", self.inlines(inner)))
+            }
+            Inline::Superscript(inner) => {
+                out.push_str(&format!("<sup>{}</sup>", self.inlines(inner)))
+            }
+            Inline::SmallCaps(inner) | Inline::Lang { content: inner, .. } => {
+                out.push_str(&self.inlines(inner))
+            }
+            Inline::Code(code) => {
+                let ticks = if code.contains('`') { "`` " } else { "`" };
+                let close: String = ticks.chars().rev().collect();
+                out.push_str(&format!("{}{}{}", ticks, code, close));
+            }
+            Inline::Kbd(keys) => out.push_str(&format!("<kbd>{}</kbd>", keys)),
+            Inline::Verbatim(text) => out.push_str(&escape_markdown(text)),
+            Inline::Abbreviation { abbr, .. } => out.push_str(&escape_markdown(abbr)),
+            Inline::Link {
+                url,
+                title,
+                content,
+            } => {
+                out.push_str(&format!("[{}]({}", self.inlines(content), url));
+                if let Some(title) = title {
+                    out.push_str(&format!(" \"{}\"", title.replace('"', "\\\"")));
+                }
+                out.push(')');
+            }
+            Inline::LinkReference {
+                content, closing, ..
+            } => out.push_str(&format!("[{}{}", self.inlines(content), closing)),
+            Inline::Image {
+                url, alt, title, ..
+            } => {
+                out.push_str(&format!("![{}]({}", escape_markdown(alt), url));
+                if let Some(title) = title {
+                    out.push_str(&format!(" \"{}\"", title.replace('"', "\\\"")));
+                }
+                out.push(')');
+            }
+            Inline::InlineMath(math) => out.push_str(&format!("${}$", math)),
+            Inline::Citation(cite) => out.push_str(&self.citation(cite)),
+            Inline::Reference {
+                label, resolved, ..
+            } => match resolved {
+                Some(text) => out.push_str(&escape_markdown(text)),
+                None => out.push_str(&format!("??{}", escape_markdown(label))),
+            },
+            Inline::Footnote(FootnoteKind::Inline(content)) => {
+                self.footnotes.push(content.clone());
+                out.push_str(&format!("[^{}]", self.footnotes.len()));
+            }
+            Inline::Footnote(FootnoteKind::Reference(id)) => match self.doc.footnotes.get(id) {
+                Some(content) => {
+                    self.footnotes.push(content.clone());
+                    out.push_str(&format!("[^{}]", self.footnotes.len()));
+                }
+                None => out.push_str(&format!("[^{}]", id)),
+            },
+            Inline::SoftBreak => out.push('\n'),
+            Inline::HardBreak => out.push_str("\\\n"),
+            Inline::NonBreakingSpace => out.push_str("&nbsp;"),
+            Inline::RawHtml(html) => out.push_str(html),
+        }
+    }
+
+    /// `[Author Year]`, `Author (Year)`, `Author` or `(Year)`, by style.
+    fn citation(&self, cite: &Citation) -> String {
+        let parts: Vec<(String, String)> = cite
+            .keys
+            .iter()
+            .map(|key| match self.doc.citations.get(key) {
+                Some(entry) => format_author_year(entry),
+                None => (key.clone(), String::new()),
+            })
+            .collect();
+        let with_locator = |i: usize, text: String| match cite.locator(i) {
+            Some(locator) => format!("{}, {}", text, locator),
+            None => text,
+        };
+        let prefix = cite
+            .prefix
+            .as_ref()
+            .map(|prefix| format!("{} ", prefix))
+            .unwrap_or_default();
+
+        match cite.style {
+            CitationStyle::Parenthetical => {
+                let items: Vec<String> = parts
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (author, year))| {
+                        with_locator(i, format!("{} {}", author, year).trim_end().to_string())
+                    })
+                    .collect();
+                format!("[{}{}]", prefix, items.join("; "))
+            }
+            CitationStyle::Textual => {
+                let items: Vec<String> = parts
+                    .into_iter()
+                    .map(|(author, year)| {
+                        if year.is_empty() {
+                            author
+                        } else {
+                            format!("{} ({})", author, year)
+                        }
+                    })
+                    .collect();
+                format!("{}{}", prefix, items.join(", "))
+            }
+            CitationStyle::AuthorOnly => {
+                let items: Vec<String> = parts.into_iter().map(|(author, _)| author).collect();
+                format!("{}{}", prefix, items.join(", "))
+            }
+            CitationStyle::YearOnly => {
+                let items: Vec<String> = parts
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (author, year))| {
+                        with_locator(i, if year.is_empty() { author } else { year })
+                    })
+                    .collect();
+                format!("({}{})", prefix, items.join("; "))
+            }
+        }
+    }
+}
+
+/// Prefix every line with `> `.
+fn quote(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Backslash-escape characters that CommonMark would read as markup.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::resolve::{resolve, ResolveConfig};
+
+    fn commonmark(input: &str) -> String {
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        to_commonmark(&resolved)
+    }
+
+    #[test]
+    fn test_theorem_becomes_labeled_blockquote() {
+        let output = commonmark(
+            "::: theorem {#thm:main}\nEvery $n$ is *interesting*.\n\nIndeed.\n:::\n\nBy @thm:main.",
+        );
+        assert_eq!(
+            output,
+            "> **Theorem 1.** Every $n$ is *interesting*.\n>\n> Indeed.\n\nBy Theorem 1.\n"
+        );
+    }
+
+    #[test]
+    fn test_footnotes_and_lists() {
+        let output = commonmark("- One^[A note.]\n- Two with 2*3\n\n1. First");
+        assert_eq!(
+            output,
+            "- One[^1]\n- Two with 2\\*3\n\n1. First\n\n[^1]: A note.\n"
+        );
+    }
+
+    #[test]
+    fn test_marker_normalization() {
+        let input = "- *One*\n- Two\n\n3. Three";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        let config = MdConfig {
+            bullet: '*',
+            ordered_delimiter: ')',
+            emphasis: '_',
+        };
+        assert_eq!(
+            to_commonmark_with(&resolved, &config),
+            "* _One_\n* Two\n\n3) Three\n"
+        );
+        assert_eq!(to_commonmark(&resolved), "- *One*\n- Two\n\n3. Three\n");
+    }
+}
@@ -1,16 +1,28 @@
 //! HTML renderer for resolved documents.
 
 use crate::ast::{
-    Alignment, BibEntry, Block, Citation, CitationStyle, DescriptionItem, EnvironmentKind,
-    FootnoteKind, Inline, ResolvedDocument,
+    Alignment, Author, BibEntry, Block, Citation, CitationFormat, CitationStyle, DescriptionItem,
+    EnvironmentKind, FootnoteKind, Inline, LabelInfo, ResolvedDocument,
 };
 use crate::error::Result;
-use crate::render::math::{create_renderer, MathBackend, MathRenderer};
+use crate::render::bib_field;
+use crate::render::math::{
+    create_renderer_with_side, EquationNumberSide, MathBackend, MathRenderer,
+};
+use crate::render::slug::{SlugMode, Slugger};
 use crate::resolve::citations::get_citation_order;
-use crate::resolve::references::label_to_id;
+use crate::resolve::numbering::{align_line_labels, is_appendix_number};
+use crate::resolve::references::{inlines_to_text, label_to_id};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Configuration for HTML rendering.
-#[derive(Debug, Clone)]
+///
+/// Deserializes from a table of the same field names, with missing fields
+/// taken from the default (see [`RenderProfile`](crate::RenderProfile)).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct HtmlConfig {
     /// Math rendering backend.
     pub math_backend: MathBackend,
@@ -24,6 +36,200 @@ pub struct HtmlConfig {
     pub include_toc: bool,
     /// CSS class prefix for styling.
     pub class_prefix: String,
+    /// Whether to generate ids for headings without an explicit label.
+    pub auto_heading_ids: bool,
+    /// How automatic heading ids are derived from heading text.
+    pub slug_mode: SlugMode,
+    /// Whether to begin the output with an HTML comment naming the generator
+    /// version and the document title/date.
+    pub include_generator_comment: bool,
+    /// Where footnote content is placed.
+    pub footnote_style: FootnoteStyle,
+    /// Whether a LaTeX-style tie (`Fig.~1`) renders as `&nbsp;` rather than a literal `~`.
+    pub tilde_as_nbsp: bool,
+    /// Whether to open the body with a title block listing the title,
+    /// authors, and their affiliations from front matter.
+    pub title_block: bool,
+    /// Whether the standalone `<title>` includes the front-matter subtitle
+    /// ("Title: Subtitle").
+    pub subtitle_in_title: bool,
+    /// Whether cross-references and citations link to their targets.
+    /// When false they render as plain text (useful for print pipelines).
+    pub link_references: bool,
+    /// Whether list items holding a single paragraph keep their `<p>` wrapper
+    /// instead of being rendered inline.
+    pub always_wrap_list_items: bool,
+    /// Whether display equations are wrapped in `<figure>` with the equation
+    /// number as a `<figcaption>`, tying the number to the math for assistive tech.
+    pub equation_as_figure: bool,
+    /// Base text direction of the document (standalone mode).
+    pub direction: TextDirection,
+    /// Whether tables get semantic striping hooks: a `table-striped` class on
+    /// the table and alternating `row-odd`/`row-even` classes on body rows.
+    pub table_classes: bool,
+    /// Whether each bibliography entry links back to every place it was cited.
+    pub bibliography_backlinks: bool,
+    /// Optional hook that can replace the rendering of individual citations.
+    #[serde(skip)]
+    pub citation_hook: Option<CitationHook>,
+    /// How references to unknown labels are shown (non-strict resolution).
+    pub unresolved_references: UnresolvedReferenceStyle,
+    /// Whether adjacent footnote references (`[^1][^2]`) share one
+    /// superscript, as `[1, 2]`, instead of each getting its own.
+    pub join_adjacent_footnotes: bool,
+    /// In-text citation format. `None` uses the document's `citation_style`
+    /// front matter, falling back to author-year.
+    pub citation_style: Option<CitationFormat>,
+    /// Deepest heading level listed in the table of contents. `None` uses
+    /// the document's `toc_depth` front matter, falling back to all levels.
+    pub toc_depth: Option<u8>,
+    /// Shallowest heading level listed in the table of contents (default:
+    /// 1). Use 2 when the only level-1 heading is the document title.
+    pub toc_min_depth: u8,
+    /// Heading above the table of contents; `None` omits it.
+    pub toc_title: Option<String>,
+    /// Whether the name and number in a labeled environment's header link to
+    /// the environment itself, giving readers a stable link to "Theorem 3".
+    pub permalinks: bool,
+    /// How code blocks handle lines wider than the page.
+    pub code_overflow: CodeOverflow,
+    /// Whether each top-level heading and the content under it are wrapped
+    /// in a `<section>`, nested by heading level.
+    pub semantic_sections: bool,
+    /// Side on which equation numbers appear, including the per-line
+    /// numbers of `align` blocks.
+    pub equation_number_side: EquationNumberSide,
+    /// Whether each top-level table of contents entry with sub-entries is a
+    /// `<details>` element, so the sub-entries can be collapsed.
+    pub collapsible_toc: bool,
+    /// Whether images get `loading="lazy"` and `decoding="async"`, so
+    /// off-screen images load only as the reader scrolls to them.
+    pub lazy_images: bool,
+    /// Whether front-matter keywords appear as a "Keywords:" line at the
+    /// end of the abstract. Standalone output always lists them in a
+    /// `<meta name="keywords">` tag.
+    pub show_keywords: bool,
+    /// Whether references to equations and environments carry a `title`
+    /// previewing the target (its LaTeX or first sentence), shown on hover.
+    pub reference_previews: bool,
+    /// Heading rendered at the appendix marker; `None` omits it.
+    pub appendix_title: Option<String>,
+    /// Order of the bibliography under author-year citations. Numeric
+    /// bibliographies always follow first citation, matching their numbers.
+    pub bibliography_sort: BibliographySort,
+}
+
+/// Treatment of long lines in code blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodeOverflow {
+    /// No extra markup; long lines overflow the layout.
+    #[default]
+    Visible,
+    /// The block gets a `code-scroll` class and scrolls horizontally.
+    Scroll,
+    /// The block gets a `code-wrap` class and long lines soft-wrap.
+    Wrap,
+}
+
+impl CodeOverflow {
+    /// Class name (without prefix) added to the `<pre>` element, if any.
+    fn class(self) -> Option<&'static str> {
+        match self {
+            CodeOverflow::Visible => None,
+            CodeOverflow::Scroll => Some("code-scroll"),
+            CodeOverflow::Wrap => Some("code-wrap"),
+        }
+    }
+}
+
+/// Placeholder shown for a reference whose label could not be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnresolvedReferenceStyle {
+    /// `??label`, linked like a resolved reference.
+    #[default]
+    Marker,
+    /// The bare label.
+    Label,
+    /// The reference as written in the source (`@label`).
+    Source,
+    /// The label in an `unresolved` span whose tooltip names the problem.
+    Span,
+}
+
+/// Callback invoked for every citation with the citation and its resolved
+/// bibliography entries (unknown keys are skipped).
+///
+/// Returning `Some(html)` replaces the built-in rendering inside the citation
+/// span; returning `None` falls back to it.
+#[derive(Clone)]
+pub struct CitationHook(Arc<CitationHookFn>);
+
+type CitationHookFn = dyn Fn(&Citation, &[&BibEntry]) -> Option<String> + Send + Sync;
+
+impl CitationHook {
+    /// Wrap a closure as a citation hook.
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&Citation, &[&BibEntry]) -> Option<String> + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+}
+
+impl std::fmt::Debug for CitationHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CitationHook")
+    }
+}
+
+/// Placement of footnote content in HTML output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FootnoteStyle {
+    /// Numbered list of notes at the end of the document.
+    #[default]
+    EndNotes,
+    /// Tufte-style margin notes placed right after each reference.
+    Sidenotes,
+}
+
+/// Order of entries in the bibliography.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BibliographySort {
+    /// Order of first citation in the text.
+    #[default]
+    Appearance,
+    /// By first author's last name, then year, then title.
+    AuthorYear,
+    /// By citation key.
+    Key,
+}
+
+/// Base text direction of the rendered document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextDirection {
+    /// Left-to-right (no `dir` attribute is emitted).
+    #[default]
+    Ltr,
+    /// Right-to-left, for Arabic, Hebrew, etc.
+    Rtl,
+    /// Let the browser infer direction from the content.
+    Auto,
+}
+
+impl TextDirection {
+    /// The `dir` attribute to add to container elements, if any.
+    fn attribute(self) -> &'static str {
+        match self {
+            TextDirection::Ltr => "",
+            TextDirection::Rtl => r#" dir="rtl""#,
+            TextDirection::Auto => r#" dir="auto""#,
+        }
+    }
 }
 
 impl Default for HtmlConfig {
@@ -35,6 +241,36 @@ impl Default for HtmlConfig {
             custom_css: None,
             include_toc: true,
             class_prefix: "mda".to_string(),
+            auto_heading_ids: false,
+            slug_mode: SlugMode::default(),
+            include_generator_comment: false,
+            footnote_style: FootnoteStyle::default(),
+            tilde_as_nbsp: false,
+            title_block: false,
+            subtitle_in_title: false,
+            link_references: true,
+            always_wrap_list_items: false,
+            equation_as_figure: false,
+            direction: TextDirection::default(),
+            table_classes: false,
+            bibliography_backlinks: false,
+            citation_hook: None,
+            unresolved_references: UnresolvedReferenceStyle::default(),
+            join_adjacent_footnotes: false,
+            citation_style: None,
+            toc_depth: None,
+            toc_min_depth: 1,
+            toc_title: Some("Table of Contents".to_string()),
+            permalinks: false,
+            code_overflow: CodeOverflow::default(),
+            semantic_sections: false,
+            equation_number_side: EquationNumberSide::default(),
+            collapsible_toc: false,
+            lazy_images: false,
+            show_keywords: false,
+            reference_previews: false,
+            appendix_title: Some("Appendices".to_string()),
+            bibliography_sort: BibliographySort::default(),
         }
     }
 }
@@ -45,26 +281,173 @@ pub fn render_html(doc: &ResolvedDocument, config: &HtmlConfig) -> Result<String
     renderer.render()
 }
 
+/// Render a resolved document to HTML, along with an index of the anchors,
+/// cross-reference targets and citations in the output.
+///
+/// The index is serializable, so it can be written as a JSON sidecar for
+/// link checkers and editor integrations.
+pub fn render_html_with_index(
+    doc: &ResolvedDocument,
+    config: &HtmlConfig,
+) -> Result<(String, RenderIndex)> {
+    let mut renderer = HtmlRenderer::new(doc, config);
+    let html = renderer.render()?;
+
+    let mut targets: Vec<IndexTarget> = doc
+        .labels
+        .iter()
+        .map(|(label, info)| IndexTarget {
+            label: label.clone(),
+            id: info.html_id.clone(),
+            display: info.display.clone(),
+        })
+        .collect();
+    targets.sort_by(|a, b| a.label.cmp(&b.label));
+
+    // Positions follow the reference list, which may be sorted
+    let positions: HashMap<String, usize> = renderer
+        .bibliography_order()
+        .into_iter()
+        .filter(|key| doc.citations.contains_key(key))
+        .zip(1..)
+        .collect();
+    let citations = get_citation_order(&doc.document)
+        .into_iter()
+        .map(|key| IndexCitation {
+            id: positions.get(&key).map(|_| format!("bib-{}", key)),
+            position: positions.get(&key).copied(),
+            key,
+        })
+        .collect();
+
+    let index = RenderIndex {
+        anchors: collect_anchor_ids(&html),
+        targets,
+        references: renderer.references,
+        citations,
+    };
+    Ok((html, index))
+}
+
+/// Anchors, link targets and citations produced by [`render_html_with_index`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RenderIndex {
+    /// Every `id` attribute in the output, in document order.
+    pub anchors: Vec<String>,
+    /// Every labeled cross-reference target, sorted by label.
+    pub targets: Vec<IndexTarget>,
+    /// Every cross-reference in the output, in document order.
+    pub references: Vec<IndexReference>,
+    /// Every cited key, in reference-list order.
+    pub citations: Vec<IndexCitation>,
+}
+
+/// A labeled element that cross-references can point at.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexTarget {
+    /// The label, e.g. `sec:intro`.
+    pub label: String,
+    /// The anchor id of the element, e.g. `sec-intro`.
+    pub id: String,
+    /// The text references to it display, e.g. `Section 1`.
+    pub display: String,
+}
+
+/// A cross-reference in the output.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexReference {
+    /// The referenced label.
+    pub label: String,
+    /// The anchor id the reference points at.
+    pub href: String,
+    /// Whether the label was found during resolution.
+    pub resolved: bool,
+}
+
+/// A cited bibliography key.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexCitation {
+    /// The citation key.
+    pub key: String,
+    /// The 1-based position in the reference list, if the key has an entry.
+    pub position: Option<usize>,
+    /// The anchor id of the reference-list entry, if the key has an entry.
+    pub id: Option<String>,
+}
+
+/// Every `id="..."` attribute value in `html`, in order.
+fn collect_anchor_ids(html: &str) -> Vec<String> {
+    html.split(" id=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('"').map(|(id, _)| id.to_string()))
+        .collect()
+}
+
 struct HtmlRenderer<'a> {
     doc: &'a ResolvedDocument,
     config: &'a HtmlConfig,
     math: Box<dyn MathRenderer>,
     output: String,
     footnote_counter: u32,
+    /// Ids for every heading in document order (only with `auto_heading_ids`).
+    heading_ids: Vec<String>,
+    heading_counter: usize,
+    citation_counter: u32,
+    /// Ids of the citation sites for each key, in document order
+    /// (only with `bibliography_backlinks`).
+    citation_sites: HashMap<String, Vec<String>>,
+    /// Bibliography position of each cited key (only for numeric citations).
+    citation_numbers: HashMap<String, usize>,
+    /// Number of environments enclosing the block being rendered.
+    environment_depth: usize,
+    /// Cross-references rendered so far, for [`RenderIndex`].
+    references: Vec<IndexReference>,
 }
 
 impl<'a> HtmlRenderer<'a> {
     fn new(doc: &'a ResolvedDocument, config: &'a HtmlConfig) -> Self {
+        let heading_ids = if config.auto_heading_ids {
+            assign_heading_ids(doc, config.slug_mode)
+        } else {
+            Vec::new()
+        };
+
+        let citation_style = config
+            .citation_style
+            .or(doc.document.metadata.citation_style)
+            .unwrap_or_default();
+        let citation_numbers = if citation_style == CitationFormat::Numeric {
+            get_citation_order(&doc.document)
+                .into_iter()
+                .filter(|key| doc.citations.contains_key(key))
+                .enumerate()
+                .map(|(i, key)| (key, i + 1))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
         Self {
             doc,
             config,
-            math: create_renderer(config.math_backend),
+            math: create_renderer_with_side(config.math_backend, config.equation_number_side),
             output: String::new(),
             footnote_counter: 0,
+            heading_ids,
+            heading_counter: 0,
+            citation_counter: 0,
+            citation_sites: HashMap::new(),
+            citation_numbers,
+            environment_depth: 0,
+            references: Vec::new(),
         }
     }
 
     fn render(&mut self) -> Result<String> {
+        if self.config.include_generator_comment {
+            self.render_generator_comment();
+        }
+
         if self.config.standalone {
             self.render_standalone()
         } else {
@@ -79,15 +462,32 @@ impl<'a> HtmlRenderer<'a> {
             .clone()
             .or_else(|| self.doc.document.metadata.title.clone())
             .unwrap_or_else(|| "Document".to_string());
+        let title = match &self.doc.document.metadata.subtitle {
+            Some(subtitle) if self.config.subtitle_in_title => format!("{}: {}", title, subtitle),
+            _ => title,
+        };
 
-        self.output
-            .push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        let lang = self.doc.document.metadata.lang.as_deref().unwrap_or("en");
+        let dir = self.config.direction.attribute();
+
+        self.output.push_str(&format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\"{}>\n<head>\n",
+            escape_html(lang),
+            dir
+        ));
         self.output.push_str("<meta charset=\"UTF-8\">\n");
         self.output.push_str(
             "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n",
         );
         self.output
             .push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+        let keywords = &self.doc.document.metadata.keywords;
+        if !keywords.is_empty() {
+            self.output.push_str(&format!(
+                "<meta name=\"keywords\" content=\"{}\">\n",
+                escape_html(&keywords.join(", "))
+            ));
+        }
 
         // Math head content
         if let Some(head) = self.math.head_content() {
@@ -107,7 +507,7 @@ impl<'a> HtmlRenderer<'a> {
 
         self.output.push_str("</head>\n<body>\n");
         self.output
-            .push_str("<article class=\"mdlatex-document\">\n");
+            .push_str(&format!("<article class=\"mdlatex-document\"{}>\n", dir));
 
         self.render_body_content()?;
 
@@ -117,18 +517,165 @@ impl<'a> HtmlRenderer<'a> {
         Ok(std::mem::take(&mut self.output))
     }
 
+    fn render_generator_comment(&mut self) {
+        self.output.push_str(&format!(
+            "<!-- generated by markdown-academic v{} -->\n",
+            env!("CARGO_PKG_VERSION")
+        ));
+
+        let metadata = &self.doc.document.metadata;
+        let title = self.config.title.as_ref().or(metadata.title.as_ref());
+        if let Some(title) = title {
+            self.output
+                .push_str(&format!("<!-- title: {} -->\n", escape_comment(title)));
+        }
+        if let Some(ref date) = metadata.date {
+            self.output
+                .push_str(&format!("<!-- date: {} -->\n", escape_comment(date)));
+        }
+    }
+
+    fn render_title_block(&mut self) {
+        let metadata = &self.doc.document.metadata;
+        let title = self.config.title.as_ref().or(metadata.title.as_ref());
+        let thesis_lines = [
+            ("department", metadata.department.clone()),
+            ("institution", metadata.institution.clone()),
+            (
+                "advisor",
+                metadata.advisor.as_ref().map(|a| format!("Advisor: {}", a)),
+            ),
+        ];
+        if title.is_none()
+            && metadata.subtitle.is_none()
+            && metadata.authors.is_empty()
+            && thesis_lines.iter().all(|(_, line)| line.is_none())
+        {
+            return;
+        }
+
+        let prefix = &self.config.class_prefix;
+        self.output
+            .push_str(&format!("<header class=\"{}title-block\">\n", prefix));
+
+        if let Some(title) = title {
+            self.output.push_str(&format!(
+                "<h1 class=\"{}title\">{}</h1>\n",
+                prefix,
+                escape_html(title)
+            ));
+        }
+        if let Some(ref subtitle) = metadata.subtitle {
+            self.output.push_str(&format!(
+                "<p class=\"{}subtitle\">{}</p>\n",
+                prefix,
+                escape_html(subtitle)
+            ));
+        }
+
+        // Deduplicated affiliations in order of first appearance
+        let mut affiliations: Vec<&Author> = Vec::new();
+        let markers: Vec<Option<usize>> = metadata
+            .authors
+            .iter()
+            .map(|author| {
+                let institution = author.institution.as_ref()?;
+                let index = affiliations
+                    .iter()
+                    .position(|a| a.institution.as_ref() == Some(institution))
+                    .unwrap_or_else(|| {
+                        affiliations.push(author);
+                        affiliations.len() - 1
+                    });
+                Some(index + 1)
+            })
+            .collect();
+        let show_markers = affiliations.len() > 1;
+
+        if !metadata.authors.is_empty() {
+            self.output
+                .push_str(&format!("<div class=\"{}authors\">\n", prefix));
+            for (author, marker) in metadata.authors.iter().zip(&markers) {
+                self.output
+                    .push_str(&format!("<span class=\"{}author\">", prefix));
+                self.output.push_str(&escape_html(&author.name));
+                if let (true, Some(n)) = (show_markers, marker) {
+                    self.output.push_str(&format!("<sup>{}</sup>", n));
+                }
+                if let Some(ref email) = author.email {
+                    self.output.push_str(&format!(
+                        " <a class=\"{}email\" href=\"mailto:{}\">{}</a>",
+                        prefix,
+                        escape_html(email),
+                        escape_html(email)
+                    ));
+                }
+                self.output.push_str("</span>\n");
+            }
+            self.output.push_str("</div>\n");
+        }
+
+        if !affiliations.is_empty() {
+            self.output
+                .push_str(&format!("<ol class=\"{}affiliations\">\n", prefix));
+            for author in &affiliations {
+                let name = escape_html(author.institution.as_deref().unwrap_or_default());
+                match author.institution_url {
+                    Some(ref url) => self.output.push_str(&format!(
+                        "<li><a href=\"{}\">{}</a></li>\n",
+                        escape_html(url),
+                        name
+                    )),
+                    None => self.output.push_str(&format!("<li>{}</li>\n", name)),
+                }
+            }
+            self.output.push_str("</ol>\n");
+        }
+
+        // Thesis-style details from front matter
+        for (class, line) in &thesis_lines {
+            if let Some(line) = line {
+                self.output.push_str(&format!(
+                    "<p class=\"{}{}\">{}</p>\n",
+                    prefix,
+                    class,
+                    escape_html(line)
+                ));
+            }
+        }
+
+        self.output.push_str("</header>\n");
+    }
+
     fn render_body(&mut self) -> Result<String> {
         self.render_body_content()?;
         Ok(std::mem::take(&mut self.output))
     }
 
     fn render_body_content(&mut self) -> Result<()> {
+        if self.config.title_block {
+            self.render_title_block();
+        }
+
+        // Levels of the headings whose <section> is still open
+        let mut open_sections: Vec<u8> = Vec::new();
         for block in &self.doc.document.blocks {
+            if let (true, Block::Heading { level, .. }) = (self.config.semantic_sections, block) {
+                while open_sections.last().is_some_and(|open| open >= level) {
+                    open_sections.pop();
+                    self.output.push_str("</section>\n");
+                }
+                open_sections.push(*level);
+                self.output.push_str("<section>\n");
+            }
             self.render_block(block)?;
         }
+        for _ in open_sections {
+            self.output.push_str("</section>\n");
+        }
 
         // Render footnotes section if any
-        if !self.doc.footnotes.is_empty() {
+        if !self.doc.footnotes.is_empty() && self.config.footnote_style == FootnoteStyle::EndNotes {
             self.render_footnotes_section()?;
         }
 
@@ -147,13 +694,39 @@ impl<'a> HtmlRenderer<'a> {
                 self.render_inlines(inlines)?;
                 self.output.push_str("</p>\n");
             }
+            Block::AttributedParagraph {
+                content,
+                id,
+                classes,
+            } => {
+                self.output.push_str("<p");
+                if let Some(id) = id {
+                    self.output
+                        .push_str(&format!(r#" id="{}""#, escape_html(&label_to_id(id))));
+                }
+                if !classes.is_empty() {
+                    let classes: Vec<String> = classes
+                        .iter()
+                        .map(|class| format!("{}{}", self.config.class_prefix, escape_html(class)))
+                        .collect();
+                    self.output
+                        .push_str(&format!(r#" class="{}""#, classes.join(" ")));
+                }
+                self.output.push('>');
+                self.render_inlines(content)?;
+                self.output.push_str("</p>\n");
+            }
             Block::Heading {
                 level,
                 content,
                 label,
             } => {
                 let tag = format!("h{}", level);
-                let id = label.as_ref().map(|l| label_to_id(l));
+                let id = match self.heading_ids.get(self.heading_counter) {
+                    Some(id) => Some(id.clone()),
+                    None => label.as_ref().map(|l| label_to_id(l)),
+                };
+                self.heading_counter += 1;
 
                 self.output.push('<');
                 self.output.push_str(&tag);
@@ -165,9 +738,16 @@ impl<'a> HtmlRenderer<'a> {
                 // Add section number if available
                 if let Some(ref lbl) = label {
                     if let Some(num) = self.doc.section_numbers.get(lbl) {
+                        // Top-level appendices show the word resolution chose
+                        // ("Appendix A"), as references to them do
+                        let num = match self.doc.labels.get(lbl) {
+                            Some(info) if is_appendix_number(num) => info.display.as_str(),
+                            _ => num.as_str(),
+                        };
                         self.output.push_str(&format!(
                             r#"<span class="{}section-number">{}</span> "#,
-                            self.config.class_prefix, num
+                            self.config.class_prefix,
+                            escape_html(num)
                         ));
                     }
                 }
@@ -179,7 +759,14 @@ impl<'a> HtmlRenderer<'a> {
                 self.output.push_str(">\n");
             }
             Block::CodeBlock { language, content } => {
-                self.output.push_str("<pre><code");
+                self.output.push_str("<pre");
+                if let Some(class) = self.config.code_overflow.class() {
+                    self.output.push_str(&format!(
+                        r#" class="{}{}""#,
+                        self.config.class_prefix, class
+                    ));
+                }
+                self.output.push_str("><code");
                 if let Some(lang) = language {
                     self.output
                         .push_str(&format!(r#" class="language-{}""#, lang));
@@ -188,6 +775,13 @@ impl<'a> HtmlRenderer<'a> {
                 self.output.push_str(&escape_html(content));
                 self.output.push_str("</code></pre>\n");
             }
+            Block::ThematicBreak if self.environment_depth > 0 => {
+                // A divider within a figure or theorem, lighter than a section break
+                self.output.push_str(&format!(
+                    "<hr class=\"{}env-rule\">\n",
+                    self.config.class_prefix
+                ));
+            }
             Block::ThematicBreak => {
                 self.output.push_str("<hr>\n");
             }
@@ -227,7 +821,7 @@ impl<'a> HtmlRenderer<'a> {
                     }
                     for block in item.content.iter() {
                         // Inline single paragraphs in list items
-                        if item.content.len() == 1 {
+                        if item.content.len() == 1 && !self.config.always_wrap_list_items {
                             if let Block::Paragraph(inlines) = block {
                                 self.render_inlines(inlines)?;
                                 continue;
@@ -244,40 +838,91 @@ impl<'a> HtmlRenderer<'a> {
                     self.output.push_str("</ul>\n");
                 }
             }
-            Block::DisplayMath { content, label } => {
+            Block::DisplayMath { content, label, .. } => {
                 let id = label.as_ref().map(|l| label_to_id(l));
+                let number = label.as_ref().and_then(|l| self.doc.env_numbers.get(l));
+                let tag = if self.config.equation_as_figure {
+                    "figure"
+                } else {
+                    "div"
+                };
 
                 self.output.push_str(&format!(
-                    r#"<div class="{}equation""#,
-                    self.config.class_prefix
+                    r#"<{} class="{}equation""#,
+                    tag, self.config.class_prefix
                 ));
                 if let Some(ref id) = id {
                     self.output.push_str(&format!(r#" id="{}""#, id));
                 }
                 self.output.push_str(">\n");
 
-                let rendered = self.math.render_display(content)?;
-                self.output.push_str(&rendered);
-
-                // Equation number
-                if let Some(ref lbl) = label {
-                    if let Some(num) = self.doc.env_numbers.get(lbl) {
-                        self.output.push_str(&format!(
+                // Labeled align lines: anchor each label and number it with \tag
+                let line_labels = align_line_labels(content);
+                let mut content = content.clone();
+                for line_label in &line_labels {
+                    self.output.push_str(&format!(
+                        r#"<span id="{}"></span>"#,
+                        label_to_id(line_label)
+                    ));
+                    let tag = match self.doc.env_numbers.get(line_label) {
+                        Some(num) => format!("\\tag{{{}}}", num),
+                        None => String::new(),
+                    };
+                    content = content.replacen(&format!("\\label{{{}}}", line_label), &tag, 1);
+                }
+                let number = number.filter(|_| line_labels.is_empty());
+
+                let rendered = self.math.render_display(&content)?;
+                let (math, number) = if self.config.equation_as_figure {
+                    (
+                        format!("<div>{}</div>", rendered),
+                        number.map(|num| format!("<figcaption>({})</figcaption>", num)),
+                    )
+                } else {
+                    let number = number.map(|num| {
+                        format!(
                             r#"<span class="{}equation-number">({})</span>"#,
                             self.config.class_prefix, num
-                        ));
+                        )
+                    });
+                    (rendered, number)
+                };
+                match (self.config.equation_number_side, number) {
+                    (EquationNumberSide::Left, Some(number)) => {
+                        self.output.push_str(&number);
+                        if self.config.equation_as_figure {
+                            self.output.push('\n');
+                        }
+                        self.output.push_str(&math);
+                    }
+                    (EquationNumberSide::Right, Some(number)) => {
+                        self.output.push_str(&math);
+                        if self.config.equation_as_figure {
+                            self.output.push('\n');
+                        }
+                        self.output.push_str(&number);
                     }
+                    (_, None) => self.output.push_str(&math),
                 }
 
-                self.output.push_str("\n</div>\n");
+                self.output.push_str(&format!("\n</{}>\n", tag));
             }
             Block::Environment {
                 kind,
                 label,
+                title,
+                proves,
                 content,
                 caption,
             } => {
-                self.render_environment(kind, label.as_deref(), content, caption.as_deref())?;
+                self.render_environment(
+                    kind,
+                    label.as_deref(),
+                    title.as_deref(),
+                    proves.as_deref(),
+                    content,
+                    caption.as_deref(),
+                )?;
             }
             Block::TableOfContents => {
                 if self.config.include_toc {
@@ -287,6 +932,7 @@ impl<'a> HtmlRenderer<'a> {
             Block::Table {
                 headers,
                 alignments,
+                widths,
                 rows,
                 label,
                 caption,
@@ -294,6 +940,7 @@ impl<'a> HtmlRenderer<'a> {
                 self.render_table(
                     headers,
                     alignments,
+                    widths.as_deref(),
                     rows,
                     label.as_deref(),
                     caption.as_deref(),
@@ -313,6 +960,14 @@ impl<'a> HtmlRenderer<'a> {
                 ));
                 self.output.push('\n');
             }
+            Block::ClearPage => {
+                // Nothing floats in HTML beyond CSS floats, which this clears
+                self.output.push_str(&format!(
+                    r#"<div class="{}clearpage" style="clear: both; page-break-after: always;"></div>"#,
+                    self.config.class_prefix
+                ));
+                self.output.push('\n');
+            }
             Block::Abstract(blocks) => {
                 self.output.push_str(&format!(
                     r#"<div class="{}abstract">"#,
@@ -326,6 +981,7 @@ impl<'a> HtmlRenderer<'a> {
                 for block in blocks {
                     self.render_block(block)?;
                 }
+                self.render_keywords_line();
                 self.output.push_str("</div>\n");
             }
             Block::AppendixMarker => {
@@ -333,10 +989,13 @@ impl<'a> HtmlRenderer<'a> {
                     r#"<div class="{}appendix-marker">"#,
                     self.config.class_prefix
                 ));
-                self.output.push_str(&format!(
-                    r#"<h1 class="{}appendix-title">Appendices</h1>"#,
-                    self.config.class_prefix
-                ));
+                if let Some(title) = &self.config.appendix_title {
+                    self.output.push_str(&format!(
+                        r#"<h1 class="{}appendix-title">{}</h1>"#,
+                        self.config.class_prefix,
+                        escape_html(title)
+                    ));
+                }
                 self.output.push_str("</div>\n");
             }
         }
@@ -364,6 +1023,8 @@ impl<'a> HtmlRenderer<'a> {
         &mut self,
         kind: &EnvironmentKind,
         label: Option<&str>,
+        title: Option<&str>,
+        proves: Option<&str>,
         content: &[Block],
         caption: Option<&[Inline]>,
     ) -> Result<()> {
@@ -372,11 +1033,12 @@ impl<'a> HtmlRenderer<'a> {
             EnvironmentKind::Proof => "proof",
             EnvironmentKind::Figure => "figure",
             EnvironmentKind::Table => "table",
+            EnvironmentKind::Listing => "listing",
             _ => "theorem-like",
         };
 
-        // Use figure element for figures
-        let tag = if matches!(kind, EnvironmentKind::Figure) {
+        // Use figure element for figures and listings
+        let tag = if matches!(kind, EnvironmentKind::Figure | EnvironmentKind::Listing) {
             "figure"
         } else {
             "div"
@@ -401,6 +1063,13 @@ impl<'a> HtmlRenderer<'a> {
                 r#"<span class="{}env-header">"#,
                 self.config.class_prefix
             ));
+            let permalink = id.as_ref().filter(|_| self.config.permalinks);
+            if let Some(id) = permalink {
+                self.output.push_str(&format!(
+                    r##"<a href="#{}" class="{}permalink">"##,
+                    id, self.config.class_prefix
+                ));
+            }
             self.output
                 .push_str(&format!("<strong>{}</strong>", kind.display_name()));
             if let Some(lbl) = label {
@@ -408,11 +1077,28 @@ impl<'a> HtmlRenderer<'a> {
                     self.output.push_str(&format!(" {}", num));
                 }
             }
+            if permalink.is_some() {
+                self.output.push_str("</a>");
+            }
+            if let Some(title) = title {
+                self.output.push_str(&format!(" ({})", escape_html(title)));
+            }
             self.output.push_str(".</span>\n");
         } else if matches!(kind, EnvironmentKind::Proof) {
+            // "Proof of Theorem 1." when the proved result is known
+            let of = match proves.and_then(|lbl| self.doc.labels.get(lbl)) {
+                Some(info) if self.config.link_references => format!(
+                    r##" of <a href="#{}" class="{}ref">{}</a>"##,
+                    info.html_id,
+                    self.config.class_prefix,
+                    escape_html(&info.display)
+                ),
+                Some(info) => format!(" of {}", escape_html(&info.display)),
+                None => String::new(),
+            };
             self.output.push_str(&format!(
-                r#"<span class="{}env-header"><em>Proof.</em></span>"#,
-                self.config.class_prefix
+                r#"<span class="{}env-header"><em>Proof{}.</em></span>"#,
+                self.config.class_prefix, of
             ));
         }
 
@@ -421,9 +1107,14 @@ impl<'a> HtmlRenderer<'a> {
             r#"<div class="{}env-content">"#,
             self.config.class_prefix
         ));
+        self.environment_depth += 1;
         for block in content {
             self.render_block(block)?;
         }
+        self.environment_depth -= 1;
+        if matches!(kind, EnvironmentKind::Abstract) {
+            self.render_keywords_line();
+        }
         self.output.push_str("</div>\n");
 
         // Caption for figures
@@ -455,20 +1146,37 @@ impl<'a> HtmlRenderer<'a> {
         Ok(())
     }
 
+    /// The "Keywords:" line closing an abstract (only with `show_keywords`).
+    fn render_keywords_line(&mut self) {
+        let keywords = &self.doc.document.metadata.keywords;
+        if self.config.show_keywords && !keywords.is_empty() {
+            self.output.push_str(&format!(
+                "<p class=\"{}keywords\"><strong>Keywords:</strong> {}</p>\n",
+                self.config.class_prefix,
+                escape_html(&keywords.join(", "))
+            ));
+        }
+    }
+
     fn render_table(
         &mut self,
         headers: &[Vec<Inline>],
         alignments: &[Alignment],
+        widths: Option<&[f64]>,
         rows: &[Vec<Vec<Inline>>],
         label: Option<&str>,
         caption: Option<&[Inline]>,
     ) -> Result<()> {
         let id = label.map(label_to_id);
 
-        self.output.push_str(&format!(
-            r#"<table class="{}table""#,
-            self.config.class_prefix
-        ));
+        let prefix = &self.config.class_prefix;
+        let class = if self.config.table_classes {
+            format!("{}table {}table-striped", prefix, prefix)
+        } else {
+            format!("{}table", prefix)
+        };
+        self.output
+            .push_str(&format!(r#"<table class="{}""#, class));
         if let Some(ref id) = id {
             self.output.push_str(&format!(r#" id="{}""#, id));
         }
@@ -487,6 +1195,16 @@ impl<'a> HtmlRenderer<'a> {
             self.output.push_str("</caption>\n");
         }
 
+        // Relative column widths
+        if let Some(widths) = widths {
+            self.output.push_str("<colgroup>\n");
+            for width in widths {
+                self.output
+                    .push_str(&format!("<col style=\"width: {:.1}%\">\n", width * 100.0));
+            }
+            self.output.push_str("</colgroup>\n");
+        }
+
         // Header
         self.output.push_str("<thead>\n<tr>\n");
         for (i, cell) in headers.iter().enumerate() {
@@ -500,8 +1218,16 @@ impl<'a> HtmlRenderer<'a> {
 
         // Body
         self.output.push_str("<tbody>\n");
-        for row in rows {
-            self.output.push_str("<tr>\n");
+        for (row_index, row) in rows.iter().enumerate() {
+            if self.config.table_classes {
+                let parity = if row_index % 2 == 0 { "odd" } else { "even" };
+                self.output.push_str(&format!(
+                    "<tr class=\"{}row-{}\">\n",
+                    self.config.class_prefix, parity
+                ));
+            } else {
+                self.output.push_str("<tr>\n");
+            }
             for (i, cell) in row.iter().enumerate() {
                 let align = alignments.get(i).copied().unwrap_or_default();
                 let style = alignment_style(align);
@@ -521,9 +1247,20 @@ impl<'a> HtmlRenderer<'a> {
     fn render_toc(&mut self) -> Result<()> {
         self.output
             .push_str(&format!(r#"<nav class="{}toc">"#, self.config.class_prefix));
-        self.output.push_str("<h2>Table of Contents</h2>\n<ul>\n");
+        if let Some(title) = &self.config.toc_title {
+            self.output
+                .push_str(&format!("<h2>{}</h2>\n", escape_html(title)));
+        }
+        self.output.push_str("<ul>\n");
 
-        let mut current_level = 0u8;
+        let mut entries = Vec::new();
+        let mut heading_index = 0;
+        let toc_depth = self
+            .config
+            .toc_depth
+            .or(self.doc.document.metadata.toc_depth)
+            .unwrap_or(6);
+        let toc_min_depth = self.config.toc_min_depth.max(1);
 
         for block in &self.doc.document.blocks {
             if let Block::Heading {
@@ -532,21 +1269,18 @@ impl<'a> HtmlRenderer<'a> {
                 label,
             } = block
             {
-                // Adjust nesting
-                while current_level < *level {
-                    self.output.push_str("<ul>\n");
-                    current_level += 1;
-                }
-                while current_level > *level {
-                    self.output.push_str("</ul>\n");
-                    current_level -= 1;
+                let auto_id = self.heading_ids.get(heading_index).cloned();
+                heading_index += 1;
+                if *level > toc_depth || *level < toc_min_depth {
+                    continue;
                 }
 
-                self.output.push_str("<li>");
-                if let Some(lbl) = label {
-                    let id = label_to_id(lbl);
+                // Render the entry text on its own, then restore the output
+                let outer = std::mem::take(&mut self.output);
+                if let Some(id) = auto_id.or_else(|| label.as_ref().map(|l| label_to_id(l))) {
                     self.output.push_str(&format!("<a href=\"#{}\">", id));
-                    if let Some(num) = self.doc.section_numbers.get(lbl) {
+                    if let Some(num) = label.as_ref().and_then(|l| self.doc.section_numbers.get(l))
+                    {
                         self.output.push_str(&format!("{}. ", num));
                     }
                     self.render_inlines(content)?;
@@ -554,14 +1288,38 @@ impl<'a> HtmlRenderer<'a> {
                 } else {
                     self.render_inlines(content)?;
                 }
-                self.output.push_str("</li>\n");
+                let entry = std::mem::replace(&mut self.output, outer);
+                // The shallowest listed level is the outermost list
+                entries.push((*level - toc_min_depth + 1, entry));
+            } else {
+                let mut nested = Vec::new();
+                collect_headings(std::slice::from_ref(block), &mut nested);
+                heading_index += nested.len();
             }
         }
 
-        // Close remaining lists
-        while current_level > 0 {
-            self.output.push_str("</ul>\n");
-            current_level -= 1;
+        if self.config.collapsible_toc {
+            push_toc_tree(&mut self.output, &entries, true);
+        } else {
+            let mut current_level = 0u8;
+            for (level, entry) in &entries {
+                // Adjust nesting
+                while current_level < *level {
+                    self.output.push_str("<ul>\n");
+                    current_level += 1;
+                }
+                while current_level > *level {
+                    self.output.push_str("</ul>\n");
+                    current_level -= 1;
+                }
+                self.output.push_str(&format!("<li>{}</li>\n", entry));
+            }
+
+            // Close remaining lists
+            while current_level > 0 {
+                self.output.push_str("</ul>\n");
+                current_level -= 1;
+            }
         }
 
         self.output.push_str("</ul>\n</nav>\n");
@@ -570,8 +1328,27 @@ impl<'a> HtmlRenderer<'a> {
     }
 
     fn render_inlines(&mut self, inlines: &[Inline]) -> Result<()> {
-        for inline in inlines {
-            self.render_inline(inline)?;
+        let join = self.config.join_adjacent_footnotes
+            && self.config.footnote_style == FootnoteStyle::EndNotes;
+
+        let mut i = 0;
+        while i < inlines.len() {
+            let run = if join {
+                inlines[i..]
+                    .iter()
+                    .take_while(|inline| matches!(inline, Inline::Footnote(_)))
+                    .count()
+            } else {
+                0
+            };
+
+            if run > 1 {
+                self.render_footnote_group(run);
+                i += run;
+            } else {
+                self.render_inline(&inlines[i])?;
+                i += 1;
+            }
         }
         Ok(())
     }
@@ -614,11 +1391,38 @@ impl<'a> HtmlRenderer<'a> {
                 self.render_inlines(inlines)?;
                 self.output.push_str("</span>");
             }
+            Inline::Lang { lang, dir, content } => {
+                self.output.push_str("<span");
+                if let Some(lang) = lang {
+                    self.output
+                        .push_str(&format!(r#" lang="{}""#, escape_html(lang)));
+                }
+                if let Some(dir) = dir {
+                    self.output
+                        .push_str(&format!(r#" dir="{}""#, escape_html(dir)));
+                }
+                self.output.push('>');
+                self.render_inlines(content)?;
+                self.output.push_str("</span>");
+            }
             Inline::Code(code) => {
                 self.output.push_str("<code>");
                 self.output.push_str(&escape_html(code));
                 self.output.push_str("</code>");
             }
+            Inline::Kbd(keys) => {
+                self.output.push_str("<kbd>");
+                self.output.push_str(&escape_html(keys));
+                self.output.push_str("</kbd>");
+            }
+            Inline::Verbatim(text) => self.output.push_str(&escape_html(text)),
+            Inline::Abbreviation { abbr, title } => {
+                self.output.push_str(&format!(
+                    r#"<abbr title="{}">{}</abbr>"#,
+                    escape_html(title),
+                    escape_html(abbr)
+                ));
+            }
             Inline::Link {
                 url,
                 title,
@@ -634,7 +1438,21 @@ impl<'a> HtmlRenderer<'a> {
                 self.render_inlines(content)?;
                 self.output.push_str("</a>");
             }
-            Inline::Image { url, alt, title } => {
+            // Left by skipping resolution; shown as written
+            Inline::LinkReference {
+                content, closing, ..
+            } => {
+                self.output.push('[');
+                self.render_inlines(content)?;
+                self.output.push_str(&escape_html(closing));
+            }
+            Inline::Image {
+                url,
+                alt,
+                title,
+                attributes,
+                ..
+            } => {
                 self.output.push_str(&format!(
                     r#"<img src="{}" alt="{}""#,
                     escape_html(url),
@@ -644,6 +1462,16 @@ impl<'a> HtmlRenderer<'a> {
                     self.output
                         .push_str(&format!(r#" title="{}""#, escape_html(title)));
                 }
+                // Responsive image candidates pass through; other keys are ignored
+                for (key, value) in attributes {
+                    if key == "srcset" || key == "sizes" {
+                        self.output
+                            .push_str(&format!(r#" {}="{}""#, key, escape_html(value)));
+                    }
+                }
+                if self.config.lazy_images {
+                    self.output.push_str(r#" loading="lazy" decoding="async""#);
+                }
                 self.output.push('>');
             }
             Inline::InlineMath(latex) => {
@@ -653,15 +1481,63 @@ impl<'a> HtmlRenderer<'a> {
             Inline::Citation(cite) => {
                 self.render_citation(cite)?;
             }
-            Inline::Reference { label, resolved } => {
+            Inline::Reference {
+                label, resolved, ..
+            } => {
                 let id = label_to_id(label);
-                let text = resolved.as_deref().unwrap_or("??");
-                self.output.push_str(&format!(
-                    "<a href=\"#{}\" class=\"{}ref\">{}</a>",
-                    id,
-                    self.config.class_prefix,
-                    escape_html(text)
-                ));
+                self.references.push(IndexReference {
+                    label: label.clone(),
+                    href: format!("#{}", id),
+                    resolved: resolved.is_some(),
+                });
+                let placeholder;
+                let text = match resolved {
+                    Some(text) => text.as_str(),
+                    None => match self.config.unresolved_references {
+                        UnresolvedReferenceStyle::Marker => {
+                            placeholder = format!("??{}", label);
+                            &placeholder
+                        }
+                        UnresolvedReferenceStyle::Label => label.as_str(),
+                        UnresolvedReferenceStyle::Source => {
+                            placeholder = format!("@{}", label);
+                            &placeholder
+                        }
+                        UnresolvedReferenceStyle::Span => {
+                            let label = escape_html(label);
+                            self.output.push_str(&format!(
+                                r#"<span class="{}unresolved" title="Unresolved reference: {}">{}</span>"#,
+                                self.config.class_prefix, label, label
+                            ));
+                            return Ok(());
+                        }
+                    },
+                };
+                let title = match self.doc.labels.get(label) {
+                    Some(LabelInfo {
+                        preview: Some(preview),
+                        ..
+                    }) if self.config.reference_previews => {
+                        format!(" title=\"{}\"", escape_html(preview))
+                    }
+                    _ => String::new(),
+                };
+                if self.config.link_references {
+                    self.output.push_str(&format!(
+                        "<a href=\"#{}\" class=\"{}ref\"{}>{}</a>",
+                        id,
+                        self.config.class_prefix,
+                        title,
+                        escape_html(text)
+                    ));
+                } else {
+                    self.output.push_str(&format!(
+                        "<span class=\"{}ref\"{}>{}</span>",
+                        self.config.class_prefix,
+                        title,
+                        escape_html(text)
+                    ));
+                }
             }
             Inline::Footnote(kind) => {
                 self.render_footnote(kind)?;
@@ -672,6 +1548,13 @@ impl<'a> HtmlRenderer<'a> {
             Inline::HardBreak => {
                 self.output.push_str("<br>\n");
             }
+            Inline::NonBreakingSpace => {
+                if self.config.tilde_as_nbsp {
+                    self.output.push_str("&nbsp;");
+                } else {
+                    self.output.push('~');
+                }
+            }
             Inline::RawHtml(html) => {
                 self.output.push_str(html);
             }
@@ -681,15 +1564,50 @@ impl<'a> HtmlRenderer<'a> {
     }
 
     fn render_citation(&mut self, cite: &Citation) -> Result<()> {
-        self.output.push_str(&format!(
-            r#"<span class="{}citation">"#,
-            self.config.class_prefix
-        ));
+        if self.config.bibliography_backlinks {
+            self.citation_counter += 1;
+            let site = format!("cite-{}", self.citation_counter);
+            self.output.push_str(&format!(
+                r#"<span id="{}" class="{}citation">"#,
+                site, self.config.class_prefix
+            ));
+            for key in &cite.keys {
+                self.citation_sites
+                    .entry(key.clone())
+                    .or_default()
+                    .push(site.clone());
+            }
+        } else {
+            self.output.push_str(&format!(
+                r#"<span class="{}citation">"#,
+                self.config.class_prefix
+            ));
+        }
+
+        if let Some(hook) = &self.config.citation_hook {
+            let entries: Vec<&BibEntry> = cite
+                .keys
+                .iter()
+                .filter_map(|key| self.doc.citations.get(key))
+                .collect();
+            if let Some(html) = (hook.0)(cite, &entries) {
+                self.output.push_str(&html);
+                self.output.push_str("</span>");
+                return Ok(());
+            }
+        }
+
+        if !self.citation_numbers.is_empty() && cite.style != CitationStyle::AuthorOnly {
+            self.render_numeric_citation(cite);
+            self.output.push_str("</span>");
+            return Ok(());
+        }
 
         match cite.style {
             CitationStyle::Parenthetical => {
                 // (Author, Year) or [Author, Year]
                 self.output.push('[');
+                self.push_citation_prefix(cite);
                 for (i, key) in cite.keys.iter().enumerate() {
                     if i > 0 {
                         self.output.push_str("; ");
@@ -697,23 +1615,22 @@ impl<'a> HtmlRenderer<'a> {
                     let id = format!("bib-{}", key);
                     if let Some(entry) = self.doc.citations.get(key) {
                         let short = format_short_citation(entry);
-                        self.output.push_str(&format!(
-                            "<a href=\"#{}\">{}</a>",
-                            id,
-                            escape_html(&short)
+                        self.output.push_str(&bib_link(
+                            &id,
+                            &escape_html(&short),
+                            self.config.link_references,
                         ));
                     } else {
                         self.output
-                            .push_str(&format!("<a href=\"#{}\">{}</a>", id, key));
+                            .push_str(&bib_link(&id, key, self.config.link_references));
                     }
-                }
-                if let Some(ref locator) = cite.locator {
-                    self.output.push_str(&format!(", {}", escape_html(locator)));
+                    self.push_locator(cite.locator(i));
                 }
                 self.output.push(']');
             }
             CitationStyle::Textual => {
                 // Author (Year)
+                self.push_citation_prefix(cite);
                 for (i, key) in cite.keys.iter().enumerate() {
                     if i > 0 {
                         self.output.push_str(", ");
@@ -722,22 +1639,20 @@ impl<'a> HtmlRenderer<'a> {
                     if let Some(entry) = self.doc.citations.get(key) {
                         let (author, year) = format_author_year(entry);
                         self.output.push_str(&format!(
-                            "{} (<a href=\"#{}\">{}</a>)",
+                            "{} ({})",
                             escape_html(&author),
-                            id,
-                            escape_html(&year)
+                            bib_link(&id, &escape_html(&year), self.config.link_references)
                         ));
                     } else {
                         self.output
-                            .push_str(&format!("<a href=\"#{}\">{}</a>", id, key));
+                            .push_str(&bib_link(&id, key, self.config.link_references));
                     }
-                }
-                if let Some(ref locator) = cite.locator {
-                    self.output.push_str(&format!(", {}", escape_html(locator)));
+                    self.push_locator(cite.locator(i));
                 }
             }
             CitationStyle::AuthorOnly => {
                 // Just Author
+                self.push_citation_prefix(cite);
                 for (i, key) in cite.keys.iter().enumerate() {
                     if i > 0 {
                         self.output.push_str(", ");
@@ -745,20 +1660,21 @@ impl<'a> HtmlRenderer<'a> {
                     let id = format!("bib-{}", key);
                     if let Some(entry) = self.doc.citations.get(key) {
                         let (author, _) = format_author_year(entry);
-                        self.output.push_str(&format!(
-                            "<a href=\"#{}\">{}</a>",
-                            id,
-                            escape_html(&author)
+                        self.output.push_str(&bib_link(
+                            &id,
+                            &escape_html(&author),
+                            self.config.link_references,
                         ));
                     } else {
                         self.output
-                            .push_str(&format!("<a href=\"#{}\">{}</a>", id, key));
+                            .push_str(&bib_link(&id, key, self.config.link_references));
                     }
                 }
             }
             CitationStyle::YearOnly => {
                 // Just (Year)
                 self.output.push('(');
+                self.push_citation_prefix(cite);
                 for (i, key) in cite.keys.iter().enumerate() {
                     if i > 0 {
                         self.output.push_str(", ");
@@ -766,18 +1682,16 @@ impl<'a> HtmlRenderer<'a> {
                     let id = format!("bib-{}", key);
                     if let Some(entry) = self.doc.citations.get(key) {
                         let (_, year) = format_author_year(entry);
-                        self.output.push_str(&format!(
-                            "<a href=\"#{}\">{}</a>",
-                            id,
-                            escape_html(&year)
+                        self.output.push_str(&bib_link(
+                            &id,
+                            &escape_html(&year),
+                            self.config.link_references,
                         ));
                     } else {
                         self.output
-                            .push_str(&format!("<a href=\"#{}\">{}</a>", id, key));
+                            .push_str(&bib_link(&id, key, self.config.link_references));
                     }
-                }
-                if let Some(ref locator) = cite.locator {
-                    self.output.push_str(&format!(", {}", escape_html(locator)));
+                    self.push_locator(cite.locator(i));
                 }
                 self.output.push(')');
             }
@@ -788,9 +1702,102 @@ impl<'a> HtmlRenderer<'a> {
         Ok(())
     }
 
-    fn render_footnote(&mut self, _kind: &FootnoteKind) -> Result<()> {
+    /// Append a citation's prefix (`see `) ahead of its first key.
+    fn push_citation_prefix(&mut self, cite: &Citation) {
+        if let Some(prefix) = &cite.prefix {
+            self.output.push_str(&escape_html(prefix));
+            self.output.push(' ');
+        }
+    }
+
+    /// Append `, locator` after a cited key.
+    fn push_locator(&mut self, locator: Option<&str>) {
+        if let Some(locator) = locator {
+            self.output.push_str(&format!(", {}", escape_html(locator)));
+        }
+    }
+
+    /// Numeric citation: `[1, 3]`, or `Author [1]` for textual citations.
+    /// Without locators the numbers are sorted and runs of three or more
+    /// collapse to a range (`[1–3, 5]`); keys with locators keep their order
+    /// and are separated by semicolons: `[1, p. 4; 3]`.
+    fn render_numeric_citation(&mut self, cite: &Citation) {
+        let linked = self.config.link_references;
+        let link = |key: &str| {
+            let id = format!("bib-{}", key);
+            match self.citation_numbers.get(key) {
+                Some(num) => bib_link(&id, &num.to_string(), linked),
+                None => bib_link(&id, key, linked),
+            }
+        };
+
+        let numbers: Vec<String> = if cite.locators.iter().any(Option::is_some) {
+            cite.keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| match cite.locator(i) {
+                    Some(locator) => format!("{}, {}", link(key), escape_html(locator)),
+                    None => link(key),
+                })
+                .collect()
+        } else {
+            let mut known: Vec<(usize, &str)> = cite
+                .keys
+                .iter()
+                .filter_map(|key| Some((*self.citation_numbers.get(key)?, key.as_str())))
+                .collect();
+            known.sort();
+            known.dedup();
+
+            let mut numbers = Vec::new();
+            for run in known.chunk_by(|a, b| b.0 == a.0 + 1) {
+                if run.len() >= 3 {
+                    let (first, last) = (run[0].1, run[run.len() - 1].1);
+                    numbers.push(format!("{}–{}", link(first), link(last)));
+                } else {
+                    numbers.extend(run.iter().map(|(_, key)| link(key)));
+                }
+            }
+            let unknown = cite
+                .keys
+                .iter()
+                .filter(|key| !self.citation_numbers.contains_key(*key));
+            numbers.extend(unknown.map(|key| link(key)));
+            numbers
+        };
+
+        if cite.style == CitationStyle::Textual {
+            let authors: Vec<String> = cite
+                .keys
+                .iter()
+                .filter_map(|key| self.doc.citations.get(key))
+                .map(|entry| escape_html(&format_author_year(entry).0))
+                .collect();
+            if !authors.is_empty() {
+                self.output.push_str(&authors.join(", "));
+                self.output.push(' ');
+            }
+        }
+
+        let separator = if cite.locators.iter().any(Option::is_some) {
+            "; "
+        } else {
+            ", "
+        };
+        self.output.push('[');
+        self.push_citation_prefix(cite);
+        self.output.push_str(&numbers.join(separator));
+        self.output.push(']');
+    }
+
+    fn render_footnote(&mut self, kind: &FootnoteKind) -> Result<()> {
         self.footnote_counter += 1;
         let num = self.footnote_counter;
+
+        if self.config.footnote_style == FootnoteStyle::Sidenotes {
+            return self.render_sidenote(kind, num);
+        }
+
         let id = format!("fn-{}", num);
         let back_id = format!("fnref-{}", num);
 
@@ -802,6 +1809,44 @@ impl<'a> HtmlRenderer<'a> {
         Ok(())
     }
 
+    /// Render `count` adjacent footnote references as one superscript.
+    fn render_footnote_group(&mut self, count: usize) {
+        let links: Vec<String> = (0..count)
+            .map(|_| {
+                self.footnote_counter += 1;
+                let num = self.footnote_counter;
+                format!(r##"<a id="fnref-{num}" href="#fn-{num}">{num}</a>"##)
+            })
+            .collect();
+
+        self.output.push_str(&format!(
+            "<sup class=\"{}footnote-ref\">[{}]</sup>",
+            self.config.class_prefix,
+            links.join(", ")
+        ));
+    }
+
+    fn render_sidenote(&mut self, kind: &FootnoteKind, num: u32) -> Result<()> {
+        let p = &self.config.class_prefix;
+        let id = format!("sn-{}", num);
+
+        self.output.push_str(&format!(
+            r#"<label for="{id}" class="{p}sidenote-number">{num}</label><input type="checkbox" id="{id}" class="{p}sidenote-toggle"><span class="{p}sidenote">"#,
+        ));
+        self.output.push_str(&format!("<sup>{}</sup> ", num));
+
+        let content = match kind {
+            FootnoteKind::Inline(content) => Some(content),
+            FootnoteKind::Reference(id) => self.doc.footnotes.get(id),
+        };
+        if let Some(content) = content {
+            self.render_inlines(content)?;
+        }
+
+        self.output.push_str("</span>");
+        Ok(())
+    }
+
     fn render_footnotes_section(&mut self) -> Result<()> {
         self.output.push_str(&format!(
             r#"<section class="{}footnotes">"#,
@@ -821,7 +1866,10 @@ impl<'a> HtmlRenderer<'a> {
 
     fn render_block_footnotes(&mut self, block: &Block, counter: &mut u32) -> Result<()> {
         match block {
-            Block::Paragraph(inlines) => self.render_inline_footnotes(inlines, counter)?,
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            } => self.render_inline_footnotes(inlines, counter)?,
             Block::Heading { content, .. } => self.render_inline_footnotes(content, counter)?,
             Block::Environment {
                 content, caption, ..
@@ -845,6 +1893,27 @@ impl<'a> HtmlRenderer<'a> {
                     }
                 }
             }
+            Block::Table {
+                headers,
+                rows,
+                caption,
+                ..
+            } => {
+                for cell in headers.iter().chain(rows.iter().flatten()) {
+                    self.render_inline_footnotes(cell, counter)?;
+                }
+                if let Some(c) = caption {
+                    self.render_inline_footnotes(c, counter)?;
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    self.render_inline_footnotes(&item.term, counter)?;
+                    for b in &item.description {
+                        self.render_block_footnotes(b, counter)?;
+                    }
+                }
+            }
             _ => {}
         }
         Ok(())
@@ -853,33 +1922,65 @@ impl<'a> HtmlRenderer<'a> {
     fn render_inline_footnotes(&mut self, inlines: &[Inline], counter: &mut u32) -> Result<()> {
         for inline in inlines {
             match inline {
-                Inline::Footnote(FootnoteKind::Inline(content)) => {
+                Inline::Footnote(kind) => {
                     *counter += 1;
                     let id = format!("fn-{}", counter);
                     let back_id = format!("fnref-{}", counter);
 
                     self.output.push_str(&format!("<li id=\"{}\">", id));
-                    self.render_inlines(content)?;
+                    match kind {
+                        FootnoteKind::Inline(content) => self.render_inlines(content)?,
+                        FootnoteKind::Reference(id) => match self.doc.footnotes.get(id) {
+                            Some(content) => self.render_inlines(content)?,
+                            // Keeps the list numbered like the references
+                            None => self.output.push_str(&escape_html(&format!("[^{}]", id))),
+                        },
+                    }
                     self.output.push_str(&format!(
                         " <a href=\"#{}\" class=\"{}footnote-back\">↩</a></li>",
                         back_id, self.config.class_prefix
                     ));
                     self.output.push('\n');
                 }
-                Inline::Emphasis(inner) | Inline::Strong(inner) | Inline::Strikethrough(inner) => {
-                    self.render_inline_footnotes(inner, counter)?;
-                }
-                Inline::Link { content, .. } => {
-                    self.render_inline_footnotes(content, counter)?;
+                _ => {
+                    if let Some(inner) = inline.children() {
+                        self.render_inline_footnotes(inner, counter)?;
+                    }
                 }
-                _ => {}
             }
         }
         Ok(())
     }
 
+    /// Cited keys in reference list order: first appearance for numeric
+    /// citations, otherwise as `bibliography_sort` says.
+    fn bibliography_order(&self) -> Vec<String> {
+        let mut order = get_citation_order(&self.doc.document);
+
+        // Entry ids are `bib-{key}` in any order, so in-text links still land
+        if self.citation_numbers.is_empty() {
+            let citations = &self.doc.citations;
+            match self.config.bibliography_sort {
+                BibliographySort::Appearance => {}
+                BibliographySort::AuthorYear => order.sort_by_cached_key(|key| {
+                    let entry = citations.get(key);
+                    let author = entry
+                        .and_then(|e| e.authors.first())
+                        .map(|a| last_name(a).to_lowercase());
+                    let year = entry.and_then(|e| e.year.clone());
+                    let title = entry
+                        .and_then(|e| e.title.as_ref())
+                        .map(|t| t.to_lowercase());
+                    (author, year, title)
+                }),
+                BibliographySort::Key => order.sort(),
+            }
+        }
+        order
+    }
+
     fn render_bibliography(&mut self) -> Result<()> {
-        let order = get_citation_order(&self.doc.document);
+        let order = self.bibliography_order();
 
         if order.is_empty() {
             return Ok(());
@@ -889,13 +1990,27 @@ impl<'a> HtmlRenderer<'a> {
             r#"<section class="{}bibliography">"#,
             self.config.class_prefix
         ));
-        self.output.push_str("<h2>References</h2>\n<ol>\n");
+        // Numeric entries carry their own `[n]` label in place of the marker
+        if self.citation_numbers.is_empty() {
+            self.output.push_str("<h2>References</h2>\n<ol>\n");
+        } else {
+            self.output.push_str(
+                "<h2>References</h2>\n<ol style=\"list-style: none; padding-inline-start: 0;\">\n",
+            );
+        }
 
         for key in order {
             if let Some(entry) = self.doc.citations.get(&key) {
                 let id = format!("bib-{}", key);
                 self.output.push_str(&format!(r#"<li id="{}">"#, id));
+                if let Some(number) = self.citation_numbers.get(&key) {
+                    self.output.push_str(&format!(
+                        r#"<span class="{}bib-number">[{}]</span> "#,
+                        self.config.class_prefix, number
+                    ));
+                }
                 self.output.push_str(&format_bibliography_entry(entry));
+                self.render_citation_backlinks(&key);
                 self.output.push_str("</li>\n");
             }
         }
@@ -905,40 +2020,215 @@ impl<'a> HtmlRenderer<'a> {
         Ok(())
     }
 
+    /// Superscript links from a bibliography entry back to each citation site.
+    fn render_citation_backlinks(&mut self, key: &str) {
+        let Some(sites) = self.citation_sites.get(key) else {
+            return;
+        };
+
+        let p = &self.config.class_prefix;
+        let mut links = format!(r#" <span class="{}citation-backlinks">↩"#, p);
+        for (i, site) in sites.iter().enumerate() {
+            links.push_str(&format!(
+                r##" <a href="#{}" class="{}citation-back"><sup>{}</sup></a>"##,
+                site,
+                p,
+                i + 1
+            ));
+        }
+        links.push_str("</span>");
+        self.output.push_str(&links);
+    }
+
     fn default_styles(&self) -> String {
         format!(
             r#"<style>
 .{p}document {{ max-width: 800px; margin: 0 auto; padding: 2em; font-family: Georgia, serif; line-height: 1.6; }}
-.{p}section-number {{ color: #666; margin-right: 0.5em; }}
+.{p}section-number {{ color: #666; margin-inline-end: 0.5em; }}
 .{p}equation {{ display: flex; align-items: center; justify-content: space-between; margin: 1em 0; }}
 .{p}equation-number {{ color: #666; }}
-.{p}theorem-like {{ margin: 1.5em 0; padding: 1em; background: #f8f8f8; border-left: 3px solid #333; }}
+.{p}theorem-like {{ margin: 1.5em 0; padding: 1em; background: #f8f8f8; border-inline-start: 3px solid #333; }}
 .{p}proof {{ margin: 1em 0; padding: 1em; font-style: italic; }}
-.{p}qed {{ float: right; }}
+.{p}qed {{ float: inline-end; }}
 .{p}figure {{ margin: 2em 0; text-align: center; }}
 .{p}figure img {{ max-width: 100%; }}
 .{p}table {{ border-collapse: collapse; margin: 1em auto; }}
 .{p}table th, .{p}table td {{ border: 1px solid #ddd; padding: 0.5em 1em; }}
 .{p}table th {{ background: #f0f0f0; }}
 .{p}toc {{ background: #fafafa; padding: 1em 2em; margin: 2em 0; border-radius: 4px; }}
-.{p}toc ul {{ list-style: none; padding-left: 1.5em; }}
-.{p}toc > ul {{ padding-left: 0; }}
+.{p}toc ul {{ list-style: none; padding-inline-start: 1.5em; }}
+.{p}toc > ul {{ padding-inline-start: 0; }}
 .{p}citation {{ }}
 .{p}ref {{ color: #0066cc; text-decoration: none; }}
 .{p}ref:hover {{ text-decoration: underline; }}
+.{p}permalink {{ color: inherit; text-decoration: none; }}
+.{p}permalink:hover {{ text-decoration: underline; }}
 .{p}footnotes {{ font-size: 0.9em; color: #666; }}
 .{p}footnote-ref {{ font-size: 0.8em; }}
+.{p}sidenote {{ float: inline-end; clear: inline-end; margin-inline-end: -40%; width: 35%; font-size: 0.85em; color: #666; }}
+.{p}sidenote-number {{ cursor: pointer; font-size: 0.8em; vertical-align: super; }}
+.{p}sidenote-toggle {{ display: none; }}
 .{p}bibliography {{ margin-top: 3em; }}
-.{p}bibliography ol {{ padding-left: 2em; }}
+.{p}bibliography ol {{ padding-inline-start: 2em; }}
 .{p}env-header {{ font-weight: bold; }}
+.{p}env-rule {{ border: none; border-top: 1px solid #ddd; margin: 1em 10%; }}
 .{p}env-content {{ margin-top: 0.5em; }}
-</style>
+{code}</style>
 "#,
-            p = self.config.class_prefix
+            p = self.config.class_prefix,
+            code = match self.config.code_overflow {
+                CodeOverflow::Visible => String::new(),
+                CodeOverflow::Scroll => format!(
+                    ".{}code-scroll {{ overflow-x: auto; max-width: 100%; }}\n",
+                    self.config.class_prefix
+                ),
+                CodeOverflow::Wrap => format!(
+                    ".{}code-wrap {{ white-space: pre-wrap; overflow-wrap: anywhere; }}\n",
+                    self.config.class_prefix
+                ),
+            }
         )
     }
 }
 
+/// Compute the HTML id of every heading, in rendering order.
+///
+/// Labeled headings keep their label-derived id; the others get a unique slug
+/// of their text, avoiding every other id the page emits: labels, paragraph
+/// ids, bibliography entries, and footnote and citation anchors.
+fn assign_heading_ids(doc: &ResolvedDocument, mode: SlugMode) -> Vec<String> {
+    let blocks = &doc.document.blocks;
+    let mut headings = Vec::new();
+    collect_headings(blocks, &mut headings);
+
+    let mut slugger = Slugger::new(mode);
+    for (label, _) in &headings {
+        if let Some(lbl) = label {
+            slugger.reserve(&label_to_id(lbl));
+        }
+    }
+    for info in doc.labels.values() {
+        slugger.reserve(&info.html_id);
+    }
+    let mut paragraph_ids = Vec::new();
+    collect_paragraph_ids(blocks, &mut paragraph_ids);
+    for id in paragraph_ids {
+        slugger.reserve(&label_to_id(id));
+    }
+    for key in doc.citations.keys() {
+        slugger.reserve(&format!("bib-{}", key));
+    }
+    for prefix in ["fn", "fnref", "sn", "cite"] {
+        slugger.reserve_numbered(prefix);
+    }
+
+    headings
+        .into_iter()
+        .map(|(label, content)| match label {
+            Some(lbl) => label_to_id(lbl),
+            None => slugger.slug(&inlines_to_text(content)),
+        })
+        .collect()
+}
+
+/// Collect headings (label and content) in the order the renderer visits them.
+fn collect_headings<'b>(blocks: &'b [Block], out: &mut Vec<(Option<&'b str>, &'b [Inline])>) {
+    for block in blocks {
+        match block {
+            Block::Heading { content, label, .. } => out.push((label.as_deref(), content)),
+            Block::BlockQuote(inner) | Block::Abstract(inner) => collect_headings(inner, out),
+            Block::Environment { content, .. } => collect_headings(content, out),
+            Block::List { items, .. } => {
+                for item in items {
+                    collect_headings(&item.content, out);
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    collect_headings(&item.description, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collect the ids of attributed paragraphs (`{#id}`).
+fn collect_paragraph_ids<'b>(blocks: &'b [Block], out: &mut Vec<&'b str>) {
+    for block in blocks {
+        match block {
+            Block::AttributedParagraph { id: Some(id), .. } => out.push(id),
+            Block::BlockQuote(inner) | Block::Abstract(inner) => collect_paragraph_ids(inner, out),
+            Block::Environment { content, .. } => collect_paragraph_ids(content, out),
+            Block::List { items, .. } => {
+                for item in items {
+                    collect_paragraph_ids(&item.content, out);
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    collect_paragraph_ids(&item.description, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Link citation text to its bibliography entry, or leave it as plain text.
+fn bib_link(id: &str, text: &str, linked: bool) -> String {
+    if linked {
+        format!("<a href=\"#{}\">{}</a>", id, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Make text safe to embed inside an HTML comment: no `--` run (which could
+/// close it as `-->` or `--!>`), no `->` and no trailing `-` that could join
+/// the closing delimiter.
+fn escape_comment(s: &str) -> String {
+    let mut text = s.replace("->", "- >");
+    while text.contains("--") {
+        text = text.replace("--", "- -");
+    }
+    if text.ends_with('-') {
+        text.push(' ');
+    }
+    text
+}
+
+/// Emit table of contents entries as properly nested list items. Each entry
+/// owns the deeper entries after it; with `collapsible`, an entry that has
+/// any becomes a `<details>` element with the entry as its summary.
+fn push_toc_tree(out: &mut String, entries: &[(u8, String)], collapsible: bool) {
+    let mut i = 0;
+    while i < entries.len() {
+        let (level, entry) = &entries[i];
+        let end = entries[i + 1..]
+            .iter()
+            .position(|(next, _)| next <= level)
+            .map_or(entries.len(), |n| i + 1 + n);
+        let children = &entries[i + 1..end];
+
+        if children.is_empty() {
+            out.push_str(&format!("<li>{}</li>\n", entry));
+        } else if collapsible {
+            out.push_str(&format!(
+                "<li><details><summary>{}</summary>\n<ul>\n",
+                entry
+            ));
+            push_toc_tree(out, children, false);
+            out.push_str("</ul>\n</details></li>\n");
+        } else {
+            out.push_str(&format!("<li>{}\n<ul>\n", entry));
+            push_toc_tree(out, children, false);
+            out.push_str("</ul>\n</li>\n");
+        }
+        i = end;
+    }
+}
+
 fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -954,20 +2244,23 @@ fn alignment_style(align: Alignment) -> &'static str {
     }
 }
 
+/// Last name of a BibTeX author: the part before the comma in "Knuth,
+/// Donald", otherwise the final word.
+fn last_name(author: &str) -> &str {
+    if let Some(comma) = author.find(',') {
+        &author[..comma]
+    } else if let Some(space) = author.rfind(' ') {
+        &author[space + 1..]
+    } else {
+        author
+    }
+}
+
 fn format_short_citation(entry: &BibEntry) -> String {
     let author = entry
         .authors
         .first()
-        .map(|a| {
-            // Extract last name
-            if let Some(comma) = a.find(',') {
-                &a[..comma]
-            } else if let Some(space) = a.rfind(' ') {
-                &a[space + 1..]
-            } else {
-                a.as_str()
-            }
-        })
+        .map(|a| last_name(a))
         .unwrap_or("Unknown");
 
     let year = entry.year.as_deref().unwrap_or("n.d.");
@@ -975,19 +2268,7 @@ fn format_short_citation(entry: &BibEntry) -> String {
     if entry.authors.len() > 2 {
         format!("{} et al., {}", author, year)
     } else if entry.authors.len() == 2 {
-        let author2 = entry
-            .authors
-            .get(1)
-            .map(|a| {
-                if let Some(comma) = a.find(',') {
-                    &a[..comma]
-                } else if let Some(space) = a.rfind(' ') {
-                    &a[space + 1..]
-                } else {
-                    a.as_str()
-                }
-            })
-            .unwrap_or("");
+        let author2 = entry.authors.get(1).map(|a| last_name(a)).unwrap_or("");
         format!("{} & {}, {}", author, author2, year)
     } else {
         format!("{}, {}", author, year)
@@ -995,64 +2276,20 @@ fn format_short_citation(entry: &BibEntry) -> String {
 }
 
 /// Format author and year separately for textual citations.
-fn format_author_year(entry: &BibEntry) -> (String, String) {
-    let author = if entry.authors.len() > 2 {
-        let first = entry
+pub(crate) fn format_author_year(entry: &BibEntry) -> (String, String) {
+    let name = |i: usize| {
+        entry
             .authors
-            .first()
-            .map(|a| {
-                if let Some(comma) = a.find(',') {
-                    &a[..comma]
-                } else if let Some(space) = a.rfind(' ') {
-                    &a[space + 1..]
-                } else {
-                    a.as_str()
-                }
-            })
-            .unwrap_or("Unknown");
-        format!("{} et al.", first)
+            .get(i)
+            .map(|a| last_name(a))
+            .unwrap_or("Unknown")
+    };
+    let author = if entry.authors.len() > 2 {
+        format!("{} et al.", name(0))
     } else if entry.authors.len() == 2 {
-        let first = entry
-            .authors
-            .first()
-            .map(|a| {
-                if let Some(comma) = a.find(',') {
-                    &a[..comma]
-                } else if let Some(space) = a.rfind(' ') {
-                    &a[space + 1..]
-                } else {
-                    a.as_str()
-                }
-            })
-            .unwrap_or("Unknown");
-        let second = entry
-            .authors
-            .get(1)
-            .map(|a| {
-                if let Some(comma) = a.find(',') {
-                    &a[..comma]
-                } else if let Some(space) = a.rfind(' ') {
-                    &a[space + 1..]
-                } else {
-                    a.as_str()
-                }
-            })
-            .unwrap_or("Unknown");
-        format!("{} & {}", first, second)
+        format!("{} & {}", name(0), name(1))
     } else {
-        entry
-            .authors
-            .first()
-            .map(|a| {
-                if let Some(comma) = a.find(',') {
-                    a[..comma].to_string()
-                } else if let Some(space) = a.rfind(' ') {
-                    a[space + 1..].to_string()
-                } else {
-                    a.to_string()
-                }
-            })
-            .unwrap_or_else(|| "Unknown".to_string())
+        name(0).to_string()
     };
 
     let year = entry.year.as_deref().unwrap_or("n.d.").to_string();
@@ -1063,45 +2300,62 @@ fn format_author_year(entry: &BibEntry) -> (String, String) {
 fn format_bibliography_entry(entry: &BibEntry) -> String {
     let mut parts = Vec::new();
 
-    // Authors
+    // Authors, or the editors of an edited volume
     if !entry.authors.is_empty() {
-        parts.push(entry.authors.join(", "));
+        parts.push(escape_html(&entry.authors.join(", ")));
+    } else if let Some(editors) = entry.editors_text() {
+        parts.push(escape_html(&editors));
     }
 
-    // Year
-    if let Some(ref year) = entry.year {
-        parts.push(format!("({})", year));
+    // Date
+    if let Some(date) = entry.date_text() {
+        parts.push(format!("({})", escape_html(&date)));
     }
 
     // Title
-    if let Some(ref title) = entry.title {
+    if let Some(title) = bib_field(&entry.title) {
         parts.push(format!("<em>{}</em>", escape_html(title)));
     }
+    if let Some(edition) = bib_field(&entry.edition) {
+        parts.push(format!("{} ed", escape_html(edition)));
+    }
 
     // Journal/Book
-    if let Some(ref journal) = entry.journal {
-        let mut journal_part = journal.clone();
-        if let Some(ref vol) = entry.volume {
-            journal_part.push_str(&format!(", {}", vol));
-            if let Some(ref num) = entry.number {
-                journal_part.push_str(&format!("({})", num));
+    if let Some(journal) = bib_field(&entry.journal) {
+        let mut journal_part = escape_html(journal);
+        if let Some(vol) = bib_field(&entry.volume) {
+            journal_part.push_str(&format!(", {}", escape_html(vol)));
+            if let Some(num) = bib_field(&entry.number) {
+                journal_part.push_str(&format!("({})", escape_html(num)));
             }
         }
-        if let Some(ref pages) = entry.pages {
-            journal_part.push_str(&format!(", {}", pages));
+        if let Some(pages) = bib_field(&entry.pages) {
+            journal_part.push_str(&format!(", {}", escape_html(pages)));
         }
         parts.push(journal_part);
-    } else if let Some(ref booktitle) = entry.booktitle {
-        parts.push(format!("In <em>{}</em>", escape_html(booktitle)));
+    } else if let Some(booktitle) = bib_field(&entry.booktitle) {
+        match entry.editors_text().filter(|_| !entry.authors.is_empty()) {
+            Some(editors) => parts.push(format!(
+                "In {}, <em>{}</em>",
+                escape_html(&editors),
+                escape_html(booktitle)
+            )),
+            None => parts.push(format!("In <em>{}</em>", escape_html(booktitle))),
+        }
     }
 
     // Publisher
-    if let Some(ref publisher) = entry.publisher {
-        parts.push(publisher.clone());
+    if let Some(publisher) = bib_field(&entry.publisher) {
+        parts.push(escape_html(publisher));
+    }
+
+    if let Some(note) = bib_field(&entry.note) {
+        parts.push(escape_html(note));
     }
 
     // DOI
-    if let Some(ref doi) = entry.doi {
+    if let Some(doi) = bib_field(&entry.doi) {
+        let doi = escape_html(doi);
         parts.push(format!(r#"<a href="https://doi.org/{}">{}</a>"#, doi, doi));
     }
 
@@ -1114,6 +2368,17 @@ mod tests {
     use crate::parser::parse;
     use crate::resolve::{resolve, ResolveConfig};
 
+    /// Parse and resolve `input` against the entries of `bibtex`.
+    fn resolve_with_bibliography(input: &str, bibtex: &str) -> ResolvedDocument {
+        let config = ResolveConfig {
+            bibliography: Some(std::sync::Arc::new(
+                crate::bibtex::parse_bibtex(bibtex).unwrap(),
+            )),
+            ..Default::default()
+        };
+        resolve(parse(input).unwrap(), &config).unwrap()
+    }
+
     #[test]
     fn test_render_simple() {
         let input = "# Hello\n\nThis is a paragraph.";
@@ -1151,4 +2416,1108 @@ mod tests {
         assert!(html.contains("<!DOCTYPE html>"));
         assert!(html.contains("<title>Test Doc</title>"));
     }
+
+    #[test]
+    fn test_small_caps() {
+        let doc = parse("By [sc]Knuth[/sc]; [sc]open and [x]{.small}").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains(r#"smallcaps">Knuth</span>"#));
+        assert!(html.contains("; [sc]open and [x]{.small}</p>"));
+    }
+
+    #[test]
+    fn test_verbatim_span_untouched() {
+        let input =
+            "*[HTML]: HyperText Markup Language\n\nSee `\"HTML\" it's *$@[`{.raw} and \"HTML\".";
+        let config = ResolveConfig {
+            smart_quotes: true,
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(
+            html.contains("See &quot;HTML&quot; it's *$@[ and “<abbr"),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn test_lang_span() {
+        let doc = parse("[bonjour]{lang=fr}").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains(r#"<span lang="fr">bonjour</span>"#));
+    }
+
+    #[test]
+    fn test_auto_heading_ids() {
+        let input = "[[toc]]\n\n# Café Méthode\n\n## Setup {#sec:setup}\n\n# Café Méthode";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            auto_heading_ids: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<h1 id="cafe-methode">"#));
+        assert!(html.contains(r#"<h1 id="cafe-methode-1">"#));
+        assert!(html.contains(r#"<h2 id="sec-setup">"#));
+        assert!(html.contains(r##"<a href="#cafe-methode">"##));
+    }
+
+    #[test]
+    fn test_auto_heading_ids_avoid_other_ids() {
+        let input = "# Eq A\n\n$$ x $$ {#eq:a}\n\n# Fn 1\n\nNote^[Text.] on [@knuth].\n\n\
+                     # Bib Knuth\n\n# Lead\n\nFirst. {#lead}";
+        let resolved = resolve_with_bibliography(
+            input,
+            "@book{knuth, author = {Donald Knuth}, title = {TAOCP}, year = {1968}}",
+        );
+        let config = HtmlConfig {
+            auto_heading_ids: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<h1 id="eq-a-1">"#), "{html}");
+        assert!(html.contains(r#"<h1 id="fn-1-1">"#));
+        assert!(html.contains(r#"<h1 id="bib-knuth-1">"#));
+        assert!(html.contains(r#"<h1 id="lead-1">"#));
+        for id in ["eq-a", "fn-1", "bib-knuth", "lead"] {
+            assert_eq!(html.matches(&format!(r#" id="{id}""#)).count(), 1, "{id}");
+        }
+    }
+
+    #[test]
+    fn test_generator_comment() {
+        let input = "+++\ntitle = \"Notes\"\ndate = \"2024-01-01\"\n+++\n\nText.";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            include_generator_comment: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        let expected = format!(
+            "<!-- generated by markdown-academic v{} -->",
+            env!("CARGO_PKG_VERSION")
+        );
+        assert!(html.starts_with(&expected));
+        assert!(html.contains("<!-- title: Notes -->"));
+        assert!(html.contains("<!-- date: 2024-01-01 -->"));
+
+        for text in ["a--->b", "--!>", "x-", "<!--", "a->"] {
+            let escaped = escape_comment(text);
+            let comment = format!("<!-- {} -->", escaped);
+            assert_eq!(comment.matches("--").count(), 2, "{text}: {comment}");
+            assert!(!escaped.contains("->"), "{text}: {comment}");
+            assert!(!escaped.ends_with('-'), "{text}: {comment}");
+        }
+
+        let standalone = HtmlConfig {
+            standalone: true,
+            ..config
+        };
+        let html = render_html(&resolved, &standalone).unwrap();
+        assert!(html.starts_with(&expected));
+    }
+
+    #[test]
+    fn test_title_block_affiliations() {
+        let input = r#"+++
+title = "Joint Work"
+authors = [
+    { name = "Ada", email = "ada@uni.edu", institution = "Uni A", institution_url = "https://a.edu" },
+    { name = "Bob", institution = "Uni B" },
+    { name = "Cy", institution = "Uni A" },
+]
++++
+
+Body."#;
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            title_block: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<h1 class="mdatitle">Joint Work</h1>"#));
+        assert!(html.contains("Ada<sup>1</sup>"));
+        assert!(html.contains("Bob<sup>2</sup>"));
+        assert!(html.contains("Cy<sup>1</sup>"));
+        assert!(html.contains(r#"href="mailto:ada@uni.edu""#));
+        assert!(html.contains(r#"<li><a href="https://a.edu">Uni A</a></li>"#));
+        assert_eq!(html.matches("<li>").count(), 2);
+    }
+
+    #[test]
+    fn test_subtitle() {
+        let input = "+++\ntitle = \"Main\"\nsubtitle = \"A <Study>\"\n+++\n\nBody.";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            standalone: true,
+            title_block: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            "<h1 class=\"mdatitle\">Main</h1>\n<p class=\"mdasubtitle\">A &lt;Study&gt;</p>\n"
+        ));
+        assert!(html.contains("<title>Main</title>"));
+
+        let config = HtmlConfig {
+            subtitle_in_title: true,
+            ..config
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains("<title>Main: A &lt;Study&gt;</title>"));
+    }
+
+    #[test]
+    fn test_thesis_title_block() {
+        let input = "+++\ntitle = \"On Graphs\"\nauthor = \"Ada\"\n\
+                     institution = \"Uni A\"\ndepartment = \"Mathematics\"\n\
+                     advisor = \"Prof. Bob\"\n+++\n\nBody.";
+        let doc = parse(input).unwrap();
+        assert_eq!(doc.metadata.institution.as_deref(), Some("Uni A"));
+        assert_eq!(doc.metadata.advisor.as_deref(), Some("Prof. Bob"));
+
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            standalone: true,
+            title_block: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            "<p class=\"mdadepartment\">Mathematics</p>\n\
+             <p class=\"mdainstitution\">Uni A</p>\n\
+             <p class=\"mdaadvisor\">Advisor: Prof. Bob</p>\n</header>"
+        ));
+    }
+
+    #[test]
+    fn test_table_colgroup_widths() {
+        let input = "| Name | Description | Notes |\n|------|------------|--|\n| a | b | c |";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains("<colgroup>"));
+        assert!(html.contains(r#"<col style="width: 30.0%">"#));
+        assert!(html.contains(r#"<col style="width: 60.0%">"#));
+        assert!(html.contains(r#"<col style="width: 10.0%">"#));
+    }
+
+    #[test]
+    fn test_unlinked_references() {
+        let input = "# Intro {#sec:intro}\n\nSee @sec:intro and [@smith2020].";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            link_references: false,
+            include_toc: false,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<span class="mdaref">Section 1</span>"#));
+        assert!(!html.contains("href=\"#sec-intro\""));
+        assert!(!html.contains("href=\"#bib-smith2020\""));
+    }
+
+    #[test]
+    fn test_always_wrap_list_items() {
+        let doc = parse("- Alpha\n- Beta").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains("<li>Alpha</li>"));
+
+        let config = HtmlConfig {
+            always_wrap_list_items: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains("<li><p>Alpha</p>\n</li>"));
+    }
+
+    #[test]
+    fn test_year_suffix_disambiguation() {
+        let input = "See [@first] and [@second].";
+        let resolved = resolve_with_bibliography(
+            input,
+            "@article{first, author = {Smith, John}, title = {One}, year = {2020}}\n\
+             @article{second, author = {Smith, John}, title = {Two}, year = {2020}}\n",
+        );
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains("Smith, 2020a"));
+        assert!(html.contains("Smith, 2020b"));
+        assert!(html.contains("(2020a)"));
+        assert!(html.contains("(2020b)"));
+    }
+
+    #[test]
+    fn test_bibliography_backlinks() {
+        let input = "See [@knuth].\n\nAgain @knuth.";
+        let resolved = resolve_with_bibliography(
+            input,
+            "@article{knuth, author = {Knuth, Donald}, title = {TeX}, year = {1984}}\n",
+        );
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(!html.contains("cite-1"));
+
+        let config = HtmlConfig {
+            bibliography_backlinks: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(r#"<span id="cite-1" class="mdacitation">"#));
+        assert!(html.contains(r#"<span id="cite-2" class="mdacitation">"#));
+        assert!(html.contains(r##"<a href="#cite-1" class="mdacitation-back"><sup>1</sup></a>"##));
+        assert!(html.contains(r##"<a href="#cite-2" class="mdacitation-back"><sup>2</sup></a>"##));
+    }
+
+    #[test]
+    fn test_citation_hook() {
+        let doc = parse("See [@smith2020] and [@jones2021].").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            citation_hook: Some(CitationHook::new(|cite, entries| {
+                assert!(entries.is_empty());
+                if cite.keys[0] == "smith2020" {
+                    Some(format!("&#8224;{}", cite.keys[0]))
+                } else {
+                    None
+                }
+            })),
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<span class="mdacitation">&#8224;smith2020</span>"#));
+        assert!(html.contains(r##"<a href="#bib-jones2021""##));
+    }
+
+    #[test]
+    fn test_numeric_citation_ranges() {
+        let bib: String = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|key| format!("@misc{{{key}, author = {{Author, {key}}}, year = {{2000}}}}\n"))
+            .collect();
+        let config = ResolveConfig {
+            bibliography: Some(std::sync::Arc::new(
+                crate::bibtex::parse_bibtex(&bib).unwrap(),
+            )),
+            citation_style: Some(CitationFormat::Numeric),
+            ..Default::default()
+        };
+        let input = "First [@a; @b], then [@e; @c; @d; @b; @missing].";
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        // Numbered by first citation: a, b, e, c, d
+        let link = |key: &str, n: &str| format!(r##"<a href="#bib-{key}">{n}</a>"##);
+        assert!(html.contains(&format!("[{}, {}]", link("a", "1"), link("b", "2"))));
+        assert!(html.contains(&format!(
+            "[{}–{}, {}]",
+            link("b", "2"),
+            link("d", "5"),
+            link("missing", "missing")
+        )));
+        assert!(html.contains(r#"<li id="bib-c"><span class="mdabib-number">[4]</span> "#));
+    }
+
+    #[test]
+    fn test_appendix_headings() {
+        let input = "# Results {#sec:results}\n\n\\appendix\n\n# Proofs {#sec:proofs}\n\n\
+                     ## Lemmas {#sec:lemmas}\n";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<h1 class="mdaappendix-title">Appendices</h1>"#));
+        assert!(html.contains(r#"<span class="mdasection-number">Appendix A</span> Proofs"#));
+        assert!(html.contains(r#"<span class="mdasection-number">A.1</span> Lemmas"#));
+        assert!(html.contains(r#"<span class="mdasection-number">1</span> Results"#));
+
+        let resolve_config = ResolveConfig {
+            appendix_label: "Anhang".to_string(),
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &resolve_config).unwrap();
+        let config = HtmlConfig {
+            appendix_title: Some("Anhänge".to_string()),
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(r#"<h1 class="mdaappendix-title">Anhänge</h1>"#));
+        assert!(html.contains(r#"<span class="mdasection-number">Anhang A</span> Proofs"#));
+
+        let config = HtmlConfig {
+            appendix_title: None,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(!html.contains("appendix-title"));
+    }
+
+    #[test]
+    fn test_bibliography_editors() {
+        let chapter = BibEntry {
+            authors: vec!["Jane Doe".to_string()],
+            editors: vec!["John Smith".to_string()],
+            title: Some("A Chapter".to_string()),
+            booktitle: Some("The Handbook".to_string()),
+            edition: Some("2nd".to_string()),
+            note: Some("In press".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_bibliography_entry(&chapter),
+            "Jane Doe. <em>A Chapter</em>. 2nd ed. \
+             In John Smith (Ed.), <em>The Handbook</em>. In press."
+        );
+
+        let volume = BibEntry {
+            editors: vec!["John Smith".to_string(), "Ann Lee".to_string()],
+            title: Some("The Handbook".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_bibliography_entry(&volume),
+            "John Smith, Ann Lee (Eds.). <em>The Handbook</em>."
+        );
+
+        // Blank fields are skipped and every field is escaped
+        let book = BibEntry {
+            title: Some("Tables".to_string()),
+            publisher: Some("Smith & <Sons>".to_string()),
+            note: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_bibliography_entry(&book),
+            "<em>Tables</em>. Smith &amp; &lt;Sons&gt;."
+        );
+    }
+
+    #[test]
+    fn test_bibliography_sort() {
+        let bib = "@book{zeta, author = {Adams, Zoe}, year = {2001}}\n\
+                   @book{alpha, author = {Young, Amy}, year = {1999}}\n\
+                   @book{mid, author = {Zoe Adams}, year = {2001}}\n\
+                   @book{old, author = {Adams, Zoe}, year = {1990}}";
+        let config = ResolveConfig {
+            bibliography: Some(std::sync::Arc::new(
+                crate::bibtex::parse_bibtex(bib).unwrap(),
+            )),
+            ..Default::default()
+        };
+        let doc = parse("[@alpha] [@zeta] [@mid] [@old]").unwrap();
+        let resolved = resolve(doc, &config).unwrap();
+        let order = |sort: BibliographySort| {
+            let config = HtmlConfig {
+                bibliography_sort: sort,
+                ..Default::default()
+            };
+            let html = render_html(&resolved, &config).unwrap();
+            let mut ids: Vec<(usize, &str)> = ["alpha", "zeta", "mid", "old"]
+                .into_iter()
+                .map(|key| (html.find(&format!(r#"<li id="bib-{key}">"#)).unwrap(), key))
+                .collect();
+            ids.sort();
+            ids.into_iter().map(|(_, key)| key).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            order(BibliographySort::Appearance),
+            ["alpha", "zeta", "mid", "old"]
+        );
+        // The two Adams 2001 entries are told apart as 2001a and 2001b
+        assert_eq!(
+            order(BibliographySort::AuthorYear),
+            ["old", "zeta", "mid", "alpha"]
+        );
+        assert_eq!(
+            order(BibliographySort::Key),
+            ["alpha", "mid", "old", "zeta"]
+        );
+
+        // The render index reports positions in the sorted list
+        let config = HtmlConfig {
+            bibliography_sort: BibliographySort::Key,
+            ..Default::default()
+        };
+        let (_, index) = render_html_with_index(&resolved, &config).unwrap();
+        let positions: Vec<(&str, Option<usize>)> = index
+            .citations
+            .iter()
+            .map(|c| (c.key.as_str(), c.position))
+            .collect();
+        assert_eq!(
+            positions,
+            [
+                ("alpha", Some(1)),
+                ("zeta", Some(4)),
+                ("mid", Some(2)),
+                ("old", Some(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_citation_prefix() {
+        let bib = "@book{knuth1984, author = {Knuth, Donald}, year = {1984}}";
+        let config = ResolveConfig {
+            bibliography: Some(std::sync::Arc::new(
+                crate::bibtex::parse_bibtex(bib).unwrap(),
+            )),
+            ..Default::default()
+        };
+        let doc = parse("Proved [see @knuth1984, p. 42].").unwrap();
+        let resolved = resolve(doc, &config).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(
+            html.contains(r##"[see <a href="#bib-knuth1984">Knuth, 1984</a>, p. 42]"##),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn test_citation_styles_reach_renderer() {
+        let doc = parse("[@a] [-@b] @c @d-").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            citation_hook: Some(CitationHook::new(|cite, _| {
+                Some(format!("{}:{:?}", cite.keys[0], cite.style))
+            })),
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        for expected in ["a:Parenthetical", "b:YearOnly", "c:Textual", "d:AuthorOnly"] {
+            assert!(html.contains(expected), "missing {}", expected);
+        }
+    }
+
+    #[test]
+    fn test_si_quantities() {
+        let doc = parse(r"A body falls at \SI{9.8}{m/s^2} for \num{12345} m.").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains("falls at 9.8\u{202F}m/s² for 12\u{202F}345 m."));
+    }
+
+    #[test]
+    fn test_unresolved_reference_styles() {
+        let doc = parse("See @fig:missing.").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let render = |style| {
+            let config = HtmlConfig {
+                unresolved_references: style,
+                ..Default::default()
+            };
+            render_html(&resolved, &config).unwrap()
+        };
+
+        assert!(render(UnresolvedReferenceStyle::Marker).contains(">??fig:missing</a>"));
+        assert!(render(UnresolvedReferenceStyle::Label).contains(">fig:missing</a>"));
+        assert!(render(UnresolvedReferenceStyle::Source).contains(">@fig:missing</a>"));
+        assert!(render(UnresolvedReferenceStyle::Span).contains(
+            r#"<span class="mdaunresolved" title="Unresolved reference: fig:missing">fig:missing</span>"#
+        ));
+    }
+
+    #[test]
+    fn test_reference_footnotes_numbered_with_inline() {
+        let input = "First[^a], then^[*inline*], then[^b].\n\n\
+                     [^a]: Note A.\n\n\
+                     [^b]: Note B,\n    continued.\n\n    Second paragraph.\n\nAfter.";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        assert_eq!(resolved.document.blocks.len(), 2);
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        for n in 1..=3 {
+            assert!(html.contains(&format!(r##"<a href="#fn-{n}">[{n}]</a>"##)));
+        }
+        let notes = &html[html.find("<ol>").unwrap()..];
+        let a = notes.find(r#"<li id="fn-1">Note A."#).unwrap();
+        let inline = notes.find(r#"<li id="fn-2"><em>inline</em>"#).unwrap();
+        let b = notes
+            .find("<li id=\"fn-3\">Note B,\ncontinued.\nSecond paragraph.")
+            .unwrap();
+        assert!(a < inline && inline < b);
+    }
+
+    #[test]
+    fn test_footnote_definitions_resolved() {
+        let input =
+            "Note[^fn-1] and^[inline].\n\n[^fn-1]: A \"quoted\" note, see @sec:a.\n\n# A {#sec:a}";
+        let config = ResolveConfig {
+            smart_quotes: true,
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+
+        // Definition ids and inline notes do not share keys
+        assert_eq!(resolved.footnotes.len(), 2);
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains("A “quoted” note, see <a"), "{html}");
+        assert!(html.contains("Section 1</a>"));
+        assert!(html.contains(r#"<li id="fn-2">inline"#));
+    }
+
+    #[test]
+    fn test_adjacent_footnote_references() {
+        let doc = parse("text[^1][^2] and^[inline].").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r##"<sup id="fnref-1" class="mdafootnote-ref"><a href="#fn-1">[1]</a></sup><sup id="fnref-2""##));
+
+        let config = HtmlConfig {
+            join_adjacent_footnotes: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            r##"text<sup class="mdafootnote-ref">[<a id="fnref-1" href="#fn-1">1</a>, <a id="fnref-2" href="#fn-2">2</a>]</sup> and"##
+        ));
+        assert!(html.contains(r#"<sup id="fnref-3" class="mdafootnote-ref">"#));
+    }
+
+    #[test]
+    fn test_front_matter_presentation_defaults() {
+        let input = "+++\ncitation_style = \"numeric\"\ntoc_depth = 1\n+++\n\n\
+                     [[toc]]\n\n# Intro {#sec:intro}\n\n## Detail {#sec:detail}\n\n\
+                     See [@lamport; @knuth] and @knuth.";
+        let resolved = resolve_with_bibliography(
+            input,
+            "@article{knuth, author = {Knuth, Donald}, title = {TeX}, year = {1984}}\n\
+             @article{lamport, author = {Lamport, Leslie}, title = {LaTeX}, year = {1994}}\n",
+        );
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r##"[<a href="#bib-lamport">1</a>, <a href="#bib-knuth">2</a>]"##));
+        assert!(html.contains(r##"Knuth [<a href="#bib-knuth">2</a>]"##));
+        assert!(!html.contains(r##"<a href="#sec-detail">"##));
+
+        // Explicit configuration overrides the front matter
+        let config = HtmlConfig {
+            citation_style: Some(CitationFormat::AuthorYear),
+            toc_depth: Some(2),
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains("Knuth, 1984"));
+        assert!(html.contains(r##"<a href="#sec-detail">"##));
+    }
+
+    #[test]
+    fn test_per_key_citation_locators() {
+        let input = "See [@knuth1984, p. 42; @lamport1994, p. 10].";
+        let resolved = resolve_with_bibliography(
+            input,
+            "@book{knuth1984, author = {Knuth, Donald}, title = {TeX}, year = {1984}}\n\
+             @book{lamport1994, author = {Lamport, Leslie}, title = {LaTeX}, year = {1994}}\n",
+        );
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(
+            r##"[<a href="#bib-knuth1984">Knuth, 1984</a>, p. 42; <a href="#bib-lamport1994">Lamport, 1994</a>, p. 10]"##
+        ));
+
+        let config = HtmlConfig {
+            citation_style: Some(CitationFormat::Numeric),
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            r##"[<a href="#bib-knuth1984">1</a>, p. 42; <a href="#bib-lamport1994">2</a>, p. 10]"##
+        ));
+    }
+
+    #[test]
+    fn test_semantic_sections() {
+        let input = "Preamble.\n\n# One\n\nIntro.\n\n## Detail\n\nMore.\n\n# Two\n\nEnd.";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(!html.contains("<section>"));
+
+        let config = HtmlConfig {
+            semantic_sections: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert_eq!(
+            html,
+            "<p>Preamble.</p>\n\
+             <section>\n<h1>One</h1>\n<p>Intro.</p>\n\
+             <section>\n<h2>Detail</h2>\n<p>More.</p>\n</section>\n</section>\n\
+             <section>\n<h1>Two</h1>\n<p>End.</p>\n</section>\n"
+        );
+    }
+
+    #[test]
+    fn test_collapsible_toc() {
+        let input = "[[toc]]\n\n# One\n\n## Detail\n\n### Deeper\n\n## More\n\n# Two";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            collapsible_toc: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            "<ul>\n\
+             <li><details><summary>One</summary>\n<ul>\n\
+             <li>Detail\n<ul>\n<li>Deeper</li>\n</ul>\n</li>\n\
+             <li>More</li>\n\
+             </ul>\n</details></li>\n\
+             <li>Two</li>\n\
+             </ul>\n</nav>"
+        ));
+    }
+
+    #[test]
+    fn test_code_listing() {
+        let input =
+            "```rust\nfn main() {}\n```\nListing: Entry point {#lst:main}\n\nSee @lst:main.";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        assert_eq!(resolved.env_numbers.get("lst:main"), Some(&"1".to_string()));
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<figure class="mdalisting mdalisting" id="lst-main">"#));
+        assert!(html.contains("<figcaption><strong>Listing 1:</strong> Entry point</figcaption>"));
+        assert!(html.contains(r##"<a href="#lst-main" class="mdaref">Listing 1</a>"##));
+    }
+
+    #[test]
+    fn test_lazy_responsive_images() {
+        let input = r#"![Plot](plot.png){srcset="plot-2x.png 2x" sizes="50vw" width=3}"#;
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert_eq!(
+            html,
+            "<p><img src=\"plot.png\" alt=\"Plot\" srcset=\"plot-2x.png 2x\" sizes=\"50vw\"></p>\n"
+        );
+
+        let config = HtmlConfig {
+            lazy_images: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(r#"sizes="50vw" loading="lazy" decoding="async">"#));
+    }
+
+    #[test]
+    fn test_paragraph_attributes() {
+        let resolved = resolve(
+            parse("An opening paragraph. {#sec:intro .lead}").unwrap(),
+            &ResolveConfig::default(),
+        )
+        .unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert_eq!(
+            html,
+            "<p id=\"sec-intro\" class=\"mdalead\">An opening paragraph.</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_rule_inside_figure() {
+        let input =
+            "::: figure {#fig:panels}\n![A](a.png)\n---\n![B](b.png)\n\nTwo panels.\n:::\n\n---";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(
+            "<div class=\"mdaenv-content\"><p><img src=\"a.png\" alt=\"A\"></p>\n\
+             <hr class=\"mdaenv-rule\">\n\
+             <p><img src=\"b.png\" alt=\"B\"></p>\n</div>\n\
+             <figcaption><strong>Figure 1:</strong> Two panels.</figcaption>"
+        ));
+        assert!(html.ends_with("</figure>\n<hr>\n"));
+    }
+
+    #[test]
+    fn test_keywords() {
+        let input = "+++\nkeywords = [\"graphs\", \"coloring\"]\n+++\n\n::: abstract\nWe color graphs.\n:::";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+
+        let config = HtmlConfig {
+            standalone: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains("<meta name=\"keywords\" content=\"graphs, coloring\">\n"));
+        assert!(!html.contains("Keywords:"));
+
+        let config = HtmlConfig {
+            show_keywords: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            "<p>We color graphs.</p>\n\
+             <p class=\"mdakeywords\"><strong>Keywords:</strong> graphs, coloring</p>\n</div>"
+        ));
+    }
+
+    #[test]
+    fn test_equation_number_side() {
+        let doc = parse("$$\nE = mc^2\n$$ {#eq:energy}").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"\]</div><span class="mdaequation-number">(1)</span>"#));
+
+        let config = HtmlConfig {
+            equation_number_side: EquationNumberSide::Left,
+            standalone: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html
+            .contains(r#"<span class="mdaequation-number">(1)</span><div class="math display">"#));
+        // Per-line numbers of align blocks follow the same side
+        assert!(html.contains("leqno: true"));
+    }
+
+    #[test]
+    fn test_footnotes_in_tables_and_description_lists() {
+        let input = "| Name | Value^[Measured at noon.] |\n| --- | --- |\n| a | 1 |\n\n\
+                     Term^[On the term.]\n: Defined^[On the definition.].";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        assert_eq!(resolved.footnotes.len(), 3);
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<li id="fn-1">Measured at noon."#));
+        assert!(html.contains(r#"<li id="fn-2">On the term."#));
+        assert!(html.contains(r#"<li id="fn-3">On the definition."#));
+        assert!(html.contains(
+            r##"<sup id="fnref-1" class="mdafootnote-ref"><a href="#fn-1">[1]</a></sup>"##
+        ));
+    }
+
+    #[test]
+    fn test_footnotes_and_references_in_sub_and_superscript() {
+        let input = "H~2^[water]~O later^[second], see ~@eq:a~ and x^n,@eq:a^.\n\n\
+                     $$ a = b $$ {#eq:a}";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        assert_eq!(resolved.footnotes.len(), 2);
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<li id="fn-1">water"#));
+        assert!(html.contains(r#"<li id="fn-2">second"#));
+        assert!(html.contains(r##"<sub><a href="#eq-a" class="mdaref">(1)</a></sub>"##));
+        assert!(html.contains(r##"<sup>n,<a href="#eq-a" class="mdaref">(1)</a></sup>"##));
+    }
+
+    #[test]
+    fn test_footnotes_and_references_in_small_caps() {
+        let input = "[see @eq:a]{.smallcaps} and [sc]caps^[In caps.][/sc]\n\n\
+                     $$ a = b $$ {#eq:a}";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        assert_eq!(resolved.footnotes.len(), 1);
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<li id="fn-1">In caps."#));
+        assert!(html.contains(
+            r##"class="mdasmallcaps">see <a href="#eq-a" class="mdaref">(1)</a></span>"##
+        ));
+    }
+
+    #[test]
+    fn test_environment_permalinks() {
+        let doc = parse("::: theorem {#thm:main}\nStatement.\n:::").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(!html.contains("mdapermalink"));
+
+        let config = HtmlConfig {
+            permalinks: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            r##"<a href="#thm-main" class="mdapermalink"><strong>Theorem</strong> 1</a>."##
+        ));
+    }
+
+    #[test]
+    fn test_code_overflow_classes() {
+        let doc = parse("```rust\nfn main() {}\n```").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<pre><code class="language-rust">"#));
+
+        let config = HtmlConfig {
+            code_overflow: CodeOverflow::Scroll,
+            standalone: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(r#"<pre class="mdacode-scroll"><code class="language-rust">"#));
+        assert!(html.contains(".mdacode-scroll { overflow-x: auto;"));
+        assert!(!html.contains(".mdacode-wrap"));
+
+        let config = HtmlConfig {
+            code_overflow: CodeOverflow::Wrap,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(r#"<pre class="mdacode-wrap">"#));
+    }
+
+    #[test]
+    fn test_equation_as_figure() {
+        let input = "$$\nE = mc^2\n$$ {#eq:energy}\n\nSee @eq:energy.";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            equation_as_figure: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<figure class="mdaequation" id="eq-energy">"#));
+        assert!(html.contains("<figcaption>(1)</figcaption>\n</figure>"));
+        assert!(html.contains(r##"<a href="#eq-energy" class="mdaref">(1)</a>"##));
+        assert!(!html.contains("mdaequation-number"));
+    }
+
+    #[test]
+    fn test_rtl_direction() {
+        let doc =
+            parse("+++\nlang = \"ar\"\n+++\n\n# مقدمة\n\nنص [Hello]{lang=en dir=ltr}.").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            standalone: true,
+            direction: TextDirection::Rtl,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<html lang="ar" dir="rtl">"#));
+        assert!(html.contains(r#"<span lang="en" dir="ltr">Hello</span>"#));
+        assert!(html.contains(r#"<article class="mdlatex-document" dir="rtl">"#));
+
+        let ltr = render_html(
+            &resolved,
+            &HtmlConfig {
+                standalone: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(ltr.contains(r#"<html lang="ar">"#));
+        assert!(ltr.contains(r#"<article class="mdlatex-document">"#));
+    }
+
+    #[test]
+    fn test_environment_title() {
+        let input =
+            "::: {.theorem #thm:py title=\"Pythagoras\"}\na^2 + b^2 = c^2\n:::\n\nBy @thm:py.";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains("<strong>Theorem</strong> 1 (Pythagoras).</span>"));
+        assert!(html.contains(r##"<a href="#thm-py" class="mdaref">Theorem 1</a>"##));
+    }
+
+    #[test]
+    fn test_reference_previews() {
+        let input = "::: theorem {#thm:main}\nEvery $n > 1$ has a \"prime\" factor. Proof follows.\n:::\n\n\
+                     $$a < b$$ {#eq:ineq}\n\nBy @thm:main and @eq:ineq.";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            reference_previews: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(
+            r##"<a href="#thm-main" class="mdaref" title="Every n &gt; 1 has a &quot;prime&quot; factor.">Theorem 1</a>"##
+        ));
+        assert!(html.contains(r##"<a href="#eq-ineq" class="mdaref" title="a &lt; b">(1)</a>"##));
+
+        let plain = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(plain.contains(r##"<a href="#thm-main" class="mdaref">Theorem 1</a>"##));
+    }
+
+    #[test]
+    fn test_table_parity_classes() {
+        let input = "| A |\n|---|\n| 1 |\n| 2 |\n| 3 |";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            table_classes: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(html.contains(r#"<table class="mdatable mdatable-striped">"#));
+        assert_eq!(html.matches(r#"<tr class="mdarow-odd">"#).count(), 2);
+        assert_eq!(html.matches(r#"<tr class="mdarow-even">"#).count(), 1);
+
+        let plain = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(!plain.contains("mdarow-"));
+    }
+
+    #[test]
+    fn test_image_formatted_alt() {
+        let doc = parse("![An *important* plot of $x^2$](plot.png)").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains(r#"<img src="plot.png" alt="An important plot of x^2">"#));
+    }
+
+    #[test]
+    fn test_render_kbd_and_abbreviation() {
+        let input = "Press [[Ctrl+C]] to copy HTML.\n\n*[HTML]: HyperText Markup Language";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains("<kbd>Ctrl+C</kbd>"));
+        assert!(html.contains(r#"<abbr title="HyperText Markup Language">HTML</abbr>"#));
+        assert!(!html.contains("*[HTML]"));
+    }
+
+    #[test]
+    fn test_sidenotes() {
+        let input = "A claim^[The supporting note.] in the text.";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            footnote_style: FootnoteStyle::Sidenotes,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+
+        assert!(
+            html.contains(r#"<span class="mdasidenote"><sup>1</sup> The supporting note.</span>"#)
+        );
+        assert!(html.contains(r#"<input type="checkbox" id="sn-1""#));
+        assert!(!html.contains("mdafootnotes"));
+    }
+
+    #[test]
+    fn test_tilde_nbsp() {
+        let input = "See Fig.~1 and ~~del~~.";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let config = HtmlConfig {
+            tilde_as_nbsp: true,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains("Fig.&nbsp;1"));
+        assert!(html.contains("<del>del</del>"));
+
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains("Fig.~1"));
+    }
+
+    #[test]
+    fn test_math_fence_equation() {
+        let input = "```{.math #eq:energy}\nE = mc^2\n```\n\nSee @eq:energy.";
+        let doc = parse(input).unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert!(html.contains(r#"<div class="mdaequation" id="eq-energy">"#));
+        assert!(html.contains(r#"<span class="mdaequation-number">(1)</span>"#));
+        assert!(html.contains(r##"<a href="#eq-energy" class="mdaref">(1)</a>"##));
+        assert!(!html.contains("<pre>"));
+    }
+
+    #[test]
+    fn test_render_index() {
+        let doc = parse("# Introduction {#sec:intro}\n\nSee @sec:intro and @sec:missing.").unwrap();
+        let resolved = resolve(doc, &ResolveConfig::default()).unwrap();
+        let (html, index) = render_html_with_index(&resolved, &HtmlConfig::default()).unwrap();
+
+        assert_eq!(
+            html,
+            render_html(&resolved, &HtmlConfig::default()).unwrap()
+        );
+        assert!(index.anchors.contains(&"sec-intro".to_string()));
+        assert_eq!(
+            index.targets,
+            vec![IndexTarget {
+                label: "sec:intro".to_string(),
+                id: "sec-intro".to_string(),
+                display: "Section 1".to_string(),
+            }]
+        );
+        assert_eq!(
+            index.references,
+            vec![
+                IndexReference {
+                    label: "sec:intro".to_string(),
+                    href: "#sec-intro".to_string(),
+                    resolved: true,
+                },
+                IndexReference {
+                    label: "sec:missing".to_string(),
+                    href: "#sec-missing".to_string(),
+                    resolved: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toc_title_and_min_depth() {
+        let input = "[[toc]]\n\n# Paper Title\n\n## Methods\n\n### Setup\n\n## Results";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+
+        let config = HtmlConfig {
+            toc_title: Some("Contents & Outline".to_string()),
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains("<h2>Contents &amp; Outline</h2>\n<ul>\n<ul>\n<li>Paper Title"));
+
+        let config = HtmlConfig {
+            toc_title: None,
+            toc_min_depth: 2,
+            ..Default::default()
+        };
+        let html = render_html(&resolved, &config).unwrap();
+        assert!(html.contains(
+            "<nav class=\"mdatoc\"><ul>\n<ul>\n\
+             <li>Methods</li>\n<ul>\n<li>Setup</li>\n</ul>\n\
+             <li>Results</li>\n</ul>\n</ul>\n</nav>"
+        ));
+    }
+
+    #[test]
+    fn test_proof_for_theorem() {
+        let input = "::: theorem {#thm:main}\nAll is well.\n:::\n\n\
+                     ::: proof {for=thm:main}\nClearly.\n:::\n\n\
+                     ::: proof {for=thm:missing}\nAlso clear.\n:::";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        let html = render_html(&resolved, &HtmlConfig::default()).unwrap();
+        assert!(html.contains(
+            r##"<span class="mdaenv-header"><em>Proof of <a href="#thm-main" class="mdaref">Theorem 1</a>.</em></span>"##
+        ));
+        assert!(html.contains(r#"<span class="mdaenv-header"><em>Proof.</em></span>"#));
+    }
 }
@@ -6,17 +6,30 @@ use crate::error::Result;
 /// Renderer that outputs raw LaTeX for client-side rendering.
 pub struct KaTeXRenderer {
     use_mathjax: bool,
+    left_tags: bool,
 }
 
 impl KaTeXRenderer {
     /// Create a new KaTeX renderer.
     pub fn new() -> Self {
-        Self { use_mathjax: false }
+        Self {
+            use_mathjax: false,
+            left_tags: false,
+        }
     }
 
     /// Create a renderer configured for MathJax.
     pub fn new_mathjax() -> Self {
-        Self { use_mathjax: true }
+        Self {
+            use_mathjax: true,
+            left_tags: false,
+        }
+    }
+
+    /// Whether the client-side engine places `\tag` numbers on the left.
+    pub fn with_left_tags(mut self, left_tags: bool) -> Self {
+        self.left_tags = left_tags;
+        self
     }
 }
 
@@ -49,11 +62,15 @@ impl MathRenderer for KaTeXRenderer {
     }
 
     fn head_content(&self) -> Option<String> {
-        if self.use_mathjax {
-            Some(MATHJAX_HEAD.to_string())
-        } else {
-            Some(KATEX_HEAD.to_string())
-        }
+        let head = match (self.use_mathjax, self.left_tags) {
+            (true, false) => MATHJAX_HEAD.to_string(),
+            (true, true) => MATHJAX_HEAD.replace("tex: {", "tex: {\n        tagSide: 'left',"),
+            (false, false) => KATEX_HEAD.to_string(),
+            (false, true) => {
+                KATEX_HEAD.replace("delimiters: [", "leqno: true,\n        delimiters: [")
+            }
+        };
+        Some(head)
     }
 }
 
@@ -102,6 +119,20 @@ mod tests {
         assert!(result.contains("math display"));
     }
 
+    #[test]
+    fn test_left_tags_head() {
+        let head = KaTeXRenderer::new().with_left_tags(true).head_content();
+        assert!(head.unwrap().contains("leqno: true,"));
+        let head = KaTeXRenderer::new_mathjax()
+            .with_left_tags(true)
+            .head_content();
+        assert!(head.unwrap().contains("tagSide: 'left',"));
+        assert!(!KaTeXRenderer::new()
+            .head_content()
+            .unwrap()
+            .contains("leqno"));
+    }
+
     #[test]
     fn test_escaping() {
         let renderer = KaTeXRenderer::new();
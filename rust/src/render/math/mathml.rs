@@ -7,16 +7,37 @@ use crate::error::Result;
 pub struct MathMLRenderer {
     #[cfg(feature = "mathml")]
     _phantom: std::marker::PhantomData<()>,
+    #[cfg_attr(not(feature = "mathml"), allow(dead_code))]
+    validate: bool,
 }
 
 impl MathMLRenderer {
-    /// Create a new MathML renderer.
+    /// Create a new MathML renderer that validates its output.
     pub fn new() -> Self {
         Self {
             #[cfg(feature = "mathml")]
             _phantom: std::marker::PhantomData,
+            validate: true,
         }
     }
+
+    /// Whether converted MathML is checked for well-formedness, falling back
+    /// to escaped LaTeX when it is malformed (default: true).
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Converted MathML, or `None` if conversion failed or (when
+    /// validating) produced malformed markup.
+    #[cfg(feature = "mathml")]
+    fn convert(&self, latex: &str, style: latex2mathml::DisplayStyle) -> Option<String> {
+        let mathml = escape_text(&latex2mathml::latex_to_mathml(latex, style).ok()?);
+        if self.validate && !is_well_formed_mathml(&mathml) {
+            return None;
+        }
+        Some(mathml)
+    }
 }
 
 impl Default for MathMLRenderer {
@@ -29,9 +50,9 @@ impl MathRenderer for MathMLRenderer {
     fn render_inline(&self, latex: &str) -> Result<String> {
         #[cfg(feature = "mathml")]
         {
-            match latex2mathml::latex_to_mathml(latex, latex2mathml::DisplayStyle::Inline) {
-                Ok(mathml) => Ok(mathml),
-                Err(_) => {
+            match self.convert(latex, latex2mathml::DisplayStyle::Inline) {
+                Some(mathml) => Ok(mathml),
+                None => {
                     // Fallback to escaped LaTeX
                     Ok(format!(
                         r#"<span class="math inline math-error">{}</span>"#,
@@ -54,9 +75,9 @@ impl MathRenderer for MathMLRenderer {
     fn render_display(&self, latex: &str) -> Result<String> {
         #[cfg(feature = "mathml")]
         {
-            match latex2mathml::latex_to_mathml(latex, latex2mathml::DisplayStyle::Block) {
-                Ok(mathml) => Ok(format!(r#"<div class="math display">{}</div>"#, mathml)),
-                Err(_) => {
+            match self.convert(latex, latex2mathml::DisplayStyle::Block) {
+                Some(mathml) => Ok(format!(r#"<div class="math display">{}</div>"#, mathml)),
+                None => {
                     // Fallback to escaped LaTeX
                     Ok(format!(
                         r#"<div class="math display math-error">{}</div>"#,
@@ -82,6 +103,93 @@ impl MathRenderer for MathMLRenderer {
     }
 }
 
+/// Escape the `<`, `>` and `&` characters latex2mathml leaves bare in text
+/// (`<mo><</mo>` for `a < b`), keeping tags and entities intact.
+#[cfg_attr(not(feature = "mathml"), allow(dead_code))]
+fn escape_text(markup: &str) -> String {
+    let mut result = String::with_capacity(markup.len());
+    let mut in_tag = false;
+    for (i, c) in markup.char_indices() {
+        let rest = &markup[i + 1..];
+        match c {
+            '<' if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') => {
+                in_tag = true;
+                result.push(c);
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                result.push(c);
+            }
+            '<' if !in_tag => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '&' if !in_tag && !starts_with_entity(rest) => result.push_str("&amp;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Whether `text` (just after a `&`) is the rest of an entity (`lt;`).
+fn starts_with_entity(text: &str) -> bool {
+    text.find(';').is_some_and(|end| {
+        end > 0
+            && text[..end]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '#')
+    })
+}
+
+/// Whether `markup` is a single well-formed `<math>` element: tags nest and
+/// close properly, text holds no stray `<` or bare `&`, and the converter
+/// reported no parse errors.
+#[cfg_attr(not(feature = "mathml"), allow(dead_code))]
+fn is_well_formed_mathml(markup: &str) -> bool {
+    if !markup.starts_with("<math") || markup.contains("[PARSE ERROR") {
+        return false;
+    }
+
+    let mut open = Vec::new();
+    let mut rest = markup;
+    while let Some(start) = rest.find(['<', '&']) {
+        let after = &rest[start..];
+
+        if let Some(entity) = after.strip_prefix('&') {
+            let Some(end) = entity.find(';').filter(|_| starts_with_entity(entity)) else {
+                return false;
+            };
+            rest = &entity[end + 1..];
+            continue;
+        }
+
+        let Some(end) = after.find('>') else {
+            return false;
+        };
+        let tag = &after[1..end];
+        if let Some(name) = tag.strip_prefix('/') {
+            if open.pop() != Some(name.trim()) {
+                return false;
+            }
+        } else {
+            let name = tag.split_whitespace().next().unwrap_or("");
+            let name = name.trim_end_matches('/');
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return false;
+            }
+            if !tag.ends_with('/') {
+                open.push(name);
+            }
+        }
+
+        rest = &after[end + 1..];
+        if open.is_empty() {
+            // The <math> element is closed; nothing may follow it
+            return rest.trim().is_empty();
+        }
+    }
+
+    false
+}
+
 fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -95,6 +203,14 @@ const MATHML_STYLES: &str = r#"<style>
 }
 math {
     font-size: 1.1em;
+    font-family: "STIX Two Math", "Cambria Math", "Latin Modern Math", math;
+}
+math[display="block"] {
+    display: block math;
+    margin: 0.5em 0;
+}
+math mo {
+    padding-inline: 0.1em;
 }
 </style>"#;
 
@@ -109,4 +225,49 @@ mod tests {
         // Should produce some output regardless of feature
         assert!(!result.is_empty());
     }
+
+    #[test]
+    fn test_well_formed_mathml() {
+        assert!(is_well_formed_mathml(
+            r#"<math display="inline"><mi>a</mi><mo>&lt;</mo><mspace width="1em"/></math>"#
+        ));
+        // Unescaped `<` in an operator, as latex2mathml emits for `a < b`
+        assert!(!is_well_formed_mathml(
+            "<math><mi>a</mi><mo><</mo><mi>b</mi></math>"
+        ));
+        assert!(!is_well_formed_mathml("<math><mi>a</mo></math>"));
+        assert!(!is_well_formed_mathml(
+            "<math><mtext>[PARSE ERROR: Ampersand]</mtext></math>"
+        ));
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            escape_text(
+                r#"<math display="inline"><mi>a</mi><mo><</mo><mo>></mo><mo>&lt;</mo></math>"#
+            ),
+            r#"<math display="inline"><mi>a</mi><mo>&lt;</mo><mo>&gt;</mo><mo>&lt;</mo></math>"#
+        );
+        assert_eq!(escape_text("<mtext>R&D</mtext>"), "<mtext>R&amp;D</mtext>");
+    }
+
+    #[cfg(feature = "mathml")]
+    #[test]
+    fn test_malformed_output_falls_back() {
+        let renderer = MathMLRenderer::new();
+        assert!(renderer.render_inline("x^2").unwrap().starts_with("<math"));
+        let result = renderer.render_inline("a < b").unwrap();
+        assert!(result.contains("<mo>&lt;</mo>"), "{result}");
+
+        // Converter parse errors still fall back to escaped LaTeX
+        let result = renderer.render_inline("x & y").unwrap();
+        assert!(result.contains(r#"<span class="math inline math-error">x &amp; y</span>"#));
+
+        let unchecked = MathMLRenderer::new().with_validation(false);
+        assert!(unchecked
+            .render_inline("x & y")
+            .unwrap()
+            .starts_with("<math"));
+    }
 }
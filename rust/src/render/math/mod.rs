@@ -7,9 +7,11 @@ pub use self::katex::KaTeXRenderer;
 pub use self::mathml::MathMLRenderer;
 
 use crate::error::Result;
+use serde::Deserialize;
 
 /// Math rendering backend.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum MathBackend {
     /// Output raw LaTeX for KaTeX/MathJax to render client-side.
     #[default]
@@ -20,6 +22,17 @@ pub enum MathBackend {
     MathJax,
 }
 
+/// Side of the page on which equation numbers are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EquationNumberSide {
+    /// After the equation, at the right margin.
+    #[default]
+    Right,
+    /// Before the equation, at the left margin (like `leqno`).
+    Left,
+}
+
 /// Trait for math renderers.
 pub trait MathRenderer {
     /// Render inline math.
@@ -34,9 +47,19 @@ pub trait MathRenderer {
 
 /// Create a math renderer for the given backend.
 pub fn create_renderer(backend: MathBackend) -> Box<dyn MathRenderer> {
+    create_renderer_with_side(backend, EquationNumberSide::default())
+}
+
+/// Create a math renderer whose `\tag` numbers (as on `align` lines) are
+/// placed on the given side.
+pub fn create_renderer_with_side(
+    backend: MathBackend,
+    side: EquationNumberSide,
+) -> Box<dyn MathRenderer> {
+    let left_tags = side == EquationNumberSide::Left;
     match backend {
-        MathBackend::KaTeX => Box::new(KaTeXRenderer::new()),
-        MathBackend::MathJax => Box::new(KaTeXRenderer::new_mathjax()),
+        MathBackend::KaTeX => Box::new(KaTeXRenderer::new().with_left_tags(left_tags)),
+        MathBackend::MathJax => Box::new(KaTeXRenderer::new_mathjax().with_left_tags(left_tags)),
         MathBackend::MathML => Box::new(MathMLRenderer::new()),
     }
 }
@@ -1,13 +1,21 @@
 //! Rendering layer for converting resolved documents to output formats.
 
+pub mod commonmark;
 pub mod html;
 pub mod math;
+pub mod slug;
 
 #[cfg(feature = "pdf")]
 pub mod pdf;
 
-pub use html::{render_html, HtmlConfig};
-pub use math::{MathBackend, MathRenderer};
+pub use commonmark::{to_commonmark, to_commonmark_with, MdConfig};
+pub use html::{
+    render_html, render_html_with_index, BibliographySort, CitationHook, CodeOverflow,
+    FootnoteStyle, HtmlConfig, IndexCitation, IndexReference, IndexTarget, RenderIndex,
+    TextDirection, UnresolvedReferenceStyle,
+};
+pub use math::{EquationNumberSide, MathBackend, MathRenderer};
+pub use slug::SlugMode;
 
 #[cfg(feature = "pdf")]
 pub use pdf::{render_pdf, render_pdf_to_file, PageMargins, PaperSize, PdfConfig};
@@ -15,6 +23,12 @@ pub use pdf::{render_pdf, render_pdf_to_file, PageMargins, PaperSize, PdfConfig}
 use crate::ast::ResolvedDocument;
 use crate::error::Result;
 
+/// A bibliography field's text, or `None` if it is missing or blank (as an
+/// undefined `@string` macro leaves it).
+pub(crate) fn bib_field(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|text| !text.trim().is_empty())
+}
+
 /// Render a resolved document to HTML.
 pub fn render(document: &ResolvedDocument, config: &HtmlConfig) -> Result<String> {
     render_html(document, config)
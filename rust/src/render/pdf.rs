@@ -7,9 +7,17 @@
 
 use crate::ast::{Block, CitationStyle, EnvironmentKind, FootnoteKind, Inline, ResolvedDocument};
 use crate::error::{RenderError, Result};
-use genpdf::elements::{Break, Paragraph};
-use genpdf::{Document, SimplePageDecorator};
+use crate::render::bib_field;
+use genpdf::elements::{Break, Paragraph, TableLayout};
+use genpdf::render::Area;
+use genpdf::style::Style;
+use genpdf::{
+    Alignment, Context, Document, Element, PageDecorator, RenderResult, SimplePageDecorator,
+};
+use lopdf::{dictionary, Object, ObjectId, StringFormat};
+use std::cell::{Cell, RefCell};
 use std::path::Path;
+use std::rc::Rc;
 
 /// Configuration for PDF rendering.
 #[derive(Debug, Clone)]
@@ -32,6 +40,11 @@ pub struct PdfConfig {
     pub page_numbers: bool,
     /// Whether to include table of contents.
     pub include_toc: bool,
+    /// Whether to stamp a light "DRAFT" watermark across every page.
+    pub draft: bool,
+    /// Whether to number the body lines in the left margin, restarting on
+    /// each page.
+    pub line_numbers: bool,
 }
 
 impl Default for PdfConfig {
@@ -46,6 +59,8 @@ impl Default for PdfConfig {
             title_page: false,
             page_numbers: true,
             include_toc: true,
+            draft: false,
+            line_numbers: false,
         }
     }
 }
@@ -104,11 +119,62 @@ pub fn render_pdf_to_file(
     Ok(())
 }
 
+/// Shared record of the page being laid out and the pages on which
+/// [`PageMarker`]s landed.
+#[derive(Clone, Default)]
+struct PageTracker {
+    current: Rc<Cell<usize>>,
+    marks: Rc<RefCell<Vec<usize>>>,
+}
+
+/// Page decorator that counts pages before applying the margins.
+struct CountingDecorator {
+    inner: SimplePageDecorator,
+    tracker: PageTracker,
+}
+
+impl PageDecorator for CountingDecorator {
+    fn decorate_page<'a>(
+        &mut self,
+        context: &Context,
+        area: Area<'a>,
+        style: Style,
+    ) -> std::result::Result<Area<'a>, genpdf::error::Error> {
+        self.tracker.current.set(self.tracker.current.get() + 1);
+        self.inner.decorate_page(context, area, style)
+    }
+}
+
+/// Zero-size element that records the (1-based) page it is laid out on.
+struct PageMarker(PageTracker);
+
+impl Element for PageMarker {
+    fn render(
+        &mut self,
+        _context: &Context,
+        _area: Area<'_>,
+        _style: Style,
+    ) -> std::result::Result<RenderResult, genpdf::error::Error> {
+        self.0.marks.borrow_mut().push(self.0.current.get());
+        Ok(RenderResult::default())
+    }
+}
+
+/// A PDF bookmark pointing at the page of a heading.
+struct OutlineItem {
+    level: u8,
+    title: String,
+    page: usize,
+}
+
 struct PdfRenderer<'a> {
     doc: &'a ResolvedDocument,
     config: &'a PdfConfig,
     footnotes: Vec<(u32, String)>,
     footnote_counter: u32,
+    /// Pages of the top-level headings, known after a first layout pass.
+    heading_pages: Option<Vec<usize>>,
+    tracker: PageTracker,
 }
 
 impl<'a> PdfRenderer<'a> {
@@ -118,10 +184,44 @@ impl<'a> PdfRenderer<'a> {
             config,
             footnotes: Vec::new(),
             footnote_counter: 0,
+            heading_pages: None,
+            tracker: PageTracker::default(),
         })
     }
 
     fn render(mut self) -> Result<Vec<u8>> {
+        let with_toc = self.config.include_toc && self.has_toc_placeholder();
+
+        // Page numbers are only known after layout, so a document with a
+        // table of contents is laid out once to find them, then again for real.
+        if with_toc {
+            let (_, pages) = self.layout(with_toc)?;
+            self.heading_pages = Some(pages);
+            self.footnotes.clear();
+            self.footnote_counter = 0;
+        }
+
+        let (buffer, pages) = self.layout(with_toc)?;
+        let outline: Vec<OutlineItem> = self
+            .top_level_headings()
+            .into_iter()
+            .zip(pages)
+            .map(|((level, title), page)| OutlineItem { level, title, page })
+            .collect();
+
+        let pdf = add_outline(buffer, &outline)?;
+        if self.config.draft || self.config.line_numbers {
+            add_draft_marks(pdf, self.config)
+        } else {
+            Ok(pdf)
+        }
+    }
+
+    /// Lay out the document, returning the PDF bytes and the page of every
+    /// top-level heading.
+    fn layout(&mut self, with_toc: bool) -> Result<(Vec<u8>, Vec<usize>)> {
+        self.tracker = PageTracker::default();
+
         // Try to load fonts from various locations
         let font_family =
             genpdf::fonts::from_files("/usr/share/fonts/liberation", "LiberationSerif", None)
@@ -151,7 +251,10 @@ impl<'a> PdfRenderer<'a> {
         // Add page decorator with margins
         let mut decorator = SimplePageDecorator::new();
         decorator.set_margins(self.config.margins.top as u32);
-        pdf.set_page_decorator(decorator);
+        pdf.set_page_decorator(CountingDecorator {
+            inner: decorator,
+            tracker: self.tracker.clone(),
+        });
 
         pdf.set_font_size(self.config.font_size);
         pdf.set_line_spacing(self.config.line_height);
@@ -162,12 +265,15 @@ impl<'a> PdfRenderer<'a> {
         }
 
         // Table of contents
-        if self.config.include_toc && self.has_toc_placeholder() {
+        if with_toc {
             self.render_toc(&mut pdf)?;
         }
 
         // Main content
         for block in &self.doc.document.blocks {
+            if matches!(block, Block::Heading { .. }) {
+                pdf.push(PageMarker(self.tracker.clone()));
+            }
             self.render_block(&mut pdf, block)?;
         }
 
@@ -186,7 +292,8 @@ impl<'a> PdfRenderer<'a> {
         pdf.render(&mut buffer)
             .map_err(|e| RenderError::Template(e.to_string()))?;
 
-        Ok(buffer)
+        let pages = self.tracker.marks.take();
+        Ok((buffer, pages))
     }
 
     fn has_toc_placeholder(&self) -> bool {
@@ -207,18 +314,39 @@ impl<'a> PdfRenderer<'a> {
 
         pdf.push(Break::new(3.0));
         pdf.push(Paragraph::new(title));
+        if let Some(ref subtitle) = self.doc.document.metadata.subtitle {
+            pdf.push(Break::new(0.3));
+            pdf.push(Paragraph::new(subtitle.clone()));
+        }
         pdf.push(Break::new(1.0));
 
         let authors = if !self.config.authors.is_empty() {
             self.config.authors.clone()
         } else {
-            self.doc.document.metadata.authors.clone()
+            self.doc
+                .document
+                .metadata
+                .authors
+                .iter()
+                .map(|a| a.name.clone())
+                .collect()
         };
 
         if !authors.is_empty() {
             pdf.push(Paragraph::new(authors.join(", ")));
         }
 
+        let metadata = &self.doc.document.metadata;
+        for line in [&metadata.department, &metadata.institution]
+            .into_iter()
+            .flatten()
+        {
+            pdf.push(Paragraph::new(line.clone()));
+        }
+        if let Some(ref advisor) = metadata.advisor {
+            pdf.push(Paragraph::new(format!("Advisor: {}", advisor)));
+        }
+
         if let Some(ref date) = self.doc.document.metadata.date {
             pdf.push(Break::new(0.5));
             pdf.push(Paragraph::new(date.clone()));
@@ -228,11 +356,10 @@ impl<'a> PdfRenderer<'a> {
         Ok(())
     }
 
-    fn render_toc(&mut self, pdf: &mut Document) -> Result<()> {
-        pdf.push(Paragraph::new("Table of Contents"));
-        pdf.push(Break::new(0.5));
-
-        for block in &self.doc.document.blocks.clone() {
+    /// Level and display text (with section number) of each top-level heading.
+    fn top_level_headings(&mut self) -> Vec<(u8, String)> {
+        let mut headings = Vec::new();
+        for block in &self.doc.document.blocks {
             if let Block::Heading {
                 level,
                 content,
@@ -240,20 +367,43 @@ impl<'a> PdfRenderer<'a> {
             } = block
             {
                 let text = self.inlines_to_string(content);
-                let prefix = if let Some(lbl) = label {
-                    if let Some(num) = self.doc.section_numbers.get(lbl) {
-                        format!("{}  ", num)
-                    } else {
-                        String::new()
-                    }
-                } else {
-                    String::new()
+                let number = label
+                    .as_ref()
+                    .and_then(|lbl| self.doc.section_numbers.get(lbl));
+                let text = match number {
+                    Some(num) => format!("{}  {}", num, text),
+                    None => text,
                 };
-
-                let indent = "  ".repeat((*level as usize).saturating_sub(1));
-                pdf.push(Paragraph::new(format!("{}{}{}", indent, prefix, text)));
+                headings.push((*level, text));
             }
         }
+        headings
+    }
+
+    fn render_toc(&mut self, pdf: &mut Document) -> Result<()> {
+        pdf.push(Paragraph::new("Table of Contents"));
+        pdf.push(Break::new(0.5));
+
+        // The page column is laid out (empty) in the first pass too, so that
+        // filling it in does not move any content.
+        let mut table = TableLayout::new(vec![9, 1]);
+        for (i, (level, text)) in self.top_level_headings().into_iter().enumerate() {
+            let page = self
+                .heading_pages
+                .as_ref()
+                .and_then(|pages| pages.get(i))
+                .map(|page| page.to_string())
+                .unwrap_or_default();
+
+            let indent = "  ".repeat((level as usize).saturating_sub(1));
+            table
+                .row()
+                .element(Paragraph::new(format!("{}{}", indent, text)))
+                .element(Paragraph::new(page).aligned(Alignment::Right))
+                .push()
+                .map_err(|e| RenderError::Template(e.to_string()))?;
+        }
+        pdf.push(table);
 
         pdf.push(Break::new(1.0));
         Ok(())
@@ -261,7 +411,10 @@ impl<'a> PdfRenderer<'a> {
 
     fn render_block(&mut self, pdf: &mut Document, block: &Block) -> Result<()> {
         match block {
-            Block::Paragraph(inlines) => {
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            } => {
                 let text = self.inlines_to_string(inlines);
                 pdf.push(Paragraph::new(text));
                 pdf.push(Break::new(0.3));
@@ -304,7 +457,11 @@ impl<'a> PdfRenderer<'a> {
             Block::BlockQuote(blocks) => {
                 pdf.push(Break::new(0.2));
                 for inner in blocks {
-                    if let Block::Paragraph(inlines) = inner {
+                    if let Block::Paragraph(inlines)
+                    | Block::AttributedParagraph {
+                        content: inlines, ..
+                    } = inner
+                    {
                         let text = self.inlines_to_string(inlines);
                         pdf.push(Paragraph::new(format!("  > {}", text)));
                     } else {
@@ -340,11 +497,19 @@ impl<'a> PdfRenderer<'a> {
 
                     for (j, inner_block) in item.content.iter().enumerate() {
                         if j == 0 {
-                            if let Block::Paragraph(inlines) = inner_block {
+                            if let Block::Paragraph(inlines)
+                            | Block::AttributedParagraph {
+                                content: inlines, ..
+                            } = inner_block
+                            {
                                 let text = self.inlines_to_string(inlines);
                                 pdf.push(Paragraph::new(format!("  {}{}", marker, text)));
                             }
-                        } else if let Block::Paragraph(inlines) = inner_block {
+                        } else if let Block::Paragraph(inlines)
+                        | Block::AttributedParagraph {
+                            content: inlines, ..
+                        } = inner_block
+                        {
                             let text = self.inlines_to_string(inlines);
                             pdf.push(Paragraph::new(format!("    {}", text)));
                         }
@@ -357,7 +522,7 @@ impl<'a> PdfRenderer<'a> {
                 pdf.push(Paragraph::new("---"));
                 pdf.push(Break::new(0.3));
             }
-            Block::DisplayMath { content, label } => {
+            Block::DisplayMath { content, label, .. } => {
                 pdf.push(Break::new(0.3));
 
                 let mut display_text = content.clone();
@@ -373,10 +538,20 @@ impl<'a> PdfRenderer<'a> {
             Block::Environment {
                 kind,
                 label,
+                title,
+                proves,
                 content,
                 caption,
             } => {
-                self.render_environment(pdf, kind, label.as_deref(), content, caption.as_deref())?;
+                self.render_environment(
+                    pdf,
+                    kind,
+                    label.as_deref(),
+                    title.as_deref(),
+                    proves.as_deref(),
+                    content,
+                    caption.as_deref(),
+                )?;
             }
             Block::TableOfContents => {
                 // Already rendered at the beginning
@@ -399,7 +574,11 @@ impl<'a> PdfRenderer<'a> {
                     let term = self.inlines_to_string(&item.term);
                     pdf.push(Paragraph::new(format!("{}:", term)));
                     for inner_block in &item.description {
-                        if let Block::Paragraph(inlines) = inner_block {
+                        if let Block::Paragraph(inlines)
+                        | Block::AttributedParagraph {
+                            content: inlines, ..
+                        } = inner_block
+                        {
                             let text = self.inlines_to_string(inlines);
                             pdf.push(Paragraph::new(format!("    {}", text)));
                         }
@@ -407,7 +586,9 @@ impl<'a> PdfRenderer<'a> {
                 }
                 pdf.push(Break::new(0.3));
             }
-            Block::PageBreak => {
+            // Figures and tables are placed where they appear, so no floats
+            // are ever pending and the barrier only starts a new page
+            Block::PageBreak | Block::ClearPage => {
                 pdf.push(genpdf::elements::PageBreak::new());
             }
             Block::Abstract(blocks) => {
@@ -428,11 +609,14 @@ impl<'a> PdfRenderer<'a> {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_environment(
         &mut self,
         pdf: &mut Document,
         kind: &EnvironmentKind,
         label: Option<&str>,
+        title: Option<&str>,
+        proves: Option<&str>,
         content: &[Block],
         caption: Option<&[Inline]>,
     ) -> Result<()> {
@@ -440,17 +624,20 @@ impl<'a> PdfRenderer<'a> {
 
         // Environment header
         let header = if kind.is_numbered() {
-            if let Some(lbl) = label {
-                if let Some(num) = self.doc.env_numbers.get(lbl) {
-                    format!("{} {}.", kind.display_name(), num)
-                } else {
-                    format!("{}.", kind.display_name())
-                }
-            } else {
-                format!("{}.", kind.display_name())
+            let mut header = kind.display_name().to_string();
+            if let Some(num) = label.and_then(|lbl| self.doc.env_numbers.get(lbl)) {
+                header.push_str(&format!(" {}", num));
+            }
+            if let Some(title) = title {
+                header.push_str(&format!(" ({})", title));
             }
+            header.push('.');
+            header
         } else if matches!(kind, EnvironmentKind::Proof) {
-            "Proof.".to_string()
+            match proves.and_then(|lbl| self.doc.labels.get(lbl)) {
+                Some(info) => format!("Proof of {}.", info.display),
+                None => "Proof.".to_string(),
+            }
         } else {
             String::new()
         };
@@ -558,18 +745,32 @@ impl<'a> PdfRenderer<'a> {
 
                 if !entry.authors.is_empty() {
                     parts.push(entry.authors.join(", "));
+                } else if let Some(editors) = entry.editors_text() {
+                    parts.push(editors);
                 }
-                if let Some(ref year) = entry.year {
-                    parts.push(format!("({})", year));
+                if let Some(date) = entry.date_text() {
+                    parts.push(format!("({})", date));
                 }
-                if let Some(ref entry_title) = entry.title {
+                if let Some(entry_title) = bib_field(&entry.title) {
                     parts.push(format!("\"{}\"", entry_title));
                 }
-                if let Some(ref journal) = entry.journal {
-                    parts.push(journal.clone());
+                if let Some(edition) = bib_field(&entry.edition) {
+                    parts.push(format!("{} ed", edition));
+                }
+                if let Some(journal) = bib_field(&entry.journal) {
+                    parts.push(journal.to_string());
                 }
-                if let Some(ref booktitle) = entry.booktitle {
-                    parts.push(format!("In {}", booktitle));
+                if let Some(booktitle) = bib_field(&entry.booktitle) {
+                    match entry.editors_text().filter(|_| !entry.authors.is_empty()) {
+                        Some(editors) => parts.push(format!("In {}, {}", editors, booktitle)),
+                        None => parts.push(format!("In {}", booktitle)),
+                    }
+                }
+                if let Some(publisher) = bib_field(&entry.publisher) {
+                    parts.push(publisher.to_string());
+                }
+                if let Some(note) = bib_field(&entry.note) {
+                    parts.push(note.to_string());
                 }
 
                 let entry_text = parts.join(". ");
@@ -588,7 +789,8 @@ impl<'a> PdfRenderer<'a> {
                 Inline::Emphasis(inner)
                 | Inline::Strong(inner)
                 | Inline::Strikethrough(inner)
-                | Inline::SmallCaps(inner) => {
+                | Inline::SmallCaps(inner)
+                | Inline::Lang { content: inner, .. } => {
                     result.push_str(&self.inlines_to_string(inner));
                 }
                 Inline::Subscript(inner) => {
@@ -604,9 +806,18 @@ impl<'a> PdfRenderer<'a> {
                     result.push_str(c);
                     result.push('`');
                 }
+                Inline::Kbd(keys) | Inline::Verbatim(keys) => result.push_str(keys),
+                Inline::Abbreviation { abbr, .. } => result.push_str(abbr),
                 Inline::Link { content, .. } => {
                     result.push_str(&self.inlines_to_string(content));
                 }
+                Inline::LinkReference {
+                    content, closing, ..
+                } => {
+                    result.push('[');
+                    result.push_str(&self.inlines_to_string(content));
+                    result.push_str(closing);
+                }
                 Inline::Image { alt, .. } => {
                     result.push_str(&format!("[Image: {}]", alt));
                 }
@@ -615,69 +826,82 @@ impl<'a> PdfRenderer<'a> {
                     result.push_str(m);
                     result.push('$');
                 }
-                Inline::Citation(cite) => match cite.style {
-                    CitationStyle::Parenthetical => {
-                        let keys: Vec<String> = cite
-                            .keys
-                            .iter()
-                            .map(|k| {
-                                if let Some(entry) = self.doc.citations.get(k) {
-                                    self.format_short_citation(entry)
+                Inline::Citation(cite) => {
+                    let prefix = cite
+                        .prefix
+                        .as_ref()
+                        .map(|prefix| format!("{} ", prefix))
+                        .unwrap_or_default();
+                    match cite.style {
+                        CitationStyle::Parenthetical => {
+                            let keys: Vec<String> = cite
+                                .keys
+                                .iter()
+                                .enumerate()
+                                .map(|(i, k)| {
+                                    let text = if let Some(entry) = self.doc.citations.get(k) {
+                                        self.format_short_citation(entry)
+                                    } else {
+                                        k.clone()
+                                    };
+                                    match cite.locator(i) {
+                                        Some(loc) => format!("{}, {}", text, loc),
+                                        None => text,
+                                    }
+                                })
+                                .collect();
+
+                            result.push_str(&format!("[{}{}]", prefix, keys.join("; ")));
+                        }
+                        CitationStyle::Textual => {
+                            result.push_str(&prefix);
+                            for (i, key) in cite.keys.iter().enumerate() {
+                                if i > 0 {
+                                    result.push_str(", ");
+                                }
+                                if let Some(entry) = self.doc.citations.get(key) {
+                                    let (author, year) = self.format_author_year(entry);
+                                    result.push_str(&format!("{} ({})", author, year));
                                 } else {
-                                    k.clone()
+                                    result.push_str(key);
                                 }
-                            })
-                            .collect();
-
-                        let mut cite_text = format!("[{}]", keys.join("; "));
-                        if let Some(ref loc) = cite.locator {
-                            cite_text = format!("[{}, {}]", keys.join("; "), loc);
-                        }
-                        result.push_str(&cite_text);
-                    }
-                    CitationStyle::Textual => {
-                        for (i, key) in cite.keys.iter().enumerate() {
-                            if i > 0 {
-                                result.push_str(", ");
-                            }
-                            if let Some(entry) = self.doc.citations.get(key) {
-                                let (author, year) = self.format_author_year(entry);
-                                result.push_str(&format!("{} ({})", author, year));
-                            } else {
-                                result.push_str(key);
                             }
                         }
-                    }
-                    CitationStyle::AuthorOnly => {
-                        for (i, key) in cite.keys.iter().enumerate() {
-                            if i > 0 {
-                                result.push_str(", ");
-                            }
-                            if let Some(entry) = self.doc.citations.get(key) {
-                                let (author, _) = self.format_author_year(entry);
-                                result.push_str(&author);
-                            } else {
-                                result.push_str(key);
+                        CitationStyle::AuthorOnly => {
+                            result.push_str(&prefix);
+                            for (i, key) in cite.keys.iter().enumerate() {
+                                if i > 0 {
+                                    result.push_str(", ");
+                                }
+                                if let Some(entry) = self.doc.citations.get(key) {
+                                    let (author, _) = self.format_author_year(entry);
+                                    result.push_str(&author);
+                                } else {
+                                    result.push_str(key);
+                                }
                             }
                         }
-                    }
-                    CitationStyle::YearOnly => {
-                        result.push('(');
-                        for (i, key) in cite.keys.iter().enumerate() {
-                            if i > 0 {
-                                result.push_str(", ");
-                            }
-                            if let Some(entry) = self.doc.citations.get(key) {
-                                let (_, year) = self.format_author_year(entry);
-                                result.push_str(&year);
-                            } else {
-                                result.push_str(key);
+                        CitationStyle::YearOnly => {
+                            result.push('(');
+                            result.push_str(&prefix);
+                            for (i, key) in cite.keys.iter().enumerate() {
+                                if i > 0 {
+                                    result.push_str(", ");
+                                }
+                                if let Some(entry) = self.doc.citations.get(key) {
+                                    let (_, year) = self.format_author_year(entry);
+                                    result.push_str(&year);
+                                } else {
+                                    result.push_str(key);
+                                }
                             }
+                            result.push(')');
                         }
-                        result.push(')');
                     }
-                },
-                Inline::Reference { label, resolved } => {
+                }
+                Inline::Reference {
+                    label, resolved, ..
+                } => {
                     let fallback = format!("??{}", label);
                     let text = resolved.as_deref().unwrap_or(&fallback);
                     result.push_str(text);
@@ -687,12 +911,17 @@ impl<'a> PdfRenderer<'a> {
                     let num = self.footnote_counter;
                     result.push_str(&format!("[{}]", num));
 
-                    if let FootnoteKind::Inline(content) = kind {
+                    let content = match kind {
+                        FootnoteKind::Inline(content) => Some(content),
+                        FootnoteKind::Reference(id) => self.doc.footnotes.get(id),
+                    };
+                    if let Some(content) = content {
                         let footnote_text = self.inlines_to_string(content);
                         self.footnotes.push((num, footnote_text));
                     }
                 }
                 Inline::SoftBreak | Inline::HardBreak => result.push(' '),
+                Inline::NonBreakingSpace => result.push('\u{a0}'),
                 Inline::RawHtml(_) => {}
             }
         }
@@ -789,6 +1018,215 @@ impl<'a> PdfRenderer<'a> {
     }
 }
 
+/// Add PDF bookmarks (the document outline shown in a viewer's navigation
+/// panel), nested by heading level.
+fn add_outline(pdf: Vec<u8>, items: &[OutlineItem]) -> Result<Vec<u8>> {
+    if items.is_empty() {
+        return Ok(pdf);
+    }
+
+    let err = |e: lopdf::Error| RenderError::Template(e.to_string());
+    let mut doc = lopdf::Document::load_mem(&pdf).map_err(err)?;
+    let pages = doc.get_pages();
+    let Some(&last_page) = pages.values().next_back() else {
+        return Ok(pdf);
+    };
+
+    // Parent of each item: the closest preceding item with a lower level
+    let mut parents = Vec::with_capacity(items.len());
+    let mut stack: Vec<(u8, usize)> = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        while stack.last().is_some_and(|&(level, _)| level >= item.level) {
+            stack.pop();
+        }
+        parents.push(stack.last().map(|&(_, idx)| idx));
+        stack.push((item.level, i));
+    }
+    let children = |parent: Option<usize>| -> Vec<usize> {
+        (0..items.len()).filter(|&i| parents[i] == parent).collect()
+    };
+
+    let root_id = doc.new_object_id();
+    let ids: Vec<ObjectId> = items.iter().map(|_| doc.new_object_id()).collect();
+
+    for (i, item) in items.iter().enumerate() {
+        let page = pages.get(&(item.page as u32)).copied().unwrap_or(last_page);
+        let mut dict = dictionary! {
+            "Title" => pdf_text_string(&item.title),
+            "Parent" => parents[i].map_or(root_id, |p| ids[p]),
+            "Dest" => vec![page.into(), "Fit".into()],
+        };
+
+        let siblings = children(parents[i]);
+        let pos = siblings.iter().position(|&s| s == i).unwrap_or(0);
+        if pos > 0 {
+            dict.set("Prev", ids[siblings[pos - 1]]);
+        }
+        if let Some(&next) = siblings.get(pos + 1) {
+            dict.set("Next", ids[next]);
+        }
+
+        let kids = children(Some(i));
+        if let (Some(&first), Some(&last)) = (kids.first(), kids.last()) {
+            dict.set("First", ids[first]);
+            dict.set("Last", ids[last]);
+            dict.set("Count", kids.len() as i64);
+        }
+
+        doc.objects.insert(ids[i], Object::Dictionary(dict));
+    }
+
+    let top = children(None);
+    doc.objects.insert(
+        root_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Outlines",
+            "First" => ids[top[0]],
+            "Last" => ids[top[top.len() - 1]],
+            "Count" => top.len() as i64,
+        }),
+    );
+
+    let catalog_id = doc
+        .trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .map_err(err)?;
+    let catalog = doc
+        .get_object_mut(catalog_id)
+        .and_then(Object::as_dict_mut)
+        .map_err(err)?;
+    catalog.set("Outlines", root_id);
+    catalog.set("PageMode", "UseOutlines");
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer)
+        .map_err(|e| RenderError::Template(e.to_string()))?;
+    Ok(buffer)
+}
+
+/// Resource name of the font used for draft marks.
+const DRAFT_FONT: &str = "MdaDraft";
+
+/// Stamp the draft watermark and margin line numbers onto every page.
+///
+/// The watermark is drawn before the page content so text stays legible on
+/// top of it; line numbers follow the body's line pitch like a ruler.
+fn add_draft_marks(pdf: Vec<u8>, config: &PdfConfig) -> Result<Vec<u8>> {
+    let err = |e: lopdf::Error| RenderError::Template(e.to_string());
+    let mut doc = lopdf::Document::load_mem(&pdf).map_err(err)?;
+
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+
+    let pt = |mm: f64| mm * 72.0 / 25.4;
+    let (width, height) = config.paper_size.dimensions();
+    let (width, height) = (pt(width), pt(height));
+
+    // Underlay: the watermark, then an open graphics state for the content
+    let mut underlay = String::new();
+    if config.draft {
+        // "DRAFT" is 3.333 em wide in Helvetica; centre it on the diagonal
+        let size = 110.0;
+        let half = 3.333 * size / 2.0 * std::f64::consts::FRAC_1_SQRT_2;
+        underlay.push_str(&format!(
+            "q 0.85 g BT /{} {} Tf 0.7071 0.7071 -0.7071 0.7071 {:.2} {:.2} Tm (DRAFT) Tj ET Q\n",
+            DRAFT_FONT,
+            size,
+            width / 2.0 - half,
+            height / 2.0 - half,
+        ));
+    }
+    underlay.push_str("q\n");
+
+    // Overlay: close the content's graphics state, then the line numbers
+    let mut overlay = String::from("Q\n");
+    if config.line_numbers {
+        let pitch = config.font_size as f64 * config.line_height;
+        let x = (pt(config.margins.left) / 2.0).max(4.0);
+        let bottom = pt(config.margins.bottom);
+        let mut y = height - pt(config.margins.top) - config.font_size as f64;
+        overlay.push_str(&format!("q 0.5 g BT /{} 7 Tf\n", DRAFT_FONT));
+        let mut line = 1;
+        while y >= bottom {
+            overlay.push_str(&format!("1 0 0 1 {:.2} {:.2} Tm ({}) Tj\n", x, y, line));
+            line += 1;
+            y -= pitch;
+        }
+        overlay.push_str("ET Q\n");
+    }
+
+    let underlay_id = doc.add_object(lopdf::Stream::new(dictionary! {}, underlay.into_bytes()));
+    let overlay_id = doc.add_object(lopdf::Stream::new(dictionary! {}, overlay.into_bytes()));
+
+    for page_id in doc.get_pages().into_values() {
+        add_page_font(&mut doc, page_id, font_id).map_err(err)?;
+
+        let page = doc
+            .get_object_mut(page_id)
+            .and_then(Object::as_dict_mut)
+            .map_err(err)?;
+        let mut contents = vec![Object::Reference(underlay_id)];
+        match page.get(b"Contents").ok().cloned() {
+            Some(Object::Array(streams)) => contents.extend(streams),
+            Some(stream @ Object::Reference(_)) => contents.push(stream),
+            _ => {}
+        }
+        contents.push(Object::Reference(overlay_id));
+        page.set("Contents", contents);
+    }
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer)
+        .map_err(|e| RenderError::Template(e.to_string()))?;
+    Ok(buffer)
+}
+
+/// Register the draft font in a page's resources, moving inline resource
+/// and font dictionaries into their own objects so they can be edited.
+fn add_page_font(
+    doc: &mut lopdf::Document,
+    page_id: ObjectId,
+    font_id: ObjectId,
+) -> lopdf::Result<()> {
+    let resources_id = indirect_entry(doc, page_id, b"Resources")?;
+    let fonts_id = indirect_entry(doc, resources_id, b"Font")?;
+    doc.get_object_mut(fonts_id)?
+        .as_dict_mut()?
+        .set(DRAFT_FONT, font_id);
+    Ok(())
+}
+
+/// The object id of a dictionary entry, creating it if it is missing or
+/// stored inline.
+fn indirect_entry(doc: &mut lopdf::Document, id: ObjectId, key: &[u8]) -> lopdf::Result<ObjectId> {
+    let entry = doc.get_object(id)?.as_dict()?.get(key).ok().cloned();
+    let entry_id = match entry {
+        Some(Object::Reference(entry_id)) => return Ok(entry_id),
+        Some(Object::Dictionary(dict)) => doc.add_object(dict),
+        _ => doc.add_object(dictionary! {}),
+    };
+    doc.get_object_mut(id)?
+        .as_dict_mut()?
+        .set(key.to_vec(), entry_id);
+    Ok(entry_id)
+}
+
+/// Encode a PDF text string: ASCII as a literal, anything else as UTF-16BE.
+fn pdf_text_string(text: &str) -> Object {
+    if text.is_ascii() {
+        return Object::string_literal(text);
+    }
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -805,4 +1243,126 @@ mod tests {
         assert_eq!(PaperSize::Letter.dimensions(), (215.9, 279.4));
         assert_eq!(PaperSize::A4.dimensions(), (210.0, 297.0));
     }
+
+    #[test]
+    fn test_clear_page() {
+        let doc = crate::parse("# One\n\nText.\n\n\\clearpage\n\n# Two").unwrap();
+        let resolved = crate::resolve(doc, &Default::default()).unwrap();
+        match render_pdf(&resolved, &PdfConfig::default()) {
+            Ok(bytes) => assert!(bytes.starts_with(b"%PDF")),
+            // Rendering needs the Liberation fonts, which may be missing
+            Err(e) => assert!(e.to_string().contains("fonts"), "{}", e),
+        }
+    }
+
+    /// A bare two-page PDF, standing in for genpdf output (which needs fonts).
+    fn blank_pdf(pages: usize) -> Vec<u8> {
+        let mut doc = lopdf::Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..pages)
+            .map(|_| {
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                })
+                .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => pages as i64,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut buffer = Vec::new();
+        doc.save_to(&mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_draft_marks() {
+        let blank = blank_pdf(2);
+        let config = PdfConfig {
+            draft: true,
+            line_numbers: true,
+            ..Default::default()
+        };
+        let pdf = add_draft_marks(blank.clone(), &config).unwrap();
+        assert!(pdf.len() > blank.len());
+
+        let doc = lopdf::Document::load_mem(&pdf).unwrap();
+        for page_id in doc.get_pages().into_values() {
+            let content = String::from_utf8(doc.get_page_content(page_id).unwrap()).unwrap();
+            assert!(content.contains("(DRAFT) Tj"));
+            assert!(content.contains("(1) Tj"));
+
+            let page = doc.get_dictionary(page_id).unwrap();
+            let resources = page.get(b"Resources").unwrap().as_reference().unwrap();
+            let resources = doc.get_dictionary(resources).unwrap();
+            let fonts = resources.get(b"Font").unwrap().as_reference().unwrap();
+            assert!(doc
+                .get_dictionary(fonts)
+                .unwrap()
+                .has(DRAFT_FONT.as_bytes()));
+        }
+
+        // Line numbers alone leave out the watermark
+        let config = PdfConfig {
+            line_numbers: true,
+            ..Default::default()
+        };
+        let pdf = add_draft_marks(blank, &config).unwrap();
+        let doc = lopdf::Document::load_mem(&pdf).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let content = String::from_utf8(doc.get_page_content(page_id).unwrap()).unwrap();
+        assert!(!content.contains("DRAFT"));
+        assert!(content.contains("(1) Tj"));
+    }
+
+    #[test]
+    fn test_outline_bookmarks() {
+        let item = |level, title: &str, page| OutlineItem {
+            level,
+            title: title.to_string(),
+            page,
+        };
+        let items = [
+            item(1, "1  Introduction", 1),
+            item(2, "1.1  Motivation", 1),
+            item(1, "2  Théorie", 2),
+        ];
+        let pdf = add_outline(blank_pdf(2), &items).unwrap();
+
+        let doc = lopdf::Document::load_mem(&pdf).unwrap();
+        let root = doc.catalog().unwrap().get(b"Outlines").unwrap();
+        let root = doc.get_dictionary(root.as_reference().unwrap()).unwrap();
+        assert_eq!(root.get(b"Count").unwrap().as_i64().unwrap(), 2);
+
+        let first = root.get(b"First").unwrap().as_reference().unwrap();
+        let first = doc.get_dictionary(first).unwrap();
+        assert_eq!(
+            first.get(b"Title").unwrap().as_str().unwrap(),
+            b"1  Introduction"
+        );
+        let child = first.get(b"First").unwrap().as_reference().unwrap();
+        let child = doc.get_dictionary(child).unwrap();
+        assert_eq!(
+            child.get(b"Title").unwrap().as_str().unwrap(),
+            b"1.1  Motivation"
+        );
+
+        let last = root.get(b"Last").unwrap().as_reference().unwrap();
+        let last = doc.get_dictionary(last).unwrap();
+        let dest = last.get(b"Dest").unwrap().as_array().unwrap();
+        assert_eq!(dest[0].as_reference().unwrap(), doc.get_pages()[&2]);
+    }
 }
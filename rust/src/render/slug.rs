@@ -0,0 +1,170 @@
+//! Heading slug generation for automatic HTML ids.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// How heading titles are converted to slugs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SlugMode {
+    /// Lowercase the title and keep all letters as-is.
+    Unicode,
+    /// Transliterate accented Latin letters to ASCII (é → e) and keep
+    /// letters from other scripts as Unicode.
+    #[default]
+    Transliterate,
+    /// Transliterate accented Latin letters to ASCII and percent-encode
+    /// letters from other scripts.
+    PercentEncode,
+}
+
+/// Generates unique slugs for a document.
+#[derive(Debug, Default)]
+pub struct Slugger {
+    mode: SlugMode,
+    used: HashSet<String>,
+    /// Prefixes of numbered ids (`fn` for `fn-1`, `fn-2`, ...)
+    numbered: Vec<String>,
+}
+
+impl Slugger {
+    /// Create a new slugger using the given mode.
+    pub fn new(mode: SlugMode) -> Self {
+        Self {
+            mode,
+            used: HashSet::new(),
+            numbered: Vec::new(),
+        }
+    }
+
+    /// Mark an id as taken so generated slugs never collide with it.
+    pub fn reserve(&mut self, id: &str) {
+        self.used.insert(id.to_string());
+    }
+
+    /// Mark every id of the form `{prefix}-{number}` as taken, for anchors
+    /// numbered as they are rendered (footnotes, citations).
+    pub fn reserve_numbered(&mut self, prefix: &str) {
+        self.numbered.push(prefix.to_string());
+    }
+
+    fn is_taken(&self, id: &str) -> bool {
+        self.used.contains(id)
+            || self.numbered.iter().any(|prefix| {
+                id.strip_prefix(prefix.as_str())
+                    .and_then(|rest| rest.strip_prefix('-'))
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            })
+    }
+
+    /// Generate a unique slug for `text`, appending `-1`, `-2`, ... on collision.
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = slugify(text, self.mode);
+        let mut candidate = base.clone();
+        let mut n = 1;
+        while self.is_taken(&candidate) {
+            candidate = format!("{}-{}", base, n);
+            n += 1;
+        }
+        self.used.insert(candidate.clone());
+        candidate
+    }
+}
+
+/// Convert text to a slug suitable for an HTML id.
+pub fn slugify(text: &str, mode: SlugMode) -> String {
+    let mut slug = String::with_capacity(text.len());
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if c.is_whitespace() || c == '-' || c == '_' {
+            if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        } else if let Some(ascii) = transliterate(c).filter(|_| mode != SlugMode::Unicode) {
+            slug.push_str(ascii);
+        } else if c.is_alphanumeric() {
+            if mode == SlugMode::PercentEncode {
+                let mut buf = [0u8; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    slug.push_str(&format!("%{:02X}", byte));
+                }
+            } else {
+                slug.push(c);
+            }
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// ASCII replacement for a lowercase accented Latin letter.
+fn transliterate(c: char) -> Option<&'static str> {
+    let ascii = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transliterate_accents() {
+        assert_eq!(
+            slugify("Café Méthode", SlugMode::Transliterate),
+            "cafe-methode"
+        );
+        assert_eq!(slugify("Straße", SlugMode::Transliterate), "strasse");
+    }
+
+    #[test]
+    fn test_non_latin_modes() {
+        assert_eq!(slugify("数学 Intro", SlugMode::Transliterate), "数学-intro");
+        assert_eq!(slugify("数", SlugMode::PercentEncode), "%E6%95%B0");
+        assert_eq!(slugify("Café", SlugMode::Unicode), "café");
+    }
+
+    #[test]
+    fn test_slugger_deduplicates() {
+        let mut slugger = Slugger::new(SlugMode::Transliterate);
+        slugger.reserve("intro");
+        assert_eq!(slugger.slug("Intro"), "intro-1");
+        assert_eq!(slugger.slug("Intro"), "intro-2");
+        assert_eq!(slugger.slug("What?!"), "what");
+
+        slugger.reserve_numbered("fn");
+        assert_eq!(slugger.slug("Fn 2"), "fn-2-1");
+        assert_eq!(slugger.slug("Fn"), "fn");
+    }
+}
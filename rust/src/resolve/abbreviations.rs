@@ -0,0 +1,213 @@
+//! Abbreviation expansion for `*[ABBR]: Expansion` definitions.
+
+use crate::ast::{Block, Document, Inline};
+
+/// Wrap every whole-word occurrence of a defined abbreviation in plain text.
+pub fn expand_abbreviations(mut document: Document) -> Document {
+    if document.metadata.abbreviations.is_empty() {
+        return document;
+    }
+
+    // Longest first so that "HTML5" wins over "HTML"
+    let mut abbreviations: Vec<(String, String)> = document
+        .metadata
+        .abbreviations
+        .clone()
+        .into_iter()
+        .collect();
+    abbreviations.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+    document.blocks = document
+        .blocks
+        .into_iter()
+        .map(|block| expand_block(block, &abbreviations))
+        .collect();
+    for inlines in document.metadata.footnote_definitions.values_mut() {
+        *inlines = expand_inlines(std::mem::take(inlines), &abbreviations);
+    }
+
+    document
+}
+
+fn expand_block(block: Block, abbrs: &[(String, String)]) -> Block {
+    match block {
+        Block::Paragraph(inlines) => Block::Paragraph(expand_inlines(inlines, abbrs)),
+        Block::AttributedParagraph {
+            content,
+            id,
+            classes,
+        } => Block::AttributedParagraph {
+            content: expand_inlines(content, abbrs),
+            id,
+            classes,
+        },
+        Block::Heading {
+            level,
+            content,
+            label,
+        } => Block::Heading {
+            level,
+            content: expand_inlines(content, abbrs),
+            label,
+        },
+        Block::Environment {
+            kind,
+            label,
+            title,
+            proves,
+            content,
+            caption,
+        } => Block::Environment {
+            kind,
+            label,
+            title,
+            proves,
+            content: content
+                .into_iter()
+                .map(|b| expand_block(b, abbrs))
+                .collect(),
+            caption: caption.map(|c| expand_inlines(c, abbrs)),
+        },
+        Block::BlockQuote(blocks) => {
+            Block::BlockQuote(blocks.into_iter().map(|b| expand_block(b, abbrs)).collect())
+        }
+        Block::List {
+            ordered,
+            start,
+            items,
+        } => Block::List {
+            ordered,
+            start,
+            items: items
+                .into_iter()
+                .map(|item| crate::ast::ListItem {
+                    content: item
+                        .content
+                        .into_iter()
+                        .map(|b| expand_block(b, abbrs))
+                        .collect(),
+                    checked: item.checked,
+                })
+                .collect(),
+        },
+        Block::Table {
+            headers,
+            alignments,
+            widths,
+            rows,
+            label,
+            caption,
+        } => Block::Table {
+            headers: headers
+                .into_iter()
+                .map(|h| expand_inlines(h, abbrs))
+                .collect(),
+            alignments,
+            widths,
+            rows: rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|cell| expand_inlines(cell, abbrs))
+                        .collect()
+                })
+                .collect(),
+            label,
+            caption: caption.map(|c| expand_inlines(c, abbrs)),
+        },
+        other => other,
+    }
+}
+
+fn expand_inlines(inlines: Vec<Inline>, abbrs: &[(String, String)]) -> Vec<Inline> {
+    let mut result = Vec::with_capacity(inlines.len());
+
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => split_text(&text, abbrs, &mut result),
+            mut other => {
+                if let Some(inner) = other.children_mut() {
+                    *inner = expand_inlines(std::mem::take(inner), abbrs);
+                }
+                result.push(other);
+            }
+        }
+    }
+
+    result
+}
+
+/// Split a text node around abbreviation occurrences.
+fn split_text(text: &str, abbrs: &[(String, String)], out: &mut Vec<Inline>) {
+    let mut plain_start = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        let at_boundary = text[..i]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+
+        let matched = if at_boundary {
+            abbrs.iter().find(|(abbr, _)| {
+                text[i..].starts_with(abbr.as_str())
+                    && text[i + abbr.len()..]
+                        .chars()
+                        .next()
+                        .is_none_or(|c| !c.is_alphanumeric())
+            })
+        } else {
+            None
+        };
+
+        if let Some((abbr, title)) = matched {
+            if plain_start < i {
+                out.push(Inline::Text(text[plain_start..i].to_string()));
+            }
+            out.push(Inline::Abbreviation {
+                abbr: abbr.clone(),
+                title: title.clone(),
+            });
+            i += abbr.len();
+            plain_start = i;
+        } else {
+            i += text[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    if plain_start < text.len() {
+        out.push(Inline::Text(text[plain_start..].to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_text_whole_words() {
+        let abbrs = vec![("HTML".to_string(), "HyperText Markup Language".to_string())];
+        let mut out = Vec::new();
+        split_text("HTML and XHTML, HTML.", &abbrs, &mut out);
+
+        let count = out
+            .iter()
+            .filter(|i| matches!(i, Inline::Abbreviation { .. }))
+            .count();
+        assert_eq!(count, 2);
+        assert_eq!(out[1], Inline::Text(" and XHTML, ".to_string()));
+    }
+
+    #[test]
+    fn test_expand_in_nested_inlines() {
+        let abbrs = vec![("HTML".to_string(), "HyperText Markup Language".to_string())];
+        let abbr = Inline::Abbreviation {
+            abbr: "HTML".to_string(),
+            title: "HyperText Markup Language".to_string(),
+        };
+        for wrap in [Inline::SmallCaps, Inline::Subscript, Inline::Superscript] {
+            let out = expand_inlines(vec![wrap(vec![Inline::Text("HTML".to_string())])], &abbrs);
+            assert_eq!(out, vec![wrap(vec![abbr.clone()])]);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Synthesized labels for unlabeled numbered elements.
+
+use crate::ast::{Block, Document, EnvironmentKind};
+use crate::resolve::numbering::align_line_labels;
+use std::collections::{HashMap, HashSet};
+
+/// Give every unlabeled numbered element a deterministic label such as
+/// `eq:auto-3` or `thm:auto-1`.
+///
+/// The number is the element's position among all elements sharing the same
+/// prefix, so identical input always yields identical labels. A number whose
+/// label is already in the document is skipped for the next free one.
+pub fn assign_auto_labels(mut document: Document) -> Document {
+    let mut labels = AutoLabels::new(&document.blocks);
+    label_blocks(&mut document.blocks, false, &mut labels);
+    document
+}
+
+/// Like [`assign_auto_labels`], but only for display equations, so every
+/// numbered equation shows its number and can be referenced.
+pub fn assign_equation_labels(mut document: Document) -> Document {
+    let mut labels = AutoLabels::new(&document.blocks);
+    label_blocks(&mut document.blocks, true, &mut labels);
+    document
+}
+
+/// Position counters per prefix, and every label in use.
+struct AutoLabels {
+    counters: HashMap<&'static str, u32>,
+    taken: HashSet<String>,
+}
+
+impl AutoLabels {
+    fn new(blocks: &[Block]) -> Self {
+        let mut taken = HashSet::new();
+        collect_labels(blocks, &mut taken);
+        Self {
+            counters: HashMap::new(),
+            taken,
+        }
+    }
+
+    /// Count the element and label it if it has no label.
+    fn assign(&mut self, label: &mut Option<String>, prefix: &'static str) {
+        let count = self.counters.entry(prefix).or_insert(0);
+        *count += 1;
+        if label.is_some() {
+            return;
+        }
+        let mut number = *count;
+        while self.taken.contains(&format!("{}:auto-{}", prefix, number)) {
+            number += 1;
+        }
+        let new = format!("{}:auto-{}", prefix, number);
+        self.taken.insert(new.clone());
+        *label = Some(new);
+    }
+}
+
+/// Labels already given to headings, equations, environments and tables.
+fn collect_labels(blocks: &[Block], taken: &mut HashSet<String>) {
+    for block in blocks {
+        match block {
+            Block::Heading { label, .. } | Block::Table { label, .. } => {
+                taken.extend(label.iter().cloned());
+            }
+            Block::DisplayMath { label, content, .. } => {
+                taken.extend(label.iter().cloned());
+                taken.extend(align_line_labels(content));
+            }
+            Block::Environment { label, content, .. } => {
+                taken.extend(label.iter().cloned());
+                collect_labels(content, taken);
+            }
+            Block::BlockQuote(blocks) => collect_labels(blocks, taken),
+            Block::List { items, .. } => {
+                for item in items {
+                    collect_labels(&item.content, taken);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn label_blocks(blocks: &mut [Block], equations_only: bool, labels: &mut AutoLabels) {
+    for block in blocks {
+        match block {
+            Block::DisplayMath {
+                label,
+                content,
+                numbered: true,
+            } if align_line_labels(content).is_empty() => {
+                labels.assign(label, "eq");
+            }
+            Block::Environment {
+                kind,
+                label,
+                content,
+                ..
+            } => {
+                if let Some(prefix) = environment_prefix(kind).filter(|_| !equations_only) {
+                    labels.assign(label, prefix);
+                }
+                label_blocks(content, equations_only, labels);
+            }
+            Block::Table { label, .. } if !equations_only => labels.assign(label, "tab"),
+            Block::BlockQuote(blocks) => label_blocks(blocks, equations_only, labels),
+            Block::List { items, .. } => {
+                for item in items {
+                    label_blocks(&mut item.content, equations_only, labels);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Label prefix for numbered environment kinds.
+fn environment_prefix(kind: &EnvironmentKind) -> Option<&'static str> {
+    let prefix = match kind {
+        EnvironmentKind::Theorem => "thm",
+        EnvironmentKind::Lemma => "lem",
+        EnvironmentKind::Proposition => "prop",
+        EnvironmentKind::Corollary => "cor",
+        EnvironmentKind::Conjecture => "conj",
+        EnvironmentKind::Axiom => "ax",
+        EnvironmentKind::Definition => "def",
+        EnvironmentKind::Example => "ex",
+        EnvironmentKind::Remark => "rem",
+        EnvironmentKind::Exercise => "exer",
+        EnvironmentKind::Figure => "fig",
+        EnvironmentKind::Table => "tab",
+        EnvironmentKind::Algorithm => "alg",
+        EnvironmentKind::Listing => "lst",
+        _ => return None,
+    };
+    Some(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+    use crate::resolve::{resolve, ResolveConfig};
+
+    #[test]
+    fn test_auto_labels_registered() {
+        let input = r#"::: theorem
+Unlabeled.
+:::
+
+::: theorem {#thm:named}
+Labeled.
+:::
+
+::: theorem
+Also unlabeled.
+:::
+
+$$
+x = 1
+$$
+"#;
+        let config = ResolveConfig {
+            auto_label: true,
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+
+        assert_eq!(resolved.labels["thm:auto-1"].display, "Theorem 1");
+        assert_eq!(resolved.labels["thm:auto-3"].display, "Theorem 3");
+        assert!(resolved.labels.contains_key("thm:named"));
+        assert_eq!(resolved.labels["eq:auto-1"].display, "(1)");
+
+        // Stable across runs
+        let again = resolve(parse(input).unwrap(), &config).unwrap();
+        let mut a: Vec<_> = resolved.labels.keys().collect();
+        let mut b: Vec<_> = again.labels.keys().collect();
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+
+        let plain = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        assert!(!plain.labels.contains_key("thm:auto-1"));
+    }
+
+    #[test]
+    fn test_number_all_equations() {
+        let input = "$$\nE = mc^2\n$$ {#eq:energy}\n\n::: theorem\nBody.\n:::\n\n$$\nF = ma\n$$";
+        let config = ResolveConfig {
+            number_all_equations: true,
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+
+        assert_eq!(resolved.env_numbers["eq:energy"], "1");
+        assert_eq!(resolved.env_numbers["eq:auto-2"], "2");
+        assert_eq!(resolved.labels["eq:auto-2"].display, "(2)");
+        assert!(!resolved.labels.contains_key("thm:auto-1"));
+
+        let html = crate::render_html(&resolved, &crate::HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<span class="mdaequation-number">(2)</span>"#));
+    }
+
+    #[test]
+    fn test_auto_labels_skip_taken() {
+        let input = "::: theorem\nA.\n:::\n\n::: theorem\nB.\n:::\n\n::: theorem {#thm:auto-1}\nC.\n:::\n\n$$\nx\n$$\n\n$$\ny\n$$ {#eq:auto-1}";
+        let config = ResolveConfig {
+            auto_label: true,
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+        assert_eq!(resolved.labels["thm:auto-1"].display, "Theorem 3");
+        assert_eq!(resolved.labels["thm:auto-2"].display, "Theorem 1");
+        assert_eq!(resolved.labels["thm:auto-3"].display, "Theorem 2");
+
+        let config = ResolveConfig {
+            number_all_equations: true,
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+        assert_eq!(resolved.labels["eq:auto-1"].display, "(2)");
+        assert_eq!(resolved.labels["eq:auto-2"].display, "(1)");
+    }
+}
@@ -1,6 +1,6 @@
 //! Citation resolution.
 
-use crate::ast::{BibEntry, Block, Document, Inline};
+use crate::ast::{BibEntry, Block, Document, FootnoteKind, Inline};
 use crate::error::{ResolutionError, Result};
 use crate::resolve::ResolveConfig;
 use std::collections::HashMap;
@@ -26,22 +26,79 @@ pub fn resolve_citations(
     Ok(document)
 }
 
+/// Append `a`, `b`, ... to the years of cited entries that share both
+/// author surnames and year, so author-year citations stay distinguishable.
+///
+/// Suffixes follow `order` (first citation gets `a`).
+pub fn disambiguate_years(bibliography: &mut HashMap<String, BibEntry>, order: &[String]) {
+    let mut groups: HashMap<(Vec<String>, String), Vec<&String>> = HashMap::new();
+    let mut group_order = Vec::new();
+
+    for key in order {
+        let Some(entry) = bibliography.get(key) else {
+            continue;
+        };
+        let Some(ref year) = entry.year else {
+            continue;
+        };
+        let surnames = entry
+            .authors
+            .iter()
+            .map(|a| surname(a).to_lowercase())
+            .collect();
+        let group_key = (surnames, year.clone());
+        let group = groups.entry(group_key.clone()).or_default();
+        if group.is_empty() {
+            group_order.push(group_key);
+        }
+        group.push(key);
+    }
+
+    for group_key in group_order {
+        let keys = &groups[&group_key];
+        if keys.len() < 2 {
+            continue;
+        }
+        for (i, key) in keys.iter().enumerate() {
+            if let Some(entry) = bibliography.get_mut(*key) {
+                let suffix = (b'a' + (i % 26) as u8) as char;
+                entry.year = Some(format!("{}{}", group_key.1, suffix));
+            }
+        }
+    }
+}
+
+/// Surname of a BibTeX author name ("Last, First" or "First Last").
+fn surname(name: &str) -> &str {
+    if let Some(comma) = name.find(',') {
+        name[..comma].trim()
+    } else {
+        name.rsplit(' ').next().unwrap_or(name)
+    }
+}
+
 /// Collect all citation keys used in the document.
-fn collect_citation_keys(document: &Document) -> Vec<String> {
+pub(crate) fn collect_citation_keys(document: &Document) -> Vec<String> {
     let mut keys = Vec::new();
 
     for block in &document.blocks {
         collect_block_citation_keys(block, &mut keys);
     }
+    for inlines in document.metadata.footnote_definitions.values() {
+        collect_inline_citation_keys(inlines, &mut keys);
+    }
 
     keys.sort();
     keys.dedup();
     keys
 }
 
-fn collect_block_citation_keys(block: &Block, keys: &mut Vec<String>) {
+pub(crate) fn collect_block_citation_keys(block: &Block, keys: &mut Vec<String>) {
     match block {
-        Block::Paragraph(inlines) => collect_inline_citation_keys(inlines, keys),
+        Block::Paragraph(inlines)
+        | Block::AttributedParagraph {
+            content: inlines, ..
+        } => collect_inline_citation_keys(inlines, keys),
         Block::Heading { content, .. } => collect_inline_citation_keys(content, keys),
         Block::Environment {
             content, caption, ..
@@ -53,7 +110,7 @@ fn collect_block_citation_keys(block: &Block, keys: &mut Vec<String>) {
                 collect_inline_citation_keys(caption, keys);
             }
         }
-        Block::BlockQuote(blocks) => {
+        Block::BlockQuote(blocks) | Block::Abstract(blocks) => {
             for block in blocks {
                 collect_block_citation_keys(block, keys);
             }
@@ -83,66 +140,116 @@ fn collect_block_citation_keys(block: &Block, keys: &mut Vec<String>) {
                 collect_inline_citation_keys(caption, keys);
             }
         }
+        Block::DescriptionList(items) => {
+            for item in items {
+                collect_inline_citation_keys(&item.term, keys);
+                for block in &item.description {
+                    collect_block_citation_keys(block, keys);
+                }
+            }
+        }
         _ => {}
     }
 }
 
-fn collect_inline_citation_keys(inlines: &[Inline], keys: &mut Vec<String>) {
+pub(crate) fn collect_inline_citation_keys(inlines: &[Inline], keys: &mut Vec<String>) {
     for inline in inlines {
         match inline {
             Inline::Citation(cite) => {
                 keys.extend(cite.keys.iter().cloned());
             }
-            Inline::Emphasis(inner) | Inline::Strong(inner) | Inline::Strikethrough(inner) => {
-                collect_inline_citation_keys(inner, keys);
-            }
-            Inline::Link { content, .. } => {
-                collect_inline_citation_keys(content, keys);
+            _ => {
+                if let Some(inner) = inline.children() {
+                    collect_inline_citation_keys(inner, keys);
+                }
             }
-            _ => {}
         }
     }
 }
 
 /// Get the list of citations in order of first appearance (for bibliography generation).
+///
+/// Citations in a footnote definition (`[^id]: ...`) count where the note is
+/// referenced. Keys listed in front matter `nocite` follow the cited ones.
 pub fn get_citation_order(document: &Document) -> Vec<String> {
-    let mut keys = Vec::new();
-    let mut seen = std::collections::HashSet::new();
+    let mut order = CitationOrder {
+        keys: Vec::new(),
+        seen: std::collections::HashSet::new(),
+        definitions: &document.metadata.footnote_definitions,
+        visited: std::collections::HashSet::new(),
+    };
 
     for block in &document.blocks {
-        collect_block_citation_order(block, &mut keys, &mut seen);
+        collect_block_citation_order(block, &mut order);
+    }
+    let CitationOrder {
+        mut keys, mut seen, ..
+    } = order;
+    for key in &document.metadata.nocite {
+        if key != "*" && seen.insert(key.clone()) {
+            keys.push(key.clone());
+        }
     }
 
     keys
 }
 
-fn collect_block_citation_order(
-    block: &Block,
-    keys: &mut Vec<String>,
-    seen: &mut std::collections::HashSet<String>,
-) {
+/// State for [`get_citation_order`].
+struct CitationOrder<'a> {
+    keys: Vec<String>,
+    seen: std::collections::HashSet<String>,
+    definitions: &'a HashMap<String, Vec<Inline>>,
+    /// Footnote definitions already walked, so a note is counted once
+    visited: std::collections::HashSet<&'a str>,
+}
+
+fn collect_block_citation_order(block: &Block, order: &mut CitationOrder<'_>) {
     match block {
-        Block::Paragraph(inlines) => collect_inline_citation_order(inlines, keys, seen),
-        Block::Heading { content, .. } => collect_inline_citation_order(content, keys, seen),
+        Block::Paragraph(inlines)
+        | Block::AttributedParagraph {
+            content: inlines, ..
+        } => collect_inline_citation_order(inlines, order),
+        Block::Heading { content, .. } => collect_inline_citation_order(content, order),
         Block::Environment {
             content, caption, ..
         } => {
             for block in content {
-                collect_block_citation_order(block, keys, seen);
+                collect_block_citation_order(block, order);
             }
             if let Some(caption) = caption {
-                collect_inline_citation_order(caption, keys, seen);
+                collect_inline_citation_order(caption, order);
             }
         }
-        Block::BlockQuote(blocks) => {
+        Block::BlockQuote(blocks) | Block::Abstract(blocks) => {
             for block in blocks {
-                collect_block_citation_order(block, keys, seen);
+                collect_block_citation_order(block, order);
             }
         }
         Block::List { items, .. } => {
             for item in items {
                 for block in &item.content {
-                    collect_block_citation_order(block, keys, seen);
+                    collect_block_citation_order(block, order);
+                }
+            }
+        }
+        Block::Table {
+            headers,
+            rows,
+            caption,
+            ..
+        } => {
+            for cell in headers.iter().chain(rows.iter().flatten()) {
+                collect_inline_citation_order(cell, order);
+            }
+            if let Some(caption) = caption {
+                collect_inline_citation_order(caption, order);
+            }
+        }
+        Block::DescriptionList(items) => {
+            for item in items {
+                collect_inline_citation_order(&item.term, order);
+                for block in &item.description {
+                    collect_block_citation_order(block, order);
                 }
             }
         }
@@ -150,27 +257,29 @@ fn collect_block_citation_order(
     }
 }
 
-fn collect_inline_citation_order(
-    inlines: &[Inline],
-    keys: &mut Vec<String>,
-    seen: &mut std::collections::HashSet<String>,
-) {
+fn collect_inline_citation_order(inlines: &[Inline], order: &mut CitationOrder<'_>) {
     for inline in inlines {
         match inline {
             Inline::Citation(cite) => {
                 for key in &cite.keys {
-                    if seen.insert(key.clone()) {
-                        keys.push(key.clone());
+                    if order.seen.insert(key.clone()) {
+                        order.keys.push(key.clone());
                     }
                 }
             }
-            Inline::Emphasis(inner) | Inline::Strong(inner) | Inline::Strikethrough(inner) => {
-                collect_inline_citation_order(inner, keys, seen);
+            Inline::Footnote(FootnoteKind::Reference(id)) => {
+                let definitions = order.definitions;
+                if let Some((id, inner)) = definitions.get_key_value(id) {
+                    if order.visited.insert(id) {
+                        collect_inline_citation_order(inner, order);
+                    }
+                }
             }
-            Inline::Link { content, .. } => {
-                collect_inline_citation_order(content, keys, seen);
+            _ => {
+                if let Some(inner) = inline.children() {
+                    collect_inline_citation_order(inner, order);
+                }
             }
-            _ => {}
         }
     }
 }
@@ -187,4 +296,129 @@ mod tests {
         let keys = collect_citation_keys(&doc);
         assert_eq!(keys, vec!["knuth1984", "lamport1994"]);
     }
+
+    #[test]
+    fn test_disambiguate_years() {
+        let entry = |key: &str, author: &str, year: &str| BibEntry {
+            key: key.to_string(),
+            authors: vec![author.to_string()],
+            year: Some(year.to_string()),
+            ..Default::default()
+        };
+        let mut bib: HashMap<String, BibEntry> = [
+            entry("smithB", "Smith, John", "2020"),
+            entry("smithA", "John Smith", "2020"),
+            entry("smith21", "Smith, John", "2021"),
+            entry("jones", "Jones, Ann", "2020"),
+        ]
+        .into_iter()
+        .map(|e| (e.key.clone(), e))
+        .collect();
+
+        let order: Vec<String> = ["smithB", "jones", "smithA", "smith21"]
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        disambiguate_years(&mut bib, &order);
+
+        assert_eq!(bib["smithB"].year.as_deref(), Some("2020a"));
+        assert_eq!(bib["smithA"].year.as_deref(), Some("2020b"));
+        assert_eq!(bib["smith21"].year.as_deref(), Some("2021"));
+        assert_eq!(bib["jones"].year.as_deref(), Some("2020"));
+    }
+
+    #[test]
+    fn test_cited_keys_nested() {
+        let input = r#"Main text^[As argued by @footnote2020.].
+
+| A | B |
+|---|---|
+| 1 | 2 |
+Table: Data from [@caption2019]. {#tab:data}
+"#;
+        let doc = parse(input).unwrap();
+        let keys = doc.cited_keys();
+        assert!(keys.contains("footnote2020"));
+        assert!(keys.contains("caption2019"));
+        assert_eq!(keys.len(), 2);
+    }
+
+    #[test]
+    fn test_citation_order_follows_footnote_references() {
+        let input = "First[^a] and [@body2020].\n\n[^a]: See [@defined2019].\n\n[@last2022]";
+        let doc = parse(input).unwrap();
+        assert_eq!(
+            get_citation_order(&doc),
+            vec!["defined2019", "body2020", "last2022"]
+        );
+        assert!(doc.cited_keys().contains("defined2019"));
+    }
+
+    #[test]
+    fn test_citation_order_includes_captions_and_footnotes() {
+        let input = "Body^[Noted in [@note2021].]\n\n\
+                     ::: figure {#fig:plot}\n![Plot](plot.png)\n\nAdapted from [@caption2019].\n:::\n\n\
+                     [@body2020]";
+        let doc = parse(input).unwrap();
+        assert_eq!(
+            get_citation_order(&doc),
+            vec!["note2021", "caption2019", "body2020"]
+        );
+
+        let bibliography: HashMap<String, BibEntry> = get_citation_order(&doc)
+            .into_iter()
+            .map(|key| {
+                let entry = BibEntry {
+                    key: key.clone(),
+                    title: Some(format!("Title of {}", key)),
+                    ..Default::default()
+                };
+                (key, entry)
+            })
+            .collect();
+        let config = crate::resolve::ResolveConfig {
+            bibliography: Some(std::sync::Arc::new(bibliography)),
+            ..Default::default()
+        };
+        let resolved = crate::resolve::resolve(doc, &config).unwrap();
+        let html = crate::render_html(&resolved, &crate::HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<li id="bib-caption2019">"#));
+        assert!(html.contains(r#"<li id="bib-note2021">"#));
+    }
+
+    #[test]
+    fn test_nocite() {
+        let input = "+++\nnocite = [\"knuth1984\"]\n+++\n\nAs shown [@lamport1994].";
+        let bibliography: HashMap<String, BibEntry> = ["knuth1984", "lamport1994", "other2000"]
+            .into_iter()
+            .map(|key| {
+                let entry = BibEntry {
+                    key: key.to_string(),
+                    title: Some(format!("Title of {}", key)),
+                    ..Default::default()
+                };
+                (key.to_string(), entry)
+            })
+            .collect();
+        let config = crate::resolve::ResolveConfig {
+            bibliography: Some(std::sync::Arc::new(bibliography)),
+            ..Default::default()
+        };
+
+        let resolved = crate::resolve::resolve(parse(input).unwrap(), &config).unwrap();
+        assert_eq!(
+            get_citation_order(&resolved.document),
+            vec!["lamport1994", "knuth1984"]
+        );
+        let html = crate::render_html(&resolved, &crate::HtmlConfig::default()).unwrap();
+        assert!(html.contains(r#"<li id="bib-knuth1984">"#));
+        assert!(!html.contains(r#"<li id="bib-other2000">"#));
+
+        let input = input.replace("\"knuth1984\"", "\"*\"");
+        let resolved = crate::resolve::resolve(parse(&input).unwrap(), &config).unwrap();
+        assert_eq!(
+            get_citation_order(&resolved.document),
+            vec!["lamport1994", "knuth1984", "other2000"]
+        );
+    }
 }
@@ -0,0 +1,318 @@
+//! Resolution that collects every problem instead of stopping at the first.
+//!
+//! Editors want all parse, math, reference and citation problems at once,
+//! each with the source line it came from, so they can be listed next to
+//! the text.
+
+use super::citations::{collect_block_citation_keys, collect_inline_citation_keys};
+use super::math_check::validate_math_spans;
+use super::{resolve, ResolveConfig};
+use crate::ast::{Block, Document, Inline, ResolvedDocument};
+use crate::error::{Error, ParseError, ResolutionError};
+use crate::parser::parse;
+use std::fmt;
+
+/// A problem found in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// 1-based source line the problem was found on, or `None` when it
+    /// belongs to no single line (a bibliography that could not be read).
+    pub line: Option<usize>,
+    /// What is wrong, in the words of the corresponding error.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Parse and resolve `source`, collecting every problem found.
+///
+/// Math is always checked, and unknown references and citations are
+/// reported whatever the strictness settings, including those inside
+/// footnote definitions. Each is placed on the line of the block or
+/// footnote definition it was found in, as recorded by the parser. The
+/// resolved document is returned unless parsing or resolution failed
+/// outright; even then the failure is the last diagnostic.
+pub fn resolve_with_diagnostics(
+    source: &str,
+    config: &ResolveConfig,
+) -> (Option<ResolvedDocument>, Vec<Diagnostic>) {
+    let mut diagnostics = Vec::new();
+
+    let document = match parse(source) {
+        Ok(document) => document,
+        Err(error) => {
+            let line = match &error {
+                Error::Parse(ParseError::Syntax { line, .. }) => Some(*line),
+                _ => None,
+            };
+            diagnostics.push(Diagnostic {
+                line,
+                message: error.to_string(),
+            });
+            return (None, diagnostics);
+        }
+    };
+    let locator = Locator::new(source, &document);
+
+    for (index, content, diagnostic) in validate_math_spans(&document) {
+        let first_line = content.lines().map(str::trim).find(|l| !l.is_empty());
+        diagnostics.push(Diagnostic {
+            line: locator.block_line(index, first_line.unwrap_or_default()),
+            message: diagnostic.to_string(),
+        });
+    }
+
+    // References and citations in source order, each with its line
+    let mut references = Vec::new();
+    let mut citations = Vec::new();
+    for (index, block) in document.blocks.iter().enumerate() {
+        let mut labels = Vec::new();
+        collect_references(std::slice::from_ref(block), &mut labels);
+        references.extend(labels.into_iter().map(|label| {
+            let line = locator.block_line(index, &format!("@{}", label));
+            (label, line)
+        }));
+        let mut keys = Vec::new();
+        collect_block_citation_keys(block, &mut keys);
+        citations.extend(keys.into_iter().map(|key| {
+            let line = locator.block_line(index, &format!("@{}", key));
+            (key, line)
+        }));
+    }
+    for (id, inlines) in &document.metadata.footnote_definitions {
+        let start = document.metadata.footnote_lines.get(id).copied();
+        let mut labels = Vec::new();
+        collect_reference_inlines(inlines, &mut labels);
+        references.extend(labels.into_iter().map(|label| {
+            let line = start.and_then(|start| locator.footnote_line(start, &format!("@{}", label)));
+            (label, line)
+        }));
+        let mut keys = Vec::new();
+        collect_inline_citation_keys(inlines, &mut keys);
+        citations.extend(keys.into_iter().map(|key| {
+            let line = start.and_then(|start| locator.footnote_line(start, &format!("@{}", key)));
+            (key, line)
+        }));
+    }
+
+    // Everything checked here is reported above or below, not as a failure
+    let lenient = ResolveConfig {
+        strict_citations: false,
+        strict_references: false,
+        validate_math: false,
+        ..config.clone()
+    };
+    let resolved = match resolve(document, &lenient) {
+        Ok(resolved) => resolved,
+        Err(error) => {
+            diagnostics.push(Diagnostic {
+                line: None,
+                message: error.to_string(),
+            });
+            return (None, diagnostics);
+        }
+    };
+
+    for warning in &resolved.warnings {
+        diagnostics.push(Diagnostic {
+            line: None,
+            message: warning.clone(),
+        });
+    }
+
+    let unknown_references = first_occurrences(references)
+        .filter(|(label, _)| !resolved.labels.contains_key(label))
+        .map(|(label, line)| (ResolutionError::UnknownReference(label), line));
+    let unknown_citations = first_occurrences(citations)
+        .filter(|(key, _)| !resolved.citations.contains_key(key))
+        .map(|(key, line)| (ResolutionError::UnknownCitation(key), line));
+    for (error, line) in unknown_references.chain(unknown_citations) {
+        diagnostics.push(Diagnostic {
+            line,
+            message: error.to_string(),
+        });
+    }
+
+    diagnostics.sort_by_key(|d| d.line.unwrap_or(usize::MAX));
+    (Some(resolved), diagnostics)
+}
+
+/// The earliest occurrence of each name, in line order.
+fn first_occurrences(
+    mut found: Vec<(String, Option<usize>)>,
+) -> impl Iterator<Item = (String, Option<usize>)> {
+    found.sort_by_key(|(_, line)| line.unwrap_or(usize::MAX));
+    let mut seen = Vec::new();
+    found.into_iter().filter(move |(name, _)| {
+        let new = !seen.contains(name);
+        if new {
+            seen.push(name.clone());
+        }
+        new
+    })
+}
+
+/// Maps parsed elements back to source lines, using the start lines the
+/// parser recorded for top-level blocks and footnote definitions.
+struct Locator<'a> {
+    lines: Vec<&'a str>,
+    block_lines: &'a [usize],
+}
+
+impl<'a> Locator<'a> {
+    fn new(source: &'a str, document: &'a Document) -> Self {
+        Self {
+            lines: source.lines().collect(),
+            block_lines: &document.block_lines,
+        }
+    }
+
+    /// The line within top-level block `index` that contains `needle`,
+    /// falling back to the block's first line.
+    fn block_line(&self, index: usize, needle: &str) -> Option<usize> {
+        let start = *self.block_lines.get(index)?;
+        let end = self
+            .block_lines
+            .get(index + 1)
+            .map_or(self.lines.len(), |next| next - 1);
+        Some(self.find(start, end, needle))
+    }
+
+    /// The line within the footnote definition starting on `start` (it
+    /// continues over blank and indented lines) that contains `needle`.
+    fn footnote_line(&self, start: usize, needle: &str) -> Option<usize> {
+        let rest = self.lines.get(start..)?;
+        let continued = rest
+            .iter()
+            .take_while(|line| line.trim().is_empty() || line.starts_with([' ', '\t']))
+            .count();
+        Some(self.find(start, start + continued, needle))
+    }
+
+    /// The first of the 1-based lines `start..=end` containing `needle`, or
+    /// `start`.
+    fn find(&self, start: usize, end: usize, needle: &str) -> usize {
+        let end = end.min(self.lines.len()).max(start);
+        let range = self.lines.get(start - 1..end).unwrap_or_default();
+        let offset = range.iter().position(|line| line.contains(needle));
+        start + offset.unwrap_or(0)
+    }
+}
+
+fn collect_references(blocks: &[Block], labels: &mut Vec<String>) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            }
+            | Block::Heading {
+                content: inlines, ..
+            } => collect_reference_inlines(inlines, labels),
+            Block::Environment {
+                content, caption, ..
+            } => {
+                collect_references(content, labels);
+                if let Some(caption) = caption {
+                    collect_reference_inlines(caption, labels);
+                }
+            }
+            Block::BlockQuote(blocks) | Block::Abstract(blocks) => {
+                collect_references(blocks, labels)
+            }
+            Block::List { items, .. } => {
+                for item in items {
+                    collect_references(&item.content, labels);
+                }
+            }
+            Block::Table {
+                headers,
+                rows,
+                caption,
+                ..
+            } => {
+                for cell in headers.iter().chain(rows.iter().flatten()) {
+                    collect_reference_inlines(cell, labels);
+                }
+                if let Some(caption) = caption {
+                    collect_reference_inlines(caption, labels);
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    collect_reference_inlines(&item.term, labels);
+                    collect_references(&item.description, labels);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_reference_inlines(inlines: &[Inline], labels: &mut Vec<String>) {
+    for inline in inlines {
+        match inline {
+            Inline::Reference { label, .. } => labels.push(label.clone()),
+            _ => {
+                if let Some(inner) = inline.children() {
+                    collect_reference_inlines(inner, labels);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_errors_two_diagnostics() {
+        let source = "# Intro {#sec:intro}\n\n\
+                      Broken math $x}$ here.\n\n\
+                      See @sec:intro and @sec:missing.";
+        let (resolved, diagnostics) = resolve_with_diagnostics(source, &ResolveConfig::default());
+        assert!(resolved.is_some());
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    line: Some(3),
+                    message: "inline math 1: unmatched `}`".to_string(),
+                },
+                Diagnostic {
+                    line: Some(5),
+                    message: "Unknown reference label: sec:missing".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_use_parsed_positions() {
+        // The first textual `@sec:missing` is inside code, not the reference
+        let source = "+++\ntitle = \"T\"\n+++\n\n```\n@sec:missing\n```\n\n\
+                      See @sec:missing.\n\nNote[^n].\n\n[^n]: Also @sec:gone.";
+        let (_, diagnostics) = resolve_with_diagnostics(source, &ResolveConfig::default());
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    line: Some(9),
+                    message: "Unknown reference label: sec:missing".to_string(),
+                },
+                Diagnostic {
+                    line: Some(13),
+                    message: "Unknown reference label: sec:gone".to_string(),
+                },
+            ]
+        );
+    }
+}
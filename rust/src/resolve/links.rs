@@ -0,0 +1,143 @@
+//! Reference-style links (`[text][id]`) resolved against link definitions.
+
+use crate::ast::{Block, Document, Inline};
+use crate::parser::normalize_link_id;
+use std::collections::HashMap;
+
+type Definitions = HashMap<String, (String, Option<String>)>;
+
+/// Turn every reference-style link into a link, or back into its source
+/// text when its id has no definition.
+pub fn resolve_link_references(mut document: Document) -> Document {
+    let definitions = std::mem::take(&mut document.metadata.link_definitions);
+    resolve_blocks(&mut document.blocks, &definitions);
+    for inlines in document.metadata.footnote_definitions.values_mut() {
+        resolve_inlines(inlines, &definitions);
+    }
+    document.metadata.link_definitions = definitions;
+    document
+}
+
+fn resolve_blocks(blocks: &mut [Block], definitions: &Definitions) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            }
+            | Block::Heading {
+                content: inlines, ..
+            } => resolve_inlines(inlines, definitions),
+            Block::Environment {
+                content, caption, ..
+            } => {
+                resolve_blocks(content, definitions);
+                if let Some(caption) = caption {
+                    resolve_inlines(caption, definitions);
+                }
+            }
+            Block::BlockQuote(blocks) | Block::Abstract(blocks) => {
+                resolve_blocks(blocks, definitions)
+            }
+            Block::List { items, .. } => {
+                for item in items {
+                    resolve_blocks(&mut item.content, definitions);
+                }
+            }
+            Block::Table {
+                headers,
+                rows,
+                caption,
+                ..
+            } => {
+                for cell in headers.iter_mut().chain(rows.iter_mut().flatten()) {
+                    resolve_inlines(cell, definitions);
+                }
+                if let Some(caption) = caption {
+                    resolve_inlines(caption, definitions);
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    resolve_inlines(&mut item.term, definitions);
+                    resolve_blocks(&mut item.description, definitions);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resolve_inlines(inlines: &mut Vec<Inline>, definitions: &Definitions) {
+    let mut result = Vec::with_capacity(inlines.len());
+    for mut inline in inlines.drain(..) {
+        if let Some(inner) = inline.children_mut() {
+            resolve_inlines(inner, definitions);
+        }
+
+        match inline {
+            Inline::LinkReference {
+                content,
+                id,
+                closing,
+            } => match definitions.get(&normalize_link_id(&id)) {
+                Some((url, title)) => result.push(Inline::Link {
+                    url: url.clone(),
+                    title: title.clone(),
+                    content,
+                }),
+                None => {
+                    result.push(Inline::Text("[".to_string()));
+                    result.extend(content);
+                    result.push(Inline::Text(closing));
+                }
+            },
+            other => result.push(other),
+        }
+    }
+    *inlines = result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_reference_style_links() {
+        let input = "See [the docs][Docs], [Docs][], [docs] and [*missing*][nope].\n\n\
+                     [DOCS]: https://example.com/docs \"Documentation\"";
+        let doc = resolve_link_references(parse(input).unwrap());
+        let Block::Paragraph(inlines) = &doc.blocks[0] else {
+            panic!("Expected paragraph");
+        };
+        assert_eq!(doc.blocks.len(), 1);
+
+        let link = |text: &str| Inline::Link {
+            url: "https://example.com/docs".to_string(),
+            title: Some("Documentation".to_string()),
+            content: vec![Inline::Text(text.to_string())],
+        };
+        let links: Vec<&Inline> = inlines
+            .iter()
+            .filter(|inline| matches!(inline, Inline::Link { .. }))
+            .collect();
+        assert_eq!(links, [&link("the docs"), &link("Docs"), &link("docs")]);
+
+        let html = crate::render_html(
+            &crate::resolve(parse(input).unwrap(), &Default::default()).unwrap(),
+            &crate::HtmlConfig::default(),
+        )
+        .unwrap();
+        assert!(html.contains("[<em>missing</em>][nope]."));
+
+        // An undefined shortcut reference inside escaped `\[[toc]]` reads
+        // as the literal marker again
+        let html = crate::render_html(
+            &crate::resolve(parse("\\[[toc]]").unwrap(), &Default::default()).unwrap(),
+            &crate::HtmlConfig::default(),
+        )
+        .unwrap();
+        assert!(html.contains("<p>[[toc]]</p>"), "{html}");
+    }
+}
@@ -18,6 +18,9 @@ pub fn expand_macros(mut document: Document) -> Result<Document> {
         .into_iter()
         .map(|block| expand_block_macros(block, &macros))
         .collect();
+    for inlines in document.metadata.footnote_definitions.values_mut() {
+        *inlines = expand_inlines_macros(std::mem::take(inlines), &macros);
+    }
 
     Ok(document)
 }
@@ -25,6 +28,15 @@ pub fn expand_macros(mut document: Document) -> Result<Document> {
 fn expand_block_macros(block: Block, macros: &HashMap<String, Macro>) -> Block {
     match block {
         Block::Paragraph(inlines) => Block::Paragraph(expand_inlines_macros(inlines, macros)),
+        Block::AttributedParagraph {
+            content,
+            id,
+            classes,
+        } => Block::AttributedParagraph {
+            content: expand_inlines_macros(content, macros),
+            id,
+            classes,
+        },
         Block::Heading {
             level,
             content,
@@ -34,18 +46,27 @@ fn expand_block_macros(block: Block, macros: &HashMap<String, Macro>) -> Block {
             content: expand_inlines_macros(content, macros),
             label,
         },
-        Block::DisplayMath { content, label } => Block::DisplayMath {
+        Block::DisplayMath {
+            content,
+            label,
+            numbered,
+        } => Block::DisplayMath {
             content: expand_math_macros(&content, macros),
             label,
+            numbered,
         },
         Block::Environment {
             kind,
             label,
+            title,
+            proves,
             content,
             caption,
         } => Block::Environment {
             kind,
             label,
+            title,
+            proves,
             content: content
                 .into_iter()
                 .map(|b| expand_block_macros(b, macros))
@@ -80,6 +101,7 @@ fn expand_block_macros(block: Block, macros: &HashMap<String, Macro>) -> Block {
         Block::Table {
             headers,
             alignments,
+            widths,
             rows,
             label,
             caption,
@@ -89,6 +111,7 @@ fn expand_block_macros(block: Block, macros: &HashMap<String, Macro>) -> Block {
                 .map(|h| expand_inlines_macros(h, macros))
                 .collect(),
             alignments,
+            widths,
             rows: rows
                 .into_iter()
                 .map(|row| {
@@ -115,21 +138,12 @@ fn expand_inlines_macros(inlines: Vec<Inline>, macros: &HashMap<String, Macro>)
 fn expand_inline_macros(inline: Inline, macros: &HashMap<String, Macro>) -> Inline {
     match inline {
         Inline::InlineMath(content) => Inline::InlineMath(expand_math_macros(&content, macros)),
-        Inline::Emphasis(inlines) => Inline::Emphasis(expand_inlines_macros(inlines, macros)),
-        Inline::Strong(inlines) => Inline::Strong(expand_inlines_macros(inlines, macros)),
-        Inline::Strikethrough(inlines) => {
-            Inline::Strikethrough(expand_inlines_macros(inlines, macros))
+        mut other => {
+            if let Some(inner) = other.children_mut() {
+                *inner = expand_inlines_macros(std::mem::take(inner), macros);
+            }
+            other
         }
-        Inline::Link {
-            url,
-            title,
-            content,
-        } => Inline::Link {
-            url,
-            title,
-            content: expand_inlines_macros(content, macros),
-        },
-        other => other,
     }
 }
 
@@ -264,6 +278,25 @@ mod tests {
         assert_eq!(result, "x \\in \\mathbb{R}");
     }
 
+    #[test]
+    fn test_expand_macros_in_nested_inlines() {
+        let mut macros = HashMap::new();
+        macros.insert(
+            "R".to_string(),
+            Macro {
+                arg_count: 0,
+                template: "\\mathbb{R}".to_string(),
+            },
+        );
+
+        let math = || vec![Inline::InlineMath("\\R".to_string())];
+        let expanded = vec![Inline::InlineMath("\\mathbb{R}".to_string())];
+        for wrap in [Inline::SmallCaps, Inline::Subscript, Inline::Superscript] {
+            let result = expand_inline_macros(wrap(math()), &macros);
+            assert_eq!(result, wrap(expanded.clone()));
+        }
+    }
+
     #[test]
     fn test_expand_macro_with_args() {
         let mut macros = HashMap::new();
@@ -0,0 +1,236 @@
+//! Early syntax checks for math content.
+//!
+//! This is not a LaTeX parser: it checks that braces balance and that
+//! `\begin{...}`/`\end{...}` pairs match, which catches most typos before a
+//! math backend sees them.
+
+use crate::ast::{Block, Document, Inline};
+use std::fmt;
+
+/// A syntax problem found in one math span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MathDiagnostic {
+    /// Where the math appears: `equation eq:energy`, `display math 2` or
+    /// `inline math 5`, counted from the start of the document.
+    pub location: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl fmt::Display for MathDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Check every math span in the document, returning all problems found.
+pub fn validate_math(document: &Document) -> Vec<MathDiagnostic> {
+    validate_math_spans(document)
+        .into_iter()
+        .map(|(_, _, diagnostic)| diagnostic)
+        .collect()
+}
+
+/// Like [`validate_math`], pairing each problem with the index of the
+/// top-level block and the math it was found in.
+pub(crate) fn validate_math_spans(document: &Document) -> Vec<(usize, String, MathDiagnostic)> {
+    let mut checker = MathChecker::default();
+    for (index, block) in document.blocks.iter().enumerate() {
+        checker.block_index = index;
+        checker.block(block);
+    }
+    checker.diagnostics
+}
+
+#[derive(Default)]
+struct MathChecker {
+    display_count: usize,
+    inline_count: usize,
+    /// Top-level block being checked
+    block_index: usize,
+    diagnostics: Vec<(usize, String, MathDiagnostic)>,
+}
+
+impl MathChecker {
+    fn blocks(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            self.block(block);
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            }
+            | Block::Heading {
+                content: inlines, ..
+            } => self.inlines(inlines),
+            Block::DisplayMath { content, label, .. } => {
+                self.display_count += 1;
+                let location = match label {
+                    Some(label) => format!("equation {}", label),
+                    None => format!("display math {}", self.display_count),
+                };
+                self.check(content, location);
+            }
+            Block::Environment {
+                content, caption, ..
+            } => {
+                self.blocks(content);
+                if let Some(caption) = caption {
+                    self.inlines(caption);
+                }
+            }
+            Block::BlockQuote(blocks) | Block::Abstract(blocks) => self.blocks(blocks),
+            Block::List { items, .. } => {
+                for item in items {
+                    self.blocks(&item.content);
+                }
+            }
+            Block::Table {
+                headers,
+                rows,
+                caption,
+                ..
+            } => {
+                for cell in headers.iter().chain(rows.iter().flatten()) {
+                    self.inlines(cell);
+                }
+                if let Some(caption) = caption {
+                    self.inlines(caption);
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    self.inlines(&item.term);
+                    self.blocks(&item.description);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) {
+        for inline in inlines {
+            match inline {
+                Inline::InlineMath(content) => {
+                    self.inline_count += 1;
+                    let location = format!("inline math {}", self.inline_count);
+                    self.check(content, location);
+                }
+                _ => {
+                    if let Some(inner) = inline.children() {
+                        self.inlines(inner);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check(&mut self, content: &str, location: String) {
+        for message in check_balance(content) {
+            let diagnostic = MathDiagnostic {
+                location: location.clone(),
+                message,
+            };
+            self.diagnostics
+                .push((self.block_index, content.to_string(), diagnostic));
+        }
+    }
+}
+
+/// Check brace balance and `\begin`/`\end` pairing in one math span.
+fn check_balance(content: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut depth = 0usize;
+    let mut environments: Vec<&str> = Vec::new();
+
+    let mut chars = content.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            // An escaped character (`\{`, `\%`) is never structural
+            '\\' => {
+                let rest = &content[i + 1..];
+                let command = if let Some(name) = rest.strip_prefix("begin{") {
+                    Some((true, name))
+                } else {
+                    rest.strip_prefix("end{").map(|name| (false, name))
+                };
+                match command {
+                    Some((begin, name)) => {
+                        // Leave the name's braces to the brace counter
+                        let name = &name[..name.find('}').unwrap_or(name.len())];
+                        if begin {
+                            environments.push(name);
+                        } else {
+                            match environments.pop() {
+                                Some(open) if open == name => {}
+                                Some(open) => problems.push(format!(
+                                    "`\\end{{{}}}` does not match `\\begin{{{}}}`",
+                                    name, open
+                                )),
+                                None => problems
+                                    .push(format!("`\\end{{{}}}` has no matching `\\begin`", name)),
+                            }
+                        }
+                        let skip = if begin { "begin" } else { "end" };
+                        for _ in 0..skip.len() {
+                            chars.next();
+                        }
+                    }
+                    None => {
+                        chars.next();
+                    }
+                }
+            }
+            '{' => depth += 1,
+            '}' if depth == 0 => problems.push("unmatched `}`".to_string()),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    if depth > 0 {
+        problems.push(format!("{} unclosed `{{`", depth));
+    }
+    for name in environments.into_iter().rev() {
+        problems.push(format!("`\\begin{{{}}}` is never closed", name));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_unbalanced_math() {
+        let doc = parse(
+            "Fine $\\frac{a}{b}$ and broken $x}$.\n\n\
+             $$\n\\begin{pmatrix} a \\end{bmatrix}\n$$ {#eq:matrix}\n\n\
+             $$\n\\{ x \\}\n$$\n\n\
+             $$\n\\frac{a}{\n$$",
+        )
+        .unwrap();
+        assert_eq!(
+            validate_math(&doc),
+            vec![
+                MathDiagnostic {
+                    location: "inline math 2".to_string(),
+                    message: "unmatched `}`".to_string(),
+                },
+                MathDiagnostic {
+                    location: "equation eq:matrix".to_string(),
+                    message: "`\\end{bmatrix}` does not match `\\begin{pmatrix}`".to_string(),
+                },
+                MathDiagnostic {
+                    location: "display math 3".to_string(),
+                    message: "1 unclosed `{`".to_string(),
+                },
+            ]
+        );
+    }
+}
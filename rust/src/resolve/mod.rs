@@ -1,23 +1,41 @@
 //! Resolution layer for linking references, citations, and expanding macros.
 
+mod abbreviations;
+mod auto_labels;
 pub mod citations;
+mod diagnostics;
+mod links;
 mod macros;
+mod math_check;
 pub mod numbering;
 pub mod references;
+mod typography;
 
-pub use citations::resolve_citations;
+pub use abbreviations::expand_abbreviations;
+pub use auto_labels::{assign_auto_labels, assign_equation_labels};
+pub use citations::{disambiguate_years, resolve_citations};
+pub use diagnostics::{resolve_with_diagnostics, Diagnostic};
+pub use links::resolve_link_references;
 pub use macros::expand_macros;
-pub use numbering::assign_numbers;
+pub use math_check::{validate_math, MathDiagnostic};
+pub use numbering::{
+    assign_numbers, assign_numbers_with, EquationGroups, HeadingSkips, NumberingCounters,
+    NumberingScheme,
+};
 pub use references::resolve_references;
+pub use typography::apply_smart_quotes;
 
-use crate::ast::{BibEntry, Document, ResolvedDocument};
+use crate::ast::{BibEntry, CitationFormat, Document, ResolvedDocument};
 use crate::bibtex::parse_bibtex;
-use crate::error::{ResolutionError, Result};
+use crate::error::{Error, ParseError, ResolutionError, Result};
+use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Configuration for resolution.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ResolveConfig {
     /// Base path for resolving relative bibliography paths.
     pub base_path: Option<String>,
@@ -25,37 +43,166 @@ pub struct ResolveConfig {
     pub strict_citations: bool,
     /// Whether to error on unknown references (default: false).
     pub strict_references: bool,
+    /// Whether to synthesize labels (`eq:auto-3`, `thm:auto-1`, ...) for
+    /// numbered elements that have none, so every one is referenceable.
+    pub auto_label: bool,
+    /// A pre-loaded bibliography (see [`load_bibliography`]). When set, it is
+    /// used instead of reading the file named in front matter.
+    #[serde(skip)]
+    pub bibliography: Option<Arc<HashMap<String, BibEntry>>>,
+    /// How headings that skip a level (h1 → h3) are numbered.
+    pub heading_skips: HeadingSkips,
+    /// Whether a missing or unreadable bibliography is an error (default:
+    /// true). When false, resolution continues with no bibliography, noting
+    /// the failure in [`ResolvedDocument::warnings`], and citations render as
+    /// unresolved, which suits drafts. Malformed BibTeX is always an error.
+    pub bibliography_required: bool,
+    /// Whether to check brace balance and `\begin`/`\end` pairing in every
+    /// math span, failing with all problems found (default: false).
+    pub validate_math: bool,
+    /// Whether every numbered display equation gets a number, not only
+    /// labeled ones, by synthesizing `eq:auto-N` labels for the rest.
+    pub number_all_equations: bool,
+    /// Whether a run of references to the same kind of target shares one
+    /// plural prefix ("Equations (2) and (3)", "Figures 1, 2").
+    pub pluralize_references: bool,
+    /// Whether references to theorems, lemmas, definitions and the like name
+    /// the section they appear in ("Theorem 2.1 (in Section: Limits)").
+    pub theorem_section_titles: bool,
+    /// Word before the letter of top-level appendix sections, in references
+    /// and in rendered headings ("Appendix B"; default: "Appendix").
+    pub appendix_label: String,
+    /// Whether straight quotes in prose become curly ones (“ ” ‘ ’). Quotes
+    /// in code, math, raw HTML and citations are left alone.
+    pub smart_quotes: bool,
+    /// How equations, figures, tables and theorem-like environments are
+    /// numbered: through the whole document, or per section ("Theorem 2.1",
+    /// "Figure 2.1", equation (2.3)). `None` uses the document's
+    /// `number_equations`, falling back to flat.
+    pub numbering_scheme: Option<NumberingScheme>,
+    /// How display equations separated only by blank lines are numbered:
+    /// separately, or as one group lettered "(3a)", "(3b)" or sharing "(3)".
+    pub equation_groups: EquationGroups,
+    /// In-text citation format, overriding the document's `citation_style`
+    /// front matter. Renderers without their own setting follow it.
+    pub citation_style: Option<CitationFormat>,
+}
+
+impl Default for ResolveConfig {
+    fn default() -> Self {
+        Self {
+            base_path: None,
+            strict_citations: false,
+            strict_references: false,
+            auto_label: false,
+            bibliography: None,
+            heading_skips: HeadingSkips::default(),
+            bibliography_required: true,
+            validate_math: false,
+            number_all_equations: false,
+            pluralize_references: false,
+            theorem_section_titles: false,
+            appendix_label: "Appendix".to_string(),
+            smart_quotes: false,
+            numbering_scheme: None,
+            equation_groups: EquationGroups::Separate,
+            citation_style: None,
+        }
+    }
 }
 
 /// Resolve all references, citations, and macros in a document.
 pub fn resolve(document: Document, config: &ResolveConfig) -> Result<ResolvedDocument> {
     let mut doc = document;
+    let mut warnings = Vec::new();
 
     // Step 1: Load bibliography if specified
-    let citations = if let Some(ref bib_path) = doc.metadata.bibliography_path {
-        load_bibliography(bib_path, config)?
+    let mut citations = if let Some(ref bibliography) = config.bibliography {
+        HashMap::clone(bibliography)
+    } else if let Some(ref bib_path) = doc.metadata.bibliography_path {
+        match load_bibliography(bib_path, config) {
+            Ok(entries) => entries,
+            Err(Error::Resolution(ResolutionError::BibliographyRead(reason)))
+                if !config.bibliography_required =>
+            {
+                warnings.push(format!("Bibliography not loaded: {}", reason));
+                HashMap::new()
+            }
+            Err(e) => return Err(e),
+        }
     } else {
         HashMap::new()
     };
 
+    // Step 1a: `nocite = ["*"]` lists every bibliography entry
+    if let Some(star) = doc.metadata.nocite.iter().position(|key| key == "*") {
+        let mut all: Vec<String> = citations.keys().cloned().collect();
+        all.sort();
+        doc.metadata.nocite.splice(star..=star, all);
+    }
+
+    // The configured citation format wins over front matter
+    if config.citation_style.is_some() {
+        doc.metadata.citation_style = config.citation_style;
+    }
+
+    // Step 1b: Turn reference-style links into links
+    doc = resolve_link_references(doc);
+
     // Step 2: Expand macros in math content
     doc = expand_macros(doc)?;
 
+    // Step 2a: Check math syntax once macros are expanded
+    if config.validate_math {
+        let diagnostics = validate_math(&doc);
+        if !diagnostics.is_empty() {
+            let messages = diagnostics.iter().map(|d| d.to_string()).collect();
+            return Err(ResolutionError::InvalidMath(messages).into());
+        }
+    }
+
+    // Step 2b: Wrap defined abbreviations in text and curl quotes
+    doc = expand_abbreviations(doc);
+    if config.smart_quotes {
+        doc = apply_smart_quotes(doc);
+    }
+
+    // Step 2c: Label unlabeled numbered elements
+    if config.auto_label {
+        doc = assign_auto_labels(doc);
+    } else if config.number_all_equations {
+        doc = assign_equation_labels(doc);
+    }
+
     // Step 3: Assign numbers to sections, environments, equations, etc.
-    let (section_numbers, env_numbers) = assign_numbers(&doc);
+    let (section_numbers, env_numbers) = assign_numbers_with(
+        &doc,
+        &mut NumberingCounters::new(config.heading_skips)
+            .with_scheme(
+                config
+                    .numbering_scheme
+                    .or(doc.metadata.numbering_scheme)
+                    .unwrap_or_default(),
+            )
+            .with_equation_groups(config.equation_groups),
+    );
 
     // Step 4: Build label registry
-    let labels = references::build_label_registry(&doc, &section_numbers, &env_numbers)?;
-
-    // Step 5: Collect footnote definitions
-    let footnotes = references::collect_footnotes(&doc)?;
+    let labels =
+        references::build_label_registry_with(&doc, &section_numbers, &env_numbers, config)?;
 
-    // Step 6: Resolve references in document
+    // Step 5: Resolve references in document
     let doc = resolve_references(doc, &labels, config)?;
 
-    // Step 7: Resolve citations
+    // Step 6: Resolve citations
     let doc = resolve_citations(doc, &citations, config)?;
 
+    // Step 7: Collect footnote contents, with their references resolved
+    let footnotes = references::collect_footnotes(&doc)?;
+
+    // Step 8: Add year suffixes (2020a, 2020b) to colliding author-year pairs
+    disambiguate_years(&mut citations, &citations::get_citation_order(&doc));
+
     Ok(ResolvedDocument {
         document: doc,
         labels,
@@ -63,26 +210,179 @@ pub fn resolve(document: Document, config: &ResolveConfig) -> Result<ResolvedDoc
         footnotes,
         section_numbers,
         env_numbers,
+        warnings,
+    })
+}
+
+/// Append `next` to `previous` as one document, continuing `next`'s numbering
+/// from where `previous` left off and combining the label registries.
+///
+/// References throughout the merged document are re-resolved, so either part
+/// may refer to labels defined in the other. A label defined in both is a
+/// [`ResolutionError::DuplicateLabel`].
+pub fn merge_registries(
+    previous: ResolvedDocument,
+    next: ResolvedDocument,
+    config: &ResolveConfig,
+) -> Result<ResolvedDocument> {
+    let mut counters = NumberingCounters::new(config.heading_skips)
+        .with_scheme(
+            config
+                .numbering_scheme
+                .or(previous.document.metadata.numbering_scheme)
+                .unwrap_or_default(),
+        )
+        .with_equation_groups(config.equation_groups);
+    assign_numbers_with(&previous.document, &mut counters);
+    let (next_sections, next_envs) = assign_numbers_with(&next.document, &mut counters);
+    let next_labels =
+        references::build_label_registry_with(&next.document, &next_sections, &next_envs, config)?;
+
+    let mut labels = previous.labels;
+    for (label, info) in next_labels {
+        if labels.contains_key(&label) {
+            return Err(ResolutionError::DuplicateLabel(label).into());
+        }
+        labels.insert(label, info);
+    }
+
+    let mut section_numbers = previous.section_numbers;
+    section_numbers.extend(next_sections);
+    let mut env_numbers = previous.env_numbers;
+    env_numbers.extend(next_envs);
+    let mut warnings = previous.warnings;
+    warnings.extend(next.warnings);
+
+    let mut citations = previous.citations;
+    for (key, entry) in next.citations {
+        citations.entry(key).or_insert(entry);
+    }
+    let mut footnotes = previous.footnotes;
+    for (id, content) in next.footnotes {
+        footnotes.entry(id).or_insert(content);
+    }
+
+    let mut document = previous.document;
+    document.blocks.extend(next.document.blocks);
+    let document = resolve_references(document, &labels, config)?;
+
+    Ok(ResolvedDocument {
+        document,
+        labels,
+        citations,
+        footnotes,
+        section_numbers,
+        env_numbers,
+        warnings,
     })
 }
 
-fn load_bibliography(path: &str, config: &ResolveConfig) -> Result<HashMap<String, BibEntry>> {
+/// Load a bibliography, relative to `config.base_path`.
+///
+/// `path` may name a single `.bib` file, a directory (all `.bib` files in it
+/// are loaded), or a glob over the final path component (`refs/*.bib`).
+/// Files are merged in path order; when a key is defined more than once,
+/// whether in one file or several, the first definition wins.
+///
+/// The result can be shared through [`ResolveConfig::bibliography`] so that
+/// documents citing the same bibliography don't parse it again.
+pub fn load_bibliography(path: &str, config: &ResolveConfig) -> Result<HashMap<String, BibEntry>> {
     let full_path = if let Some(ref base) = config.base_path {
         Path::new(base).join(path)
     } else {
         Path::new(path).to_path_buf()
     };
 
-    let content = std::fs::read_to_string(&full_path).map_err(|e| {
-        ResolutionError::BibliographyRead(format!("{}: {}", full_path.display(), e))
-    })?;
+    let files = bibliography_files(&full_path)?;
+    let mut entries = HashMap::new();
+
+    for file in files {
+        let content = std::fs::read_to_string(&file)
+            .map_err(|e| ResolutionError::BibliographyRead(format!("{}: {}", file.display(), e)))?;
+        let parsed = parse_bibtex(&content).map_err(|e| match e {
+            Error::Parse(ParseError::BibTeX(message)) => {
+                ParseError::BibTeX(format!("{}: {}", file.display(), message)).into()
+            }
+            other => other,
+        })?;
+        for (key, entry) in parsed {
+            entries.entry(key).or_insert(entry);
+        }
+    }
 
-    Ok(parse_bibtex(&content).map_err(|e| ResolutionError::BibliographyRead(e.to_string()))?)
+    Ok(entries)
+}
+
+/// Expand a bibliography path into the list of files to load.
+fn bibliography_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let pattern = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let (dir, pattern) = if path.is_dir() {
+        (path.to_path_buf(), "*.bib".to_string())
+    } else if pattern.contains(['*', '?']) {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        (dir.to_path_buf(), pattern)
+    } else {
+        return Ok(vec![path.to_path_buf()]);
+    };
+
+    let read_dir = std::fs::read_dir(&dir)
+        .map_err(|e| ResolutionError::BibliographyRead(format!("{}: {}", dir.display(), e)))?;
+
+    let mut files: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_name()
+                .is_some_and(|name| glob_match(&pattern, &name.to_string_lossy()))
+        })
+        .collect();
+
+    if files.is_empty() {
+        return Err(ResolutionError::BibliographyRead(format!(
+            "{}: no bibliography files match",
+            path.display()
+        ))
+        .into());
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Match a file name against a pattern with `*` and `?` wildcards.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::{Block, Inline};
     use crate::parser::parse;
 
     #[test]
@@ -99,4 +399,153 @@ Some text with a reference to @sec:intro.
 
         assert!(resolved.labels.contains_key("sec:intro"));
     }
+
+    #[test]
+    fn test_outline_nesting() {
+        let input = "# Intro {#sec:intro}\n\n## Background\n\n### Detail\n\n## Scope {#sec:scope}\n\n# Methods";
+        let resolved = resolve(parse(input).unwrap(), &ResolveConfig::default()).unwrap();
+        let outline = resolved.outline();
+
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].text, "Intro");
+        assert_eq!(outline[0].html_id.as_deref(), Some("sec-intro"));
+        assert_eq!(outline[0].number.as_deref(), Some("1"));
+        assert_eq!(outline[0].children.len(), 2);
+        assert_eq!(outline[0].children[0].children[0].text, "Detail");
+        assert_eq!(outline[0].children[1].number.as_deref(), Some("1.2"));
+        assert_eq!(outline[1].text, "Methods");
+        assert!(outline[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_bibliography_directory_and_glob() {
+        let dir = std::env::temp_dir().join(format!("mda-bibdir-{}", std::process::id()));
+        let refs = dir.join("refs");
+        std::fs::create_dir_all(&refs).unwrap();
+        std::fs::write(
+            refs.join("a.bib"),
+            "@book{knuth1984, author = {Knuth, Donald}, title = {The TeXbook}, year = {1984}}",
+        )
+        .unwrap();
+        std::fs::write(
+            refs.join("b.bib"),
+            "@book{lamport1994, author = {Lamport, Leslie}, title = {LaTeX}, year = {1994}}\n\
+             @book{knuth1984, title = {Duplicate}, year = {1986}}",
+        )
+        .unwrap();
+        std::fs::write(refs.join("notes.txt"), "not a bibliography").unwrap();
+
+        let config = ResolveConfig {
+            base_path: Some(dir.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let from_dir = load_bibliography("refs", &config).unwrap();
+        let from_glob = load_bibliography("refs/*.bib", &config).unwrap();
+        let missing = load_bibliography("refs/*.bibtex", &config);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(from_dir.len(), 2);
+        assert!(from_dir.contains_key("knuth1984"));
+        assert!(from_dir.contains_key("lamport1994"));
+        assert_eq!(from_dir["knuth1984"].title.as_deref(), Some("The TeXbook"));
+        assert_eq!(from_glob.len(), 2);
+        assert!(missing.is_err());
+        assert!(glob_match("*.bib", "refs.bib"));
+        assert!(!glob_match("a?.bib", "a.bib"));
+    }
+
+    #[test]
+    fn test_merge_registries_continues_numbering() {
+        let config = ResolveConfig::default();
+        let first = resolve(
+            parse("# One {#sec:one}\n\n$$\na = b\n$$ {#eq:a}").unwrap(),
+            &config,
+        )
+        .unwrap();
+        let second = resolve(
+            parse("# Two {#sec:two}\n\n$$\nc = d\n$$ {#eq:c}\n\nCompare @eq:a and @eq:c.").unwrap(),
+            &config,
+        )
+        .unwrap();
+
+        let merged = merge_registries(first.clone(), second, &config).unwrap();
+        assert_eq!(merged.env_numbers["eq:a"], "1");
+        assert_eq!(merged.env_numbers["eq:c"], "2");
+        assert_eq!(merged.labels["eq:c"].display, "(2)");
+        assert_eq!(merged.section_numbers["sec:two"], "2");
+        assert_eq!(merged.document.blocks.len(), 5);
+
+        let Block::Paragraph(inlines) = &merged.document.blocks[4] else {
+            panic!("Expected paragraph");
+        };
+        let resolved: Vec<_> = inlines
+            .iter()
+            .filter_map(|i| match i {
+                Inline::Reference { resolved, .. } => resolved.as_deref(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(resolved, vec!["(1)", "(2)"]);
+
+        let collision = merge_registries(first.clone(), first, &config);
+        assert!(collision.is_err());
+    }
+
+    #[test]
+    fn test_preloaded_bibliography() {
+        let entries = parse_bibtex(
+            "@book{knuth1984, author = {Knuth, Donald}, title = {TeX}, year = {1984}}",
+        )
+        .unwrap();
+        let config = ResolveConfig {
+            bibliography: Some(Arc::new(entries)),
+            strict_citations: true,
+            ..Default::default()
+        };
+
+        // The front-matter file does not exist; only the shared entries are used
+        let input = "+++\nbibliography = \"missing.bib\"\n+++\n\nSee [@knuth1984].";
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+        assert!(resolved.citations.contains_key("knuth1984"));
+
+        let without = ResolveConfig {
+            bibliography: None,
+            ..config
+        };
+        assert!(resolve(parse(input).unwrap(), &without).is_err());
+    }
+
+    #[test]
+    fn test_optional_bibliography() {
+        let input = "+++\nbibliography = \"missing.bib\"\n+++\n\nSee [@knuth1984].";
+        assert!(resolve(parse(input).unwrap(), &ResolveConfig::default()).is_err());
+
+        let config = ResolveConfig {
+            bibliography_required: false,
+            ..Default::default()
+        };
+        let resolved = resolve(parse(input).unwrap(), &config).unwrap();
+        assert!(resolved.citations.is_empty());
+        assert_eq!(resolved.warnings.len(), 1);
+        assert!(resolved.warnings[0].contains("missing.bib"));
+
+        let html = crate::render_html(&resolved, &crate::HtmlConfig::default()).unwrap();
+        assert!(html.contains("knuth1984"));
+    }
+
+    #[test]
+    fn test_validate_math_option() {
+        let input = "Broken:\n\n$$\\frac{a}{$$";
+        assert!(resolve(parse(input).unwrap(), &ResolveConfig::default()).is_ok());
+
+        let config = ResolveConfig {
+            validate_math: true,
+            ..Default::default()
+        };
+        let err = resolve(parse(input).unwrap(), &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Resolution error: Invalid math: display math 1: 1 unclosed `{`"
+        );
+    }
 }
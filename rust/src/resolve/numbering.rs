@@ -1,82 +1,288 @@
 //! Automatic numbering for sections, environments, equations, etc.
 
+pub use crate::ast::NumberingScheme;
+
 use crate::ast::{Block, Document, EnvironmentKind};
+use serde::Deserialize;
 use std::collections::HashMap;
 
+/// Running counters for every numbered element kind.
+///
+/// Numbering one document after another with the same counters continues
+/// the sequence, as for chapters of a multi-file project.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NumberingCounters {
+    heading_skips: HeadingSkips,
+    open_levels: Vec<u8>, // levels of the enclosing headings
+    sections: [u32; 6],   // h1..h6
+    equation: u32,
+    figure: u32,
+    table: u32,
+    theorem: u32,
+    lemma: u32,
+    definition: u32,
+    example: u32,
+    algorithm: u32,
+    listing: u32,
+    /// How equations, figures, tables and theorem-like environments are
+    /// numbered
+    scheme: NumberingScheme,
+    /// Set after an appendix marker: top-level sections are lettered
+    appendix: bool,
+    /// How runs of adjacent display equations are numbered
+    equation_groups: EquationGroups,
+}
+
+/// How a run of adjacent display equations (`$$...$$` blocks separated only
+/// by blank lines) is numbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EquationGroups {
+    /// Every equation takes its own number ("(3)", "(4)").
+    #[default]
+    Separate,
+    /// The run takes one number, lettered per equation ("(3a)", "(3b)").
+    Subnumbered,
+    /// Every equation of the run takes the same number ("(3)").
+    Shared,
+}
+
+/// How headings that skip a level (`#` followed by `###`) are numbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeadingSkips {
+    /// Number by literal level, so the skipped level shows as 0 ("1.0.1").
+    #[default]
+    Literal,
+    /// Number by nesting depth, treating the `###` as a subsection ("1.1").
+    Normalize,
+}
+
+impl NumberingCounters {
+    /// Fresh counters using the given policy for skipped heading levels.
+    pub fn new(heading_skips: HeadingSkips) -> Self {
+        Self {
+            heading_skips,
+            ..Self::default()
+        }
+    }
+
+    /// Number equations, figures, tables and theorem-like environments by
+    /// the given scheme.
+    pub fn with_scheme(mut self, scheme: NumberingScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Number runs of adjacent display equations as one group.
+    pub fn with_equation_groups(mut self, groups: EquationGroups) -> Self {
+        self.equation_groups = groups;
+        self
+    }
+
+    /// Display number for the `count`th equation or environment of its
+    /// section.
+    fn scheme_number(&self, count: u32) -> String {
+        match self.scheme {
+            NumberingScheme::BySection { depth } => {
+                let idx = (depth as usize).saturating_sub(1).min(5);
+                if self.sections[..=idx].iter().all(|&n| n == 0) {
+                    // Not yet inside a section of that depth
+                    count.to_string()
+                } else {
+                    format!("{}.{}", self.section_number(idx), count)
+                }
+            }
+            NumberingScheme::Flat => count.to_string(),
+        }
+    }
+
+    /// Display number of the current section at depth `idx` ("2.1", or
+    /// "A.1" in the appendix).
+    fn section_number(&self, idx: usize) -> String {
+        let mut parts: Vec<String> = self.sections[..=idx].iter().map(u32::to_string).collect();
+        if self.appendix {
+            parts[0] = appendix_letter(self.sections[0]);
+        }
+        parts.join(".")
+    }
+}
+
 /// Assign numbers to all numbered elements in the document.
 /// Returns (section_numbers, env_numbers).
-pub fn assign_numbers(document: &Document) -> (HashMap<String, String>, HashMap<String, u32>) {
+pub fn assign_numbers(document: &Document) -> (HashMap<String, String>, HashMap<String, String>) {
+    assign_numbers_with(document, &mut NumberingCounters::default())
+}
+
+/// Assign numbers starting from (and advancing) the given counters.
+pub fn assign_numbers_with(
+    document: &Document,
+    counters: &mut NumberingCounters,
+) -> (HashMap<String, String>, HashMap<String, String>) {
     let mut section_numbers = HashMap::new();
     let mut env_numbers = HashMap::new();
 
-    // Counters
-    let mut section_counters = [0u32; 6]; // h1..h6
-    let mut equation_counter = 0u32;
-    let mut figure_counter = 0u32;
-    let mut table_counter = 0u32;
-    let mut theorem_counter = 0u32;
-    let mut lemma_counter = 0u32;
-    let mut definition_counter = 0u32;
-    let mut example_counter = 0u32;
-    let mut algorithm_counter = 0u32;
-
-    for block in &document.blocks {
-        assign_block_numbers(
-            block,
-            &mut section_counters,
-            &mut section_numbers,
-            &mut env_numbers,
-            &mut equation_counter,
-            &mut figure_counter,
-            &mut table_counter,
-            &mut theorem_counter,
-            &mut lemma_counter,
-            &mut definition_counter,
-            &mut example_counter,
-            &mut algorithm_counter,
-        );
-    }
+    assign_blocks_numbers(
+        &document.blocks,
+        counters,
+        &mut section_numbers,
+        &mut env_numbers,
+    );
 
     (section_numbers, env_numbers)
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Number a sequence of sibling blocks, treating a run of two or more
+/// numbered display equations as one group unless groups are off.
+fn assign_blocks_numbers(
+    blocks: &[Block],
+    counters: &mut NumberingCounters,
+    section_numbers: &mut HashMap<String, String>,
+    env_numbers: &mut HashMap<String, String>,
+) {
+    let is_equation = |block: &Block| matches!(block, Block::DisplayMath { numbered: true, .. });
+
+    let mut i = 0;
+    while i < blocks.len() {
+        let run = blocks[i..]
+            .iter()
+            .take_while(|block| is_equation(block))
+            .count();
+        if run >= 2 && counters.equation_groups != EquationGroups::Separate {
+            assign_equation_group(&blocks[i..i + run], counters, env_numbers);
+            i += run;
+        } else {
+            assign_block_numbers(&blocks[i], counters, section_numbers, env_numbers);
+            i += 1;
+        }
+    }
+}
+
+/// Give a run of display equations one number. Each equation, or each
+/// labeled line of an `align`, is a member of the group.
+fn assign_equation_group(
+    blocks: &[Block],
+    counters: &mut NumberingCounters,
+    env_numbers: &mut HashMap<String, String>,
+) {
+    counters.equation += 1;
+    let number = counters.scheme_number(counters.equation);
+    let mut member = 0;
+    let mut next_number = || {
+        member += 1;
+        match counters.equation_groups {
+            EquationGroups::Subnumbered => {
+                format!("{}{}", number, appendix_letter(member).to_lowercase())
+            }
+            _ => number.clone(),
+        }
+    };
+
+    for block in blocks {
+        let Block::DisplayMath { label, content, .. } = block else {
+            continue;
+        };
+        let line_labels = align_line_labels(content);
+        if line_labels.is_empty() {
+            let number = next_number();
+            if let Some(lbl) = label {
+                env_numbers.insert(lbl.clone(), number);
+            }
+        }
+        for (i, line_label) in line_labels.iter().enumerate() {
+            let number = next_number();
+            if i == 0 {
+                if let Some(lbl) = label {
+                    env_numbers.insert(lbl.clone(), number.clone());
+                }
+            }
+            env_numbers.insert(line_label.clone(), number);
+        }
+    }
+}
+
 fn assign_block_numbers(
     block: &Block,
-    section_counters: &mut [u32; 6],
+    counters: &mut NumberingCounters,
     section_numbers: &mut HashMap<String, String>,
-    env_numbers: &mut HashMap<String, u32>,
-    equation_counter: &mut u32,
-    figure_counter: &mut u32,
-    table_counter: &mut u32,
-    theorem_counter: &mut u32,
-    lemma_counter: &mut u32,
-    definition_counter: &mut u32,
-    example_counter: &mut u32,
-    algorithm_counter: &mut u32,
+    env_numbers: &mut HashMap<String, String>,
 ) {
     match block {
         Block::Heading { level, label, .. } => {
-            let idx = (*level as usize).saturating_sub(1).min(5);
+            let idx = match counters.heading_skips {
+                HeadingSkips::Literal => (*level as usize).saturating_sub(1).min(5),
+                HeadingSkips::Normalize => {
+                    while counters
+                        .open_levels
+                        .last()
+                        .is_some_and(|open| open >= level)
+                    {
+                        counters.open_levels.pop();
+                    }
+                    counters.open_levels.push(*level);
+                    (counters.open_levels.len() - 1).min(5)
+                }
+            };
 
             // Increment this level's counter
-            section_counters[idx] += 1;
+            counters.sections[idx] += 1;
 
             // Reset lower level counters
-            for counter in section_counters.iter_mut().take(6).skip(idx + 1) {
+            for counter in counters.sections.iter_mut().take(6).skip(idx + 1) {
                 *counter = 0;
             }
+            if let NumberingScheme::BySection { depth } = counters.scheme {
+                if idx < depth as usize {
+                    counters.equation = 0;
+                    counters.figure = 0;
+                    counters.table = 0;
+                    counters.theorem = 0;
+                    counters.lemma = 0;
+                    counters.definition = 0;
+                    counters.example = 0;
+                    counters.algorithm = 0;
+                    counters.listing = 0;
+                }
+            }
 
             if let Some(lbl) = label {
                 // Build section number string
-                let number = build_section_number(section_counters, idx);
+                let number = counters.section_number(idx);
                 section_numbers.insert(lbl.clone(), number);
             }
         }
-        Block::DisplayMath { label, .. } => {
-            *equation_counter += 1;
-            if let Some(lbl) = label {
-                env_numbers.insert(lbl.clone(), *equation_counter);
+        Block::AppendixMarker => {
+            // Sections after the marker restart, lettered "A", "B", ...
+            counters.appendix = true;
+            counters.sections = [0; 6];
+            counters.open_levels.clear();
+        }
+        Block::DisplayMath {
+            numbered: false, ..
+        } => {}
+        Block::DisplayMath { label, content, .. } => {
+            let line_labels = align_line_labels(content);
+            if line_labels.is_empty() {
+                counters.equation += 1;
+                if let Some(lbl) = label {
+                    env_numbers.insert(lbl.clone(), counters.scheme_number(counters.equation));
+                }
+            } else {
+                // Each labeled line is its own equation; a block label
+                // refers to the first line.
+                for (i, line_label) in line_labels.iter().enumerate() {
+                    counters.equation += 1;
+                    env_numbers.insert(
+                        line_label.clone(),
+                        counters.scheme_number(counters.equation),
+                    );
+                    if i == 0 {
+                        if let Some(lbl) = label {
+                            env_numbers
+                                .insert(lbl.clone(), counters.scheme_number(counters.equation));
+                        }
+                    }
+                }
             }
         }
         Block::Environment {
@@ -91,32 +297,36 @@ fn assign_block_numbers(
                 | EnvironmentKind::Corollary
                 | EnvironmentKind::Conjecture
                 | EnvironmentKind::Axiom => {
-                    *theorem_counter += 1;
-                    Some(*theorem_counter)
+                    counters.theorem += 1;
+                    Some(counters.scheme_number(counters.theorem))
                 }
                 EnvironmentKind::Lemma => {
-                    *lemma_counter += 1;
-                    Some(*lemma_counter)
+                    counters.lemma += 1;
+                    Some(counters.scheme_number(counters.lemma))
                 }
                 EnvironmentKind::Definition => {
-                    *definition_counter += 1;
-                    Some(*definition_counter)
+                    counters.definition += 1;
+                    Some(counters.scheme_number(counters.definition))
                 }
                 EnvironmentKind::Example | EnvironmentKind::Remark | EnvironmentKind::Exercise => {
-                    *example_counter += 1;
-                    Some(*example_counter)
+                    counters.example += 1;
+                    Some(counters.scheme_number(counters.example))
                 }
                 EnvironmentKind::Figure => {
-                    *figure_counter += 1;
-                    Some(*figure_counter)
+                    counters.figure += 1;
+                    Some(counters.scheme_number(counters.figure))
                 }
                 EnvironmentKind::Table => {
-                    *table_counter += 1;
-                    Some(*table_counter)
+                    counters.table += 1;
+                    Some(counters.scheme_number(counters.table))
                 }
                 EnvironmentKind::Algorithm => {
-                    *algorithm_counter += 1;
-                    Some(*algorithm_counter)
+                    counters.algorithm += 1;
+                    Some(counters.scheme_number(counters.algorithm))
+                }
+                EnvironmentKind::Listing => {
+                    counters.listing += 1;
+                    Some(counters.scheme_number(counters.listing))
                 }
                 // Non-numbered environments
                 EnvironmentKind::Proof
@@ -134,77 +344,61 @@ fn assign_block_numbers(
             }
 
             // Process nested blocks
-            for inner in content {
-                assign_block_numbers(
-                    inner,
-                    section_counters,
-                    section_numbers,
-                    env_numbers,
-                    equation_counter,
-                    figure_counter,
-                    table_counter,
-                    theorem_counter,
-                    lemma_counter,
-                    definition_counter,
-                    example_counter,
-                    algorithm_counter,
-                );
-            }
+            assign_blocks_numbers(content, counters, section_numbers, env_numbers);
         }
         Block::Table { label, .. } => {
-            *table_counter += 1;
+            counters.table += 1;
             if let Some(lbl) = label {
-                env_numbers.insert(lbl.clone(), *table_counter);
+                env_numbers.insert(lbl.clone(), counters.scheme_number(counters.table));
             }
         }
         Block::BlockQuote(blocks) => {
-            for inner in blocks {
-                assign_block_numbers(
-                    inner,
-                    section_counters,
-                    section_numbers,
-                    env_numbers,
-                    equation_counter,
-                    figure_counter,
-                    table_counter,
-                    theorem_counter,
-                    lemma_counter,
-                    definition_counter,
-                    example_counter,
-                    algorithm_counter,
-                );
-            }
+            assign_blocks_numbers(blocks, counters, section_numbers, env_numbers);
         }
         Block::List { items, .. } => {
             for item in items {
-                for inner in &item.content {
-                    assign_block_numbers(
-                        inner,
-                        section_counters,
-                        section_numbers,
-                        env_numbers,
-                        equation_counter,
-                        figure_counter,
-                        table_counter,
-                        theorem_counter,
-                        lemma_counter,
-                        definition_counter,
-                        example_counter,
-                        algorithm_counter,
-                    );
-                }
+                assign_blocks_numbers(&item.content, counters, section_numbers, env_numbers);
             }
         }
         _ => {}
     }
 }
 
-fn build_section_number(counters: &[u32; 6], max_level: usize) -> String {
-    counters[..=max_level]
-        .iter()
-        .map(|n| n.to_string())
-        .collect::<Vec<_>>()
-        .join(".")
+/// Whether `number` is that of a top-level appendix section, which is
+/// lettered ("B", but not "B.1" or "2").
+pub fn is_appendix_number(number: &str) -> bool {
+    number.starts_with(|c: char| c.is_ascii_uppercase()) && !number.contains('.')
+}
+
+/// Spreadsheet-style letter for the `n`th appendix: "A" to "Z", then "AA".
+fn appendix_letter(mut n: u32) -> String {
+    let mut letters = Vec::new();
+    while n > 0 {
+        n -= 1;
+        letters.push(char::from(b'A' + (n % 26) as u8));
+        n /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Labels given by `\label{...}` on the lines of an `align`/`aligned` block,
+/// in order. Other display math yields no line labels.
+pub fn align_line_labels(content: &str) -> Vec<String> {
+    if !content.contains("\\begin{align") {
+        return Vec::new();
+    }
+
+    let mut labels = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("\\label{") {
+        let after = &rest[start + "\\label{".len()..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        labels.push(after[..end].trim().to_string());
+        rest = &after[end + 1..];
+    }
+    labels
 }
 
 #[cfg(test)]
@@ -212,6 +406,22 @@ mod tests {
     use super::*;
     use crate::parser::parse;
 
+    #[test]
+    fn test_skipped_heading_levels() {
+        let input = "# One {#sec:one}\n\n### Deep {#sec:deep}\n\n### Deeper {#sec:deeper}\n\n## Sub {#sec:sub}\n\n# Two {#sec:two}";
+        let doc = parse(input).unwrap();
+
+        let (literal, _) = assign_numbers(&doc);
+        assert_eq!(literal["sec:deep"], "1.0.1");
+
+        let mut counters = NumberingCounters::new(HeadingSkips::Normalize);
+        let (normalized, _) = assign_numbers_with(&doc, &mut counters);
+        assert_eq!(normalized["sec:deep"], "1.1");
+        assert_eq!(normalized["sec:deeper"], "1.2");
+        assert_eq!(normalized["sec:sub"], "1.3");
+        assert_eq!(normalized["sec:two"], "2");
+    }
+
     #[test]
     fn test_section_numbering() {
         let input = r#"
@@ -264,8 +474,149 @@ A lemma.
         let doc = parse(input).unwrap();
         let (_, env_numbers) = assign_numbers(&doc);
 
-        assert_eq!(env_numbers.get("thm:one"), Some(&1));
-        assert_eq!(env_numbers.get("thm:two"), Some(&2));
-        assert_eq!(env_numbers.get("lem:one"), Some(&1));
+        assert_eq!(env_numbers.get("thm:one"), Some(&"1".to_string()));
+        assert_eq!(env_numbers.get("thm:two"), Some(&"2".to_string()));
+        assert_eq!(env_numbers.get("lem:one"), Some(&"1".to_string()));
+    }
+
+    #[test]
+    fn test_align_line_labels() {
+        let input = r#"$$ {#eq:before}
+x = 1
+$$
+
+$$
+\begin{align}
+a &= b \label{eq:first} \\
+c &= d \label{eq:second}
+\end{align}
+$$"#;
+
+        let doc = parse(input).unwrap();
+        let (_, env_numbers) = assign_numbers(&doc);
+
+        assert_eq!(env_numbers.get("eq:first"), Some(&"2".to_string()));
+        assert_eq!(env_numbers.get("eq:second"), Some(&"3".to_string()));
+        assert!(align_line_labels("x = 1 \\label{eq:x}").is_empty());
+    }
+
+    #[test]
+    fn test_figures_by_section() {
+        let input = "::: figure {#fig:pre}\nP\n:::\n\n# One\n\n::: figure {#fig:a}\nA\n:::\n\n\
+                     ## Part\n\n::: figure {#fig:b}\nB\n:::\n\n\
+                     # Two\n\n::: figure {#fig:c}\nC\n:::\n\n\
+                     | x |\n|---|\n| 1 |\nTable: Data {#tab:d}\n\n\
+                     See @fig:c.";
+        let config = crate::resolve::ResolveConfig {
+            numbering_scheme: Some(NumberingScheme::BySection { depth: 1 }),
+            ..Default::default()
+        };
+        let resolved =
+            crate::resolve::resolve(crate::parser::parse(input).unwrap(), &config).unwrap();
+        let number = |label: &str| resolved.env_numbers[label].as_str();
+        assert_eq!(number("fig:pre"), "1");
+        assert_eq!(number("fig:a"), "1.1");
+        assert_eq!(number("fig:b"), "1.2");
+        assert_eq!(number("fig:c"), "2.1");
+        assert_eq!(number("tab:d"), "2.1");
+        assert_eq!(resolved.labels["fig:c"].display, "Figure 2.1");
+    }
+
+    #[test]
+    fn test_numbering_by_section() {
+        let input = "$$x$$ {#eq:pre}\n\n\
+                     # One\n\n::: theorem {#thm:a}\nA\n:::\n\n$$y$$ {#eq:a}\n\n\
+                     ## Part\n\n::: theorem {#thm:b}\nB\n:::\n\n\
+                     # Two\n\n::: theorem {#thm:c}\nC\n:::\n\n$$z$$ {#eq:c}\n\n\
+                     See @thm:c and @eq:c.";
+        let config = crate::resolve::ResolveConfig {
+            numbering_scheme: Some(NumberingScheme::BySection { depth: 1 }),
+            ..Default::default()
+        };
+        let resolved =
+            crate::resolve::resolve(crate::parser::parse(input).unwrap(), &config).unwrap();
+        let number = |label: &str| resolved.env_numbers[label].as_str();
+        assert_eq!(number("eq:pre"), "1");
+        assert_eq!(number("thm:a"), "1.1");
+        assert_eq!(number("eq:a"), "1.1");
+        assert_eq!(number("thm:b"), "1.2");
+        assert_eq!(number("thm:c"), "2.1");
+        assert_eq!(number("eq:c"), "2.1");
+        assert_eq!(resolved.labels["thm:c"].display, "Theorem 2.1");
+
+        let html = crate::render_html(&resolved, &Default::default()).unwrap();
+        assert!(html.contains("(2.1)"));
+    }
+
+    #[test]
+    fn test_numbering_from_front_matter() {
+        let input = "+++\nnumber_equations = \"per-section\"\n+++\n\n\
+                     # One\n\n$$x$$ {#eq:a}";
+        let number = |config: &crate::resolve::ResolveConfig| {
+            let resolved =
+                crate::resolve::resolve(crate::parser::parse(input).unwrap(), config).unwrap();
+            resolved.env_numbers["eq:a"].clone()
+        };
+        assert_eq!(number(&Default::default()), "1.1");
+
+        let flat = crate::resolve::ResolveConfig {
+            numbering_scheme: Some(NumberingScheme::Flat),
+            ..Default::default()
+        };
+        assert_eq!(number(&flat), "1");
+        assert!(crate::parser::parse("+++\nnumber_equations = \"roman\"\n+++\n\nText.").is_err());
+    }
+
+    #[test]
+    fn test_appendix_numbering() {
+        let input = "# Intro {#sec:intro}\n\n<!-- appendix -->\n\n\
+                     # Proofs {#sec:proofs}\n\n## Lemmas {#sec:lemmas}\n\n\
+                     # Data {#sec:data}\n\nSee @sec:data.";
+        let resolved = crate::resolve::resolve(
+            crate::parser::parse(input).unwrap(),
+            &crate::resolve::ResolveConfig::default(),
+        )
+        .unwrap();
+        let number = |label: &str| resolved.section_numbers[label].as_str();
+        assert_eq!(number("sec:intro"), "1");
+        assert_eq!(number("sec:proofs"), "A");
+        assert_eq!(number("sec:lemmas"), "A.1");
+        assert_eq!(number("sec:data"), "B");
+        assert_eq!(resolved.labels["sec:data"].display, "Appendix B");
+        assert_eq!(resolved.labels["sec:lemmas"].display, "Section A.1");
+        assert_eq!(appendix_letter(28), "AB");
+
+        let config = crate::resolve::ResolveConfig {
+            appendix_label: "Anhang".to_string(),
+            ..Default::default()
+        };
+        let resolved =
+            crate::resolve::resolve(crate::parser::parse(input).unwrap(), &config).unwrap();
+        assert_eq!(resolved.labels["sec:data"].display, "Anhang B");
+    }
+
+    #[test]
+    fn test_equation_groups() {
+        let input = "$$a = b$$ {#eq:one}\n\n$$c = d$$ {#eq:two}\n\n$$e = f$$ {#eq:three}\n\nText.\n\n$$g = h$$ {#eq:alone}";
+        let doc = parse(input).unwrap();
+
+        let (_, separate) = assign_numbers(&doc);
+        assert_eq!(separate["eq:three"], "3");
+        assert_eq!(separate["eq:alone"], "4");
+
+        let mut counters =
+            NumberingCounters::default().with_equation_groups(EquationGroups::Subnumbered);
+        let (_, grouped) = assign_numbers_with(&doc, &mut counters);
+        assert_eq!(grouped["eq:one"], "1a");
+        assert_eq!(grouped["eq:two"], "1b");
+        assert_eq!(grouped["eq:three"], "1c");
+        assert_eq!(grouped["eq:alone"], "2");
+
+        let mut counters =
+            NumberingCounters::default().with_equation_groups(EquationGroups::Shared);
+        let (_, shared) = assign_numbers_with(&doc, &mut counters);
+        assert_eq!(shared["eq:one"], "1");
+        assert_eq!(shared["eq:three"], "1");
+        assert_eq!(shared["eq:alone"], "2");
     }
 }
@@ -1,7 +1,10 @@
 //! Cross-reference resolution.
 
-use crate::ast::{Block, Document, FootnoteKind, Inline, LabelInfo};
+use crate::ast::{
+    Block, Document, EnvironmentKind, FootnoteKind, Inline, LabelInfo, ReferenceForm,
+};
 use crate::error::{ResolutionError, Result};
+use crate::resolve::numbering::{align_line_labels, is_appendix_number};
 use crate::resolve::ResolveConfig;
 use std::collections::HashMap;
 
@@ -9,32 +12,79 @@ use std::collections::HashMap;
 pub fn build_label_registry(
     document: &Document,
     section_numbers: &HashMap<String, String>,
-    env_numbers: &HashMap<String, u32>,
+    env_numbers: &HashMap<String, String>,
+) -> Result<HashMap<String, LabelInfo>> {
+    build_label_registry_with(
+        document,
+        section_numbers,
+        env_numbers,
+        &ResolveConfig::default(),
+    )
+}
+
+/// Build a registry of all labels in the document, following `config` for
+/// the appendix label and for naming the enclosing section in theorem-like
+/// references ("Theorem 2.1 (in Section: Limits)").
+pub fn build_label_registry_with(
+    document: &Document,
+    section_numbers: &HashMap<String, String>,
+    env_numbers: &HashMap<String, String>,
+    config: &ResolveConfig,
 ) -> Result<HashMap<String, LabelInfo>> {
     let mut labels = HashMap::new();
+    let mut context = LabelContext {
+        section_numbers,
+        env_numbers,
+        section_titles: config.theorem_section_titles,
+        appendix_label: &config.appendix_label,
+        current_section: None,
+    };
 
     for block in &document.blocks {
-        collect_block_labels(block, &mut labels, section_numbers, env_numbers)?;
+        collect_block_labels(block, &mut labels, &mut context)?;
     }
 
     Ok(labels)
 }
 
+/// State carried through label collection.
+struct LabelContext<'a> {
+    section_numbers: &'a HashMap<String, String>,
+    env_numbers: &'a HashMap<String, String>,
+    section_titles: bool,
+    /// Word before the letter of a top-level appendix section
+    appendix_label: &'a str,
+    /// Title of the most recent heading, for `section_titles`
+    current_section: Option<String>,
+}
+
 fn collect_block_labels(
     block: &Block,
     labels: &mut HashMap<String, LabelInfo>,
-    section_numbers: &HashMap<String, String>,
-    env_numbers: &HashMap<String, u32>,
+    context: &mut LabelContext,
 ) -> Result<()> {
     match block {
+        Block::Heading {
+            label: None,
+            content,
+            ..
+        } => {
+            context.current_section = Some(inlines_to_text(content));
+        }
         Block::Heading {
             level: _,
             label: Some(lbl),
             content,
             ..
         } => {
-            let display = if let Some(num) = section_numbers.get(lbl) {
-                format!("Section {}", num)
+            context.current_section = Some(inlines_to_text(content));
+            let number = context.section_numbers.get(lbl).cloned();
+            let display = if let Some(num) = &number {
+                if is_appendix_number(num) {
+                    format!("{} {}", context.appendix_label, num)
+                } else {
+                    format!("Section {}", num)
+                }
             } else {
                 // Use heading text
                 inlines_to_text(content)
@@ -46,24 +96,40 @@ fn collect_block_labels(
                 return Err(ResolutionError::DuplicateLabel(lbl.clone()).into());
             }
 
-            labels.insert(lbl.clone(), LabelInfo { display, html_id });
+            labels.insert(
+                lbl.clone(),
+                LabelInfo {
+                    full: display.clone(),
+                    display,
+                    number,
+                    html_id,
+                    preview: None,
+                },
+            );
         }
-        Block::DisplayMath {
-            label: Some(lbl), ..
-        } => {
-            let display = if let Some(num) = env_numbers.get(lbl) {
-                format!("({})", num)
-            } else {
-                "(?)".to_string()
-            };
+        Block::DisplayMath { label, content, .. } => {
+            let line_labels = align_line_labels(content);
+            for lbl in label.iter().chain(&line_labels) {
+                let number = context.env_numbers.get(lbl).cloned();
+                let display = format!("({})", number.as_deref().unwrap_or("?"));
 
-            let html_id = label_to_id(lbl);
+                let html_id = label_to_id(lbl);
 
-            if labels.contains_key(lbl) {
-                return Err(ResolutionError::DuplicateLabel(lbl.clone()).into());
-            }
+                if labels.contains_key(lbl) {
+                    return Err(ResolutionError::DuplicateLabel(lbl.clone()).into());
+                }
 
-            labels.insert(lbl.clone(), LabelInfo { display, html_id });
+                labels.insert(
+                    lbl.clone(),
+                    LabelInfo {
+                        full: format!("Equation {}", display),
+                        display,
+                        number,
+                        html_id,
+                        preview: Some(content.trim().to_string()),
+                    },
+                );
+            }
         }
         Block::Environment {
             kind,
@@ -72,11 +138,17 @@ fn collect_block_labels(
             ..
         } => {
             if let Some(lbl) = label {
-                let display = if let Some(num) = env_numbers.get(lbl) {
+                let number = context.env_numbers.get(lbl).cloned();
+                let mut display = if let Some(num) = &number {
                     format!("{} {}", kind.display_name(), num)
                 } else {
                     kind.display_name().to_string()
                 };
+                if context.section_titles && is_theorem_like(kind) {
+                    if let Some(section) = &context.current_section {
+                        display = format!("{} (in Section: {})", display, section);
+                    }
+                }
 
                 let html_id = label_to_id(lbl);
 
@@ -84,16 +156,26 @@ fn collect_block_labels(
                     return Err(ResolutionError::DuplicateLabel(lbl.clone()).into());
                 }
 
-                labels.insert(lbl.clone(), LabelInfo { display, html_id });
+                labels.insert(
+                    lbl.clone(),
+                    LabelInfo {
+                        full: display.clone(),
+                        display,
+                        number,
+                        html_id,
+                        preview: first_sentence(content),
+                    },
+                );
             }
             for block in content {
-                collect_block_labels(block, labels, section_numbers, env_numbers)?;
+                collect_block_labels(block, labels, context)?;
             }
         }
         Block::Table {
             label: Some(lbl), ..
         } => {
-            let display = if let Some(num) = env_numbers.get(lbl) {
+            let number = context.env_numbers.get(lbl).cloned();
+            let display = if let Some(num) = &number {
                 format!("Table {}", num)
             } else {
                 "Table".to_string()
@@ -105,17 +187,26 @@ fn collect_block_labels(
                 return Err(ResolutionError::DuplicateLabel(lbl.clone()).into());
             }
 
-            labels.insert(lbl.clone(), LabelInfo { display, html_id });
+            labels.insert(
+                lbl.clone(),
+                LabelInfo {
+                    full: display.clone(),
+                    display,
+                    number,
+                    html_id,
+                    preview: None,
+                },
+            );
         }
         Block::BlockQuote(blocks) => {
             for block in blocks {
-                collect_block_labels(block, labels, section_numbers, env_numbers)?;
+                collect_block_labels(block, labels, context)?;
             }
         }
         Block::List { items, .. } => {
             for item in items {
                 for block in &item.content {
-                    collect_block_labels(block, labels, section_numbers, env_numbers)?;
+                    collect_block_labels(block, labels, context)?;
                 }
             }
         }
@@ -125,13 +216,50 @@ fn collect_block_labels(
     Ok(())
 }
 
-/// Collect footnote definitions from the document.
+/// The first sentence of the first paragraph among `blocks`, if any.
+fn first_sentence(blocks: &[Block]) -> Option<String> {
+    let text = blocks.iter().find_map(|block| match block {
+        Block::Paragraph(inlines)
+        | Block::AttributedParagraph {
+            content: inlines, ..
+        } => Some(inlines_to_text(inlines)),
+        _ => None,
+    })?;
+    let text = text.trim();
+    let end = text
+        .match_indices(['.', '!', '?'])
+        .map(|(i, _)| i + 1)
+        .find(|&i| text[i..].chars().next().is_none_or(char::is_whitespace))
+        .unwrap_or(text.len());
+    let sentence = text[..end].trim();
+    (!sentence.is_empty()).then(|| sentence.to_string())
+}
+
+/// Whether references to this environment may name their section.
+fn is_theorem_like(kind: &EnvironmentKind) -> bool {
+    matches!(
+        kind,
+        EnvironmentKind::Theorem
+            | EnvironmentKind::Lemma
+            | EnvironmentKind::Proposition
+            | EnvironmentKind::Corollary
+            | EnvironmentKind::Definition
+            | EnvironmentKind::Conjecture
+            | EnvironmentKind::Axiom
+    )
+}
+
+/// Collect footnote contents from the document: inline footnotes under
+/// `^[N]` ids (numbered in order), and referenced definitions
+/// (`[^id]: ...`) under their own ids. Definition ids cannot contain
+/// brackets, so the two never collide.
 pub fn collect_footnotes(document: &Document) -> Result<HashMap<String, Vec<Inline>>> {
     let mut footnotes = HashMap::new();
     let mut counter = 1;
+    let definitions = &document.metadata.footnote_definitions;
 
     for block in &document.blocks {
-        collect_block_footnotes(block, &mut footnotes, &mut counter)?;
+        collect_block_footnotes(block, &mut footnotes, definitions, &mut counter)?;
     }
 
     Ok(footnotes)
@@ -140,34 +268,59 @@ pub fn collect_footnotes(document: &Document) -> Result<HashMap<String, Vec<Inli
 fn collect_block_footnotes(
     block: &Block,
     footnotes: &mut HashMap<String, Vec<Inline>>,
+    definitions: &HashMap<String, Vec<Inline>>,
     counter: &mut u32,
 ) -> Result<()> {
     match block {
-        Block::Paragraph(inlines) => {
-            collect_inline_footnotes(inlines, footnotes, counter)?;
+        Block::Paragraph(inlines)
+        | Block::AttributedParagraph {
+            content: inlines, ..
+        } => {
+            collect_inline_footnotes(inlines, footnotes, definitions, counter)?;
         }
         Block::Heading { content, .. } => {
-            collect_inline_footnotes(content, footnotes, counter)?;
+            collect_inline_footnotes(content, footnotes, definitions, counter)?;
         }
         Block::Environment {
             content, caption, ..
         } => {
             for block in content {
-                collect_block_footnotes(block, footnotes, counter)?;
+                collect_block_footnotes(block, footnotes, definitions, counter)?;
             }
             if let Some(caption) = caption {
-                collect_inline_footnotes(caption, footnotes, counter)?;
+                collect_inline_footnotes(caption, footnotes, definitions, counter)?;
             }
         }
         Block::BlockQuote(blocks) => {
             for block in blocks {
-                collect_block_footnotes(block, footnotes, counter)?;
+                collect_block_footnotes(block, footnotes, definitions, counter)?;
             }
         }
         Block::List { items, .. } => {
             for item in items {
                 for block in &item.content {
-                    collect_block_footnotes(block, footnotes, counter)?;
+                    collect_block_footnotes(block, footnotes, definitions, counter)?;
+                }
+            }
+        }
+        Block::Table {
+            headers,
+            rows,
+            caption,
+            ..
+        } => {
+            for cell in headers.iter().chain(rows.iter().flatten()) {
+                collect_inline_footnotes(cell, footnotes, definitions, counter)?;
+            }
+            if let Some(caption) = caption {
+                collect_inline_footnotes(caption, footnotes, definitions, counter)?;
+            }
+        }
+        Block::DescriptionList(items) => {
+            for item in items {
+                collect_inline_footnotes(&item.term, footnotes, definitions, counter)?;
+                for block in &item.description {
+                    collect_block_footnotes(block, footnotes, definitions, counter)?;
                 }
             }
         }
@@ -180,24 +333,26 @@ fn collect_block_footnotes(
 fn collect_inline_footnotes(
     inlines: &[Inline],
     footnotes: &mut HashMap<String, Vec<Inline>>,
+    definitions: &HashMap<String, Vec<Inline>>,
     counter: &mut u32,
 ) -> Result<()> {
     for inline in inlines {
         match inline {
             Inline::Footnote(FootnoteKind::Inline(content)) => {
-                let id = format!("fn-{}", counter);
+                let id = format!("^[{}]", counter);
                 footnotes.insert(id, content.clone());
                 *counter += 1;
             }
-            Inline::Emphasis(inlines)
-            | Inline::Strong(inlines)
-            | Inline::Strikethrough(inlines) => {
-                collect_inline_footnotes(inlines, footnotes, counter)?;
+            Inline::Footnote(FootnoteKind::Reference(id)) => {
+                if let Some(content) = definitions.get(id) {
+                    footnotes.insert(id.clone(), content.clone());
+                }
             }
-            Inline::Link { content, .. } => {
-                collect_inline_footnotes(content, footnotes, counter)?;
+            _ => {
+                if let Some(inner) = inline.children() {
+                    collect_inline_footnotes(inner, footnotes, definitions, counter)?;
+                }
             }
-            _ => {}
         }
     }
 
@@ -215,6 +370,9 @@ pub fn resolve_references(
         .into_iter()
         .map(|block| resolve_block_references(block, labels, config))
         .collect::<Result<Vec<_>>>()?;
+    for inlines in document.metadata.footnote_definitions.values_mut() {
+        *inlines = resolve_inlines_references(std::mem::take(inlines), labels, config)?;
+    }
 
     Ok(document)
 }
@@ -228,6 +386,15 @@ fn resolve_block_references(
         Block::Paragraph(inlines) => Ok(Block::Paragraph(resolve_inlines_references(
             inlines, labels, config,
         )?)),
+        Block::AttributedParagraph {
+            content,
+            id,
+            classes,
+        } => Ok(Block::AttributedParagraph {
+            content: resolve_inlines_references(content, labels, config)?,
+            id,
+            classes,
+        }),
         Block::Heading {
             level,
             content,
@@ -240,11 +407,15 @@ fn resolve_block_references(
         Block::Environment {
             kind,
             label,
+            title,
+            proves,
             content,
             caption,
         } => Ok(Block::Environment {
             kind,
             label,
+            title,
+            proves,
             content: content
                 .into_iter()
                 .map(|b| resolve_block_references(b, labels, config))
@@ -283,6 +454,7 @@ fn resolve_block_references(
         Block::Table {
             headers,
             alignments,
+            widths,
             rows,
             label,
             caption,
@@ -292,6 +464,7 @@ fn resolve_block_references(
                 .map(|h| resolve_inlines_references(h, labels, config))
                 .collect::<Result<Vec<_>>>()?,
             alignments,
+            widths,
             rows: rows
                 .into_iter()
                 .map(|row| {
@@ -305,6 +478,21 @@ fn resolve_block_references(
                 .map(|c| resolve_inlines_references(c, labels, config))
                 .transpose()?,
         }),
+        Block::DescriptionList(items) => Ok(Block::DescriptionList(
+            items
+                .into_iter()
+                .map(|item| {
+                    Ok(crate::ast::DescriptionItem {
+                        term: resolve_inlines_references(item.term, labels, config)?,
+                        description: item
+                            .description
+                            .into_iter()
+                            .map(|b| resolve_block_references(b, labels, config))
+                            .collect::<Result<Vec<_>>>()?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )),
         other => Ok(other),
     }
 }
@@ -314,10 +502,90 @@ fn resolve_inlines_references(
     labels: &HashMap<String, LabelInfo>,
     config: &ResolveConfig,
 ) -> Result<Vec<Inline>> {
-    inlines
+    let mut inlines = inlines
         .into_iter()
         .map(|inline| resolve_inline_references(inline, labels, config))
-        .collect()
+        .collect::<Result<Vec<_>>>()?;
+    if config.pluralize_references {
+        pluralize_reference_runs(&mut inlines, labels);
+    }
+    Ok(inlines)
+}
+
+/// Share one plural prefix across a run of references to the same kind of
+/// target, separated by `, `, ` and `, `, and ` or `–`: "@eq:a and @eq:b"
+/// becomes "Equations (1) and (2)". Each reference keeps its own link.
+fn pluralize_reference_runs(inlines: &mut [Inline], labels: &HashMap<String, LabelInfo>) {
+    // The kind name and number text of each plain, resolved reference
+    let parts: Vec<Option<(&str, &str)>> = inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Reference {
+                label,
+                form: ReferenceForm::Default,
+                resolved: Some(_),
+            } => labels.get(label).and_then(kind_and_number),
+            _ => None,
+        })
+        .collect();
+
+    let mut i = 0;
+    while i < inlines.len() {
+        let Some((kind, _)) = parts[i] else {
+            i += 1;
+            continue;
+        };
+        let mut run = vec![i];
+        let mut next = i + 2;
+        while next < inlines.len()
+            && matches!(&inlines[next - 1], Inline::Text(sep) if matches!(sep.as_str(), ", " | " and " | ", and " | "–"))
+            && parts[next].is_some_and(|(k, _)| k == kind)
+        {
+            run.push(next);
+            next += 2;
+        }
+
+        if run.len() > 1 {
+            for (n, &index) in run.iter().enumerate() {
+                let Some((_, number)) = parts[index] else {
+                    continue;
+                };
+                let text = if n == 0 {
+                    format!("{} {}", plural(kind), number)
+                } else {
+                    number.to_string()
+                };
+                if let Inline::Reference { resolved, .. } = &mut inlines[index] {
+                    *resolved = Some(text);
+                }
+            }
+        }
+        i = run[run.len() - 1] + 1;
+    }
+}
+
+/// Split a numbered target's full text into kind name and number text:
+/// ("Equation", "(2)") or ("Figure", "1").
+fn kind_and_number(info: &LabelInfo) -> Option<(&str, &str)> {
+    let number = info.number.as_deref()?;
+    let item = if info.full != info.display {
+        info.display.as_str()
+    } else {
+        number
+    };
+    let kind = info.full.strip_suffix(item)?.trim_end();
+    (!kind.is_empty()).then_some((kind, item))
+}
+
+/// English plural of a kind name ("Figures", "Corollaries", "Appendices").
+fn plural(kind: &str) -> String {
+    if let Some(stem) = kind.strip_suffix("ix") {
+        format!("{}ices", stem)
+    } else if let Some(stem) = kind.strip_suffix('y') {
+        format!("{}ies", stem)
+    } else {
+        format!("{}s", kind)
+    }
 }
 
 fn resolve_inline_references(
@@ -326,38 +594,25 @@ fn resolve_inline_references(
     config: &ResolveConfig,
 ) -> Result<Inline> {
     match inline {
-        Inline::Reference { label, .. } => {
-            let resolved = if let Some(info) = labels.get(&label) {
-                Some(info.display.clone())
-            } else {
-                if config.strict_references {
-                    return Err(ResolutionError::UnknownReference(label.clone()).into());
-                }
-                // Leave as unresolved marker
-                Some(format!("??{}", label))
-            };
+        Inline::Reference { label, form, .. } => {
+            let resolved = labels.get(&label).map(|info| info.text(form).to_string());
+            if resolved.is_none() && config.strict_references {
+                return Err(ResolutionError::UnknownReference(label.clone()).into());
+            }
+            // Unresolved references stay `None`; renderers choose a placeholder
 
-            Ok(Inline::Reference { label, resolved })
+            Ok(Inline::Reference {
+                label,
+                form,
+                resolved,
+            })
+        }
+        mut other => {
+            if let Some(inner) = other.children_mut() {
+                *inner = resolve_inlines_references(std::mem::take(inner), labels, config)?;
+            }
+            Ok(other)
         }
-        Inline::Emphasis(inlines) => Ok(Inline::Emphasis(resolve_inlines_references(
-            inlines, labels, config,
-        )?)),
-        Inline::Strong(inlines) => Ok(Inline::Strong(resolve_inlines_references(
-            inlines, labels, config,
-        )?)),
-        Inline::Strikethrough(inlines) => Ok(Inline::Strikethrough(resolve_inlines_references(
-            inlines, labels, config,
-        )?)),
-        Inline::Link {
-            url,
-            title,
-            content,
-        } => Ok(Inline::Link {
-            url,
-            title,
-            content: resolve_inlines_references(content, labels, config)?,
-        }),
-        other => Ok(other),
     }
 }
 
@@ -376,24 +631,25 @@ pub fn label_to_id(label: &str) -> String {
 }
 
 /// Convert inline elements to plain text.
-fn inlines_to_text(inlines: &[Inline]) -> String {
+pub(crate) fn inlines_to_text(inlines: &[Inline]) -> String {
     let mut result = String::new();
 
     for inline in inlines {
         match inline {
             Inline::Text(t) => result.push_str(t),
-            Inline::Code(t) => result.push_str(t),
-            Inline::Emphasis(inner) | Inline::Strong(inner) | Inline::Strikethrough(inner) => {
-                result.push_str(&inlines_to_text(inner));
-            }
-            Inline::Link { content, .. } => {
-                result.push_str(&inlines_to_text(content));
-            }
+            Inline::Code(t) | Inline::Kbd(t) | Inline::Verbatim(t) => result.push_str(t),
+            Inline::Abbreviation { abbr, .. } => result.push_str(abbr),
             Inline::InlineMath(m) => {
                 result.push_str(m);
             }
-            Inline::SoftBreak | Inline::HardBreak => result.push(' '),
-            _ => {}
+            Inline::SoftBreak | Inline::HardBreak | Inline::NonBreakingSpace => result.push(' '),
+            // Footnote text is shown elsewhere, not in place
+            Inline::Footnote(_) => {}
+            _ => {
+                if let Some(inner) = inline.children() {
+                    result.push_str(&inlines_to_text(inner));
+                }
+            }
         }
     }
 
@@ -410,4 +666,99 @@ mod tests {
         assert_eq!(label_to_id("eq:euler"), "eq-euler");
         assert_eq!(label_to_id("fig-1"), "fig-1");
     }
+
+    #[test]
+    fn test_align_line_references() {
+        let input = r#"$$
+\begin{align}
+a &= b \label{eq:first} \\
+c &= d \label{eq:second}
+\end{align}
+$$
+
+By @eq:first and @eq:second."#;
+
+        let doc = crate::parser::parse(input).unwrap();
+        let resolved = crate::resolve::resolve(doc, &ResolveConfig::default()).unwrap();
+
+        assert_eq!(resolved.labels["eq:first"].display, "(1)");
+        assert_eq!(resolved.labels["eq:second"].display, "(2)");
+        assert_eq!(resolved.labels["eq:second"].html_id, "eq-second");
+    }
+
+    #[test]
+    fn test_reference_display_hints() {
+        let input = "$$\nx = 1\n$$ {#eq:x}\n\nSee @eq:x, @eq:x{bare} and @eq:x{full}.";
+        let doc = crate::parser::parse(input).unwrap();
+        let resolved = crate::resolve::resolve(doc, &ResolveConfig::default()).unwrap();
+
+        let Block::Paragraph(inlines) = &resolved.document.blocks[1] else {
+            panic!("expected paragraph");
+        };
+        let texts: Vec<_> = inlines
+            .iter()
+            .filter_map(|inline| match inline {
+                Inline::Reference { resolved, .. } => resolved.as_deref(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, ["(1)", "1", "Equation (1)"]);
+
+        let html = crate::render_html(&resolved, &crate::HtmlConfig::default()).unwrap();
+        assert!(html.contains(r##"<a href="#eq-x" class="mdaref">1</a> and"##));
+    }
+
+    #[test]
+    fn test_pluralized_reference_runs() {
+        let input = "$$\nx = 1\n$$ {#eq:a}\n\n$$\ny = 2\n$$ {#eq:b}\n\n\
+                     ::: figure {#fig:p}\nP\n:::\n\n\
+                     By @eq:a and @eq:b, then @eq:b, @fig:p.";
+        let config = ResolveConfig {
+            pluralize_references: true,
+            ..Default::default()
+        };
+        let resolved =
+            crate::resolve::resolve(crate::parser::parse(input).unwrap(), &config).unwrap();
+
+        let html = crate::render_html(&resolved, &crate::HtmlConfig::default()).unwrap();
+        assert!(html.contains(
+            r##"By <a href="#eq-a" class="mdaref">Equations (1)</a> and <a href="#eq-b" class="mdaref">(2)</a>, then <a href="#eq-b" class="mdaref">(2)</a>, <a href="#fig-p" class="mdaref">Figure 1</a>."##
+        ));
+
+        let resolved = crate::resolve::resolve(
+            crate::parser::parse(input).unwrap(),
+            &ResolveConfig::default(),
+        )
+        .unwrap();
+        let html = crate::render_html(&resolved, &crate::HtmlConfig::default()).unwrap();
+        assert!(html.contains(r##"<a href="#eq-a" class="mdaref">(1)</a> and"##));
+        assert_eq!(plural("Corollary"), "Corollaries");
+        assert_eq!(plural("Appendix"), "Appendices");
+    }
+
+    #[test]
+    fn test_theorem_section_titles() {
+        let input = "# Limits {#sec:limits}\n\n\
+                     ::: theorem {#thm:squeeze}\nSqueeze.\n:::\n\n\
+                     ::: figure {#fig:plot}\nPlot.\n:::\n\n\
+                     See @thm:squeeze and @fig:plot.";
+        let config = ResolveConfig {
+            theorem_section_titles: true,
+            ..Default::default()
+        };
+        let resolved =
+            crate::resolve::resolve(crate::parser::parse(input).unwrap(), &config).unwrap();
+        assert_eq!(
+            resolved.labels["thm:squeeze"].display,
+            "Theorem 1 (in Section: Limits)"
+        );
+        assert_eq!(resolved.labels["fig:plot"].display, "Figure 1");
+
+        let resolved = crate::resolve::resolve(
+            crate::parser::parse(input).unwrap(),
+            &ResolveConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(resolved.labels["thm:squeeze"].display, "Theorem 1");
+    }
 }
@@ -0,0 +1,208 @@
+//! Smart quotes: straight quotes in prose become typographic ones.
+//!
+//! Quote marks follow the language: the document's `lang`, or the enclosing
+//! `[text]{lang=fr}` span («French», „German“).
+//!
+//! Only plain text is rewritten. Code spans, math, raw HTML, link targets
+//! and citation keys and locators are separate inline kinds and keep their
+//! straight quotes, so every renderer sees the same result.
+
+use crate::ast::{Block, Document, FootnoteKind, Inline};
+
+/// Replace straight quotes in the document's text with curly ones.
+pub fn apply_smart_quotes(mut document: Document) -> Document {
+    let marks = QuoteMarks::for_lang(document.metadata.lang.as_deref());
+    quote_blocks(&mut document.blocks, marks);
+    for inlines in document.metadata.footnote_definitions.values_mut() {
+        quote_inlines(inlines, &mut None, marks);
+    }
+    document
+}
+
+/// Opening and closing marks for double and single quotes in a language.
+#[derive(Debug, Clone, Copy)]
+struct QuoteMarks {
+    open: char,
+    close: char,
+    open_single: char,
+    close_single: char,
+}
+
+impl QuoteMarks {
+    /// Marks for a language tag, by its primary subtag (`fr-CA` is French).
+    /// Unknown and missing languages get English marks.
+    fn for_lang(lang: Option<&str>) -> Self {
+        let primary = lang
+            .and_then(|lang| lang.split(['-', '_']).next())
+            .map(str::to_ascii_lowercase);
+        let (open, close, open_single, close_single) = match primary.as_deref() {
+            Some("fr") => ('«', '»', '‹', '›'),
+            Some("de") => ('„', '“', '‚', '‘'),
+            Some("ru") => ('«', '»', '„', '“'),
+            Some("es" | "it" | "pt") => ('«', '»', '“', '”'),
+            _ => ('“', '”', '‘', '’'),
+        };
+        Self {
+            open,
+            close,
+            open_single,
+            close_single,
+        }
+    }
+}
+
+fn quote_blocks(blocks: &mut [Block], marks: QuoteMarks) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines)
+            | Block::AttributedParagraph {
+                content: inlines, ..
+            }
+            | Block::Heading {
+                content: inlines, ..
+            } => quote_inlines(inlines, &mut None, marks),
+            Block::Environment {
+                content, caption, ..
+            } => {
+                quote_blocks(content, marks);
+                if let Some(caption) = caption {
+                    quote_inlines(caption, &mut None, marks);
+                }
+            }
+            Block::BlockQuote(blocks) | Block::Abstract(blocks) => quote_blocks(blocks, marks),
+            Block::List { items, .. } => {
+                for item in items {
+                    quote_blocks(&mut item.content, marks);
+                }
+            }
+            Block::Table {
+                headers,
+                rows,
+                caption,
+                ..
+            } => {
+                for cell in headers.iter_mut().chain(rows.iter_mut().flatten()) {
+                    quote_inlines(cell, &mut None, marks);
+                }
+                if let Some(caption) = caption {
+                    quote_inlines(caption, &mut None, marks);
+                }
+            }
+            Block::DescriptionList(items) => {
+                for item in items {
+                    quote_inlines(&mut item.term, &mut None, marks);
+                    quote_blocks(&mut item.description, marks);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Rewrite quotes in a run of inlines. `prev` is the character before the
+/// run (`None` at the start of a paragraph), which decides whether a quote
+/// opens or closes; it carries across inline boundaries.
+fn quote_inlines(inlines: &mut [Inline], prev: &mut Option<char>, marks: QuoteMarks) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => {
+                if text.contains(['"', '\'']) {
+                    *text = quote_text(text, prev, marks);
+                } else if let Some(last) = text.chars().last() {
+                    *prev = Some(last);
+                }
+            }
+            Inline::Emphasis(inner)
+            | Inline::Strong(inner)
+            | Inline::Strikethrough(inner)
+            | Inline::Subscript(inner)
+            | Inline::Superscript(inner)
+            | Inline::SmallCaps(inner)
+            | Inline::Link { content: inner, .. } => quote_inlines(inner, prev, marks),
+            Inline::Lang { lang, content, .. } => {
+                // A direction-only span keeps the surrounding quote marks
+                let marks = match lang {
+                    Some(lang) => QuoteMarks::for_lang(Some(lang)),
+                    None => marks,
+                };
+                quote_inlines(content, prev, marks)
+            }
+            // A footnote is its own paragraph
+            Inline::Footnote(FootnoteKind::Inline(inner)) => quote_inlines(inner, &mut None, marks),
+            Inline::SoftBreak | Inline::HardBreak | Inline::NonBreakingSpace => *prev = Some(' '),
+            // Code, math, citations and the like read as a word
+            _ => *prev = Some('x'),
+        }
+    }
+}
+
+fn quote_text(text: &str, prev: &mut Option<char>, marks: QuoteMarks) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let opens = prev.is_none_or(|p| {
+            p.is_whitespace() || "([{-–—/".contains(p) || p == marks.open || p == marks.open_single
+        });
+        // Inside a word (`l'homme`, `don't`) a single quote is an apostrophe
+        let apostrophe = prev.is_some_and(char::is_alphanumeric)
+            && chars.peek().is_some_and(|next| next.is_alphanumeric());
+        let curled = match c {
+            '"' if opens => marks.open,
+            '"' => marks.close,
+            '\'' if apostrophe => '’',
+            '\'' if opens => marks.open_single,
+            '\'' => marks.close_single,
+            other => other,
+        };
+        result.push(curled);
+        *prev = Some(curled);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_smart_quotes_skip_code_and_math() {
+        let doc = parse(r#"She said "it's `x = "y"`" and $f'(x)$ isn't 'quoted'."#).unwrap();
+        let doc = apply_smart_quotes(doc);
+        let Block::Paragraph(inlines) = &doc.blocks[0] else {
+            panic!("Expected paragraph");
+        };
+        let text: String = inlines
+            .iter()
+            .map(|inline| match inline {
+                Inline::Text(text) => text.clone(),
+                Inline::Code(code) => format!("`{}`", code),
+                Inline::InlineMath(math) => format!("${}$", math),
+                other => panic!("Unexpected inline {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            text,
+            "She said “it’s `x = \"y\"`” and $f'(x)$ isn’t ‘quoted’."
+        );
+    }
+
+    #[test]
+    fn test_quotes_follow_lang() {
+        let text = |source: &str| {
+            let doc = apply_smart_quotes(parse(source).unwrap());
+            let Block::Paragraph(inlines) = &doc.blocks[0] else {
+                panic!("Expected paragraph");
+            };
+            crate::resolve::references::inlines_to_text(inlines)
+        };
+        assert_eq!(
+            text(r#"He said "hi" and [il dit "l'homme"]{lang=fr}."#),
+            "He said “hi” and il dit «l’homme»."
+        );
+        assert_eq!(
+            text("+++\nlang = \"de-AT\"\n+++\n\"Hallo\" and 'hi'"),
+            "„Hallo“ and ‚hi‘"
+        );
+    }
+}
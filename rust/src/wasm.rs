@@ -154,9 +154,12 @@ pub fn validate_document(input: &str) -> Result<JsValue, JsError> {
 
     // Try to resolve
     let resolve_config = ResolveConfig::default();
-    if let Err(e) = resolve(doc, &resolve_config) {
-        result.valid = false;
-        result.errors.push(format!("Resolution error: {}", e));
+    match resolve(doc, &resolve_config) {
+        Ok(resolved) => result.warnings = resolved.warnings,
+        Err(e) => {
+            result.valid = false;
+            result.errors.push(format!("Resolution error: {}", e));
+        }
     }
 
     serde_wasm_bindgen::to_value(&result)
@@ -286,6 +289,7 @@ impl RenderOptions {
             custom_css: self.custom_css.clone(),
             include_toc: self.include_toc,
             class_prefix: self.class_prefix.clone(),
+            ..Default::default()
         }
     }
 }
@@ -360,7 +364,12 @@ impl DocumentInfo {
         let metadata = MetadataInfo {
             title: doc.metadata.title.clone(),
             subtitle: doc.metadata.subtitle.clone(),
-            authors: doc.metadata.authors.clone(),
+            authors: doc
+                .metadata
+                .authors
+                .iter()
+                .map(|a| a.name.clone())
+                .collect(),
             date: doc.metadata.date.clone(),
             keywords: doc.metadata.keywords.clone(),
             institution: doc.metadata.institution.clone(),
@@ -423,7 +432,10 @@ impl DocumentInfo {
                         });
                     }
                 }
-                Block::Paragraph(inlines) => {
+                Block::Paragraph(inlines)
+                | Block::AttributedParagraph {
+                    content: inlines, ..
+                } => {
                     let preview = Self::inline_preview(inlines);
                     stats.word_count += Self::count_words(&preview);
                     Self::count_inline_elements(inlines, stats);
@@ -473,6 +485,7 @@ impl DocumentInfo {
                             "table"
                         }
                         EnvironmentKind::Algorithm => "algorithm",
+                        EnvironmentKind::Listing => "listing",
                         EnvironmentKind::Abstract => "abstract",
                         EnvironmentKind::Note => "note",
                         EnvironmentKind::Warning => "warning",
@@ -577,10 +590,11 @@ impl DocumentInfo {
             match inline {
                 Inline::Citation(_) => stats.citation_count += 1,
                 Inline::Footnote(_) => stats.footnote_count += 1,
-                Inline::Emphasis(inner) | Inline::Strong(inner) => {
-                    Self::count_inline_elements(inner, stats);
+                _ => {
+                    if let Some(inner) = inline.children() {
+                        Self::count_inline_elements(inner, stats);
+                    }
                 }
-                _ => {}
             }
         }
     }